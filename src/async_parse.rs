@@ -0,0 +1,63 @@
+use std::{collections::HashSet, path::Path, sync::Arc};
+
+use crate::{BDBDump, DBKey, ParseMode, Result, ZcashdDump, ZcashdParser, ZcashdWallet};
+
+/// Runs `f` on tokio's blocking thread pool, so that it does not hold up the
+/// runtime's worker threads. A panic in `f` is resumed in the caller.
+async fn run_blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    match tokio::task::spawn_blocking(f).await {
+        Ok(value) => value,
+        Err(err) => std::panic::resume_unwind(err.into_panic()),
+    }
+}
+
+impl ZcashdDump {
+    /// Runs `db_dump` against the wallet file at `path` and reads its output,
+    /// as [`BDBDump::from_file`] followed by
+    /// [`from_bdb_dump`](Self::from_bdb_dump) with strict key parsing, without
+    /// blocking the async runtime.
+    pub async fn from_file_async(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        run_blocking(move || Self::from_bdb_dump(&BDBDump::from_file(&path)?, true)).await
+    }
+}
+
+impl ZcashdParser<'_> {
+    /// [`parse_dump`](Self::parse_dump) for async callers: the parse runs on
+    /// tokio's blocking thread pool, so a large wallet does not stall the
+    /// runtime. Pass an `Arc` to keep using the dump afterwards.
+    pub async fn parse_async(
+        dump: impl Into<Arc<ZcashdDump>>,
+        mode: impl Into<ParseMode>,
+    ) -> Result<(ZcashdWallet, HashSet<DBKey>)> {
+        let dump = dump.into();
+        let mode = mode.into();
+        run_blocking(move || ZcashdParser::parse_dump(&dump, mode)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        ZcashdParser,
+        test_support::{dump_from_records, minimal_wallet_dump},
+    };
+
+    #[tokio::test]
+    async fn test_parse_async() {
+        let dump = Arc::new(minimal_wallet_dump());
+        let (wallet, unparsed) = ZcashdParser::parse_async(dump.clone(), false)
+            .await
+            .unwrap();
+        let (expected, expected_unparsed) = ZcashdParser::parse_dump(&dump, false).unwrap();
+
+        assert_eq!(unparsed, expected_unparsed);
+        assert_eq!(wallet.keys(), expected.keys());
+        assert_eq!(wallet.bestblock(), expected.bestblock());
+
+        let incomplete = dump_from_records(&[("version", vec![], vec![0x01])]);
+        assert!(ZcashdParser::parse_async(incomplete, true).await.is_err());
+    }
+}
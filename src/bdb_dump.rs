@@ -1,10 +1,15 @@
 use std::{
     collections::HashMap,
+    io::BufRead,
     path::Path,
     process::{Command, Stdio},
 };
 
-use crate::{error::DumpInconsistency, Error, Result};
+use crate::{
+    Error, Result,
+    error::DumpInconsistency,
+    zcashd_dump::{DBKey, DBValue},
+};
 use zewif::Data;
 
 pub struct BDBDump {
@@ -40,77 +45,322 @@ impl BDBDump {
             });
         }
 
-        // Convert the stdout to a string for parsing
-        let stdout = String::from_utf8_lossy(&output.stdout);
+        Self::from_reader(output.stdout.as_slice())
+    }
 
-        // Initialize HashMaps to hold header and data records
-        let mut header_records: HashMap<String, String> = HashMap::new();
+    /// Reads the complete output of `db_dump` into memory.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self> {
+        let mut records = RawRecords::new(reader);
         let mut data_records: HashMap<Data, Data> = HashMap::new();
+        let mut records_count = 0;
+        for record in records.by_ref() {
+            let (key, value) = record?;
+            data_records.insert(key, value);
+            records_count += 1;
+        }
+
+        if records_count != data_records.len() {
+            return Err(Error::DumpInconsistency {
+                reason: DumpInconsistency::NonUniqueKeys,
+            });
+        }
 
-        // Flag to indicate if we're past the header
-        let mut in_data_section = false;
+        Ok(BDBDump {
+            header_records: records.header_records,
+            data_records,
+        })
+    }
 
-        // Temporary variable to hold the key
-        let mut current_key: Option<Data> = None;
+    /// Iterates over the records in `db_dump` output as they are read, so
+    /// that a wallet can be processed without holding the whole dump in
+    /// memory.
+    ///
+    /// Header lines are skipped. Unlike [`from_reader`](Self::from_reader),
+    /// duplicate keys are not detected, since that would require remembering
+    /// every key seen.
+    pub fn records_streaming(
+        reader: impl BufRead,
+    ) -> impl Iterator<Item = Result<(DBKey, DBValue)>> {
+        RawRecords::new(reader).map(|record| {
+            let (key, value) = record?;
+            Ok((DBKey::parse_data(&key)?, DBValue::new(value)))
+        })
+    }
+}
 
-        let mut records_count = 0;
+/// Reads raw key/value pairs from `db_dump` output one line at a time,
+/// collecting the header as it goes.
+struct RawRecords<R> {
+    reader: R,
+    header_records: HashMap<String, String>,
+    in_data_section: bool,
+    finished: bool,
+}
+
+impl<R: BufRead> RawRecords<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            header_records: HashMap::new(),
+            in_data_section: false,
+            finished: false,
+        }
+    }
 
-        // Iterate over each line of the db_dump output
-        for line in stdout.lines() {
+    // Returns the next data line, or `None` at the end of the data section.
+    // `db_dump` ends every line with a newline and the data with `DATA=END`,
+    // so output missing either was cut short.
+    fn next_data_line(&mut self) -> Result<Option<String>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 || !line.ends_with('\n') {
+                return Err(Error::DumpInconsistency {
+                    reason: DumpInconsistency::Truncated,
+                });
+            }
             let trimmed = line.trim();
 
             // Check for the end of the header section
             if trimmed == "HEADER=END" {
-                in_data_section = true;
+                check_header(&self.header_records)?;
+                self.in_data_section = true;
                 continue;
             }
 
             // Parse header lines
-            if !in_data_section {
-                if let Some(eq_pos) = trimmed.find('=') {
-                    let key = &trimmed[..eq_pos];
-                    let value = &trimmed[eq_pos + 1..];
-                    header_records.insert(key.to_string(), value.to_string());
+            if !self.in_data_section {
+                if let Some((key, value)) = trimmed.split_once('=') {
+                    self.header_records
+                        .insert(key.to_string(), value.to_string());
                 } else {
-                    eprintln!("Invalid header line: {}", trimmed);
+                    return Err(Error::DumpInconsistency {
+                        reason: DumpInconsistency::InvalidHeaderLine,
+                    });
                 }
                 continue;
             }
 
-            if line.starts_with("DATA=END") {
-                break;
+            if trimmed.starts_with("DATA=END") {
+                return Ok(None);
             }
 
-            // Each data entry line starts with a space; remove it
-            let hex_str = trimmed.trim_start_matches(' ');
-
-            // Decode the hexadecimal string
-            let bytes = Data::from_hex(hex_str)?;
-
-            // Alternate between key and value
-            if current_key.is_none() {
-                current_key = Some(bytes);
-            } else {
-                let key = current_key.take().unwrap();
-                let value = bytes;
-                data_records.insert(key, value);
-                records_count += 1;
-            }
+            return Ok(Some(trimmed.to_string()));
         }
+    }
 
-        // Check if there was an unmatched key without a corresponding value
-        if current_key.is_some() {
+    fn next_record(&mut self) -> Result<Option<(Data, Data)>> {
+        // Data lines alternate between key and value
+        let Some(key) = self.next_data_line()? else {
+            return Ok(None);
+        };
+        let Some(value) = self.next_data_line()? else {
             return Err(Error::DumpInconsistency {
                 reason: DumpInconsistency::UnmatchedKeyValue,
             });
+        };
+        Ok(Some((Data::from_hex(&key)?, Data::from_hex(&value)?)))
+    }
+}
+
+impl<R: BufRead> Iterator for RawRecords<R> {
+    type Item = Result<(Data, Data)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
         }
+        let record = self.next_record().transpose();
+        if !matches!(record, Some(Ok(_))) {
+            self.finished = true;
+        }
+        record
+    }
+}
 
-        if records_count != data_records.len() {
-            return Err(Error::DumpInconsistency {
-                reason: DumpInconsistency::NonUniqueKeys,
-            });
+/// Rejects dumps whose records would be misread: anything other than the
+/// hex-encoded `bytevalue` format, a B-tree database, or little-endian byte
+/// order. Fields that `db_dump` did not write are not checked.
+fn check_header(header_records: &HashMap<String, String>) -> Result<()> {
+    for (field, expected, reason) in [
+        ("format", "bytevalue", DumpInconsistency::UnsupportedFormat),
+        ("type", "btree", DumpInconsistency::UnsupportedAccessMethod),
+        ("db_lorder", "1234", DumpInconsistency::UnsupportedByteOrder),
+    ] {
+        if header_records
+            .get(field)
+            .is_some_and(|value| value != expected)
+        {
+            return Err(Error::DumpInconsistency { reason });
         }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Read};
+
+    use super::BDBDump;
+    use crate::{Error, error::DumpInconsistency, test_support::peak_allocation};
+
+    const HEADER: &str = "VERSION=3\nformat=bytevalue\ntype=btree\nHEADER=END\n";
+
+    /// Produces `db_dump` output for `count` records on demand, so the full
+    /// dump never exists in memory.
+    struct SyntheticDump {
+        count: usize,
+        next: usize,
+        pending: Vec<u8>,
+    }
+
+    impl SyntheticDump {
+        fn new(count: usize) -> Self {
+            Self {
+                count,
+                next: 0,
+                pending: HEADER.as_bytes().to_vec(),
+            }
+        }
+    }
+
+    impl Read for SyntheticDump {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.pending.is_empty() {
+                if self.next < self.count {
+                    // Key: CompactSize-prefixed "pool" followed by the index.
+                    let mut key = vec![4];
+                    key.extend_from_slice(b"pool");
+                    key.extend_from_slice(&(self.next as i64).to_le_bytes());
+                    self.pending =
+                        format!(" {}\n {}\n", hex::encode(key), "00".repeat(64)).into_bytes();
+                    self.next += 1;
+                } else if self.next == self.count {
+                    self.pending = b"DATA=END\n".to_vec();
+                    self.next += 1;
+                } else {
+                    return Ok(0);
+                }
+            }
+            let n = buf.len().min(self.pending.len());
+            buf[..n].copy_from_slice(&self.pending[..n]);
+            self.pending.drain(..n);
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_records_streaming_large_dump() {
+        let count = 200_000;
+        let (seen, peak) = peak_allocation(|| {
+            let reader = BufReader::new(SyntheticDump::new(count));
+            let mut seen = 0;
+            for record in BDBDump::records_streaming(reader) {
+                let (key, value) = record.unwrap();
+                assert_eq!(key.keyname, "pool");
+                assert_eq!(value.len(), 64);
+                seen += 1;
+            }
+            seen
+        });
+        assert_eq!(seen, count);
+        // The dump itself is about 30 MB; streaming holds one record at a time.
+        assert!(
+            peak < 1 << 16,
+            "streaming allocated {peak} bytes at its peak"
+        );
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let dump = BDBDump::from_reader(BufReader::new(SyntheticDump::new(10))).unwrap();
+        assert_eq!(
+            dump.header_records.get("type").map(String::as_str),
+            Some("btree")
+        );
+        assert_eq!(dump.data_records.len(), 10);
+    }
+
+    #[test]
+    fn test_unmatched_key_is_an_error() {
+        let output = format!("{HEADER} 04706f6f6c\nDATA=END\n");
+        let mut records = BDBDump::records_streaming(output.as_bytes());
+        assert!(matches!(
+            records.next(),
+            Some(Err(Error::DumpInconsistency { .. }))
+        ));
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn test_duplicate_keys_are_an_error() {
+        let output = format!("{HEADER} 04706f6f6c\n 00\n 04706f6f6c\n 01\nDATA=END\n");
+        assert!(matches!(
+            BDBDump::from_reader(output.as_bytes()),
+            Err(Error::DumpInconsistency {
+                reason: DumpInconsistency::NonUniqueKeys
+            })
+        ));
+    }
+
+    #[test]
+    fn test_truncated_dump_is_an_error() {
+        let complete = format!("{HEADER} 04706f6f6c\n 00112233\nDATA=END\n");
+        assert!(BDBDump::from_reader(complete.as_bytes()).is_ok());
+
+        // Cut off in the middle of the value, and just before `DATA=END`.
+        for truncated in [
+            &complete[..complete.len() - 13],
+            &complete[..complete.len() - 9],
+        ] {
+            assert!(matches!(
+                BDBDump::from_reader(truncated.as_bytes()),
+                Err(Error::DumpInconsistency {
+                    reason: DumpInconsistency::Truncated
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_header_line_is_an_error() {
+        let output = "VERSION=3\nformat=bytevalue\nnot a header\nHEADER=END\nDATA=END\n";
+        assert!(matches!(
+            BDBDump::from_reader(output.as_bytes()),
+            Err(Error::DumpInconsistency {
+                reason: DumpInconsistency::InvalidHeaderLine
+            })
+        ));
+    }
+
+    #[test]
+    fn test_big_endian_dump_is_rejected() {
+        let output = "VERSION=3\nformat=bytevalue\ntype=btree\ndb_lorder=4321\nHEADER=END\n\
+                      04706f6f6c\n 00\nDATA=END\n";
+        assert!(matches!(
+            BDBDump::from_reader(output.as_bytes()),
+            Err(Error::DumpInconsistency {
+                reason: DumpInconsistency::UnsupportedByteOrder
+            })
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_dump_header_is_rejected() {
+        let print_format = "VERSION=3\nformat=print\ntype=btree\nHEADER=END\nDATA=END\n";
+        assert!(matches!(
+            BDBDump::records_streaming(print_format.as_bytes()).next(),
+            Some(Err(Error::DumpInconsistency {
+                reason: DumpInconsistency::UnsupportedFormat
+            }))
+        ));
 
-        Ok(BDBDump { header_records, data_records })
+        let hash = "VERSION=3\nformat=bytevalue\ntype=hash\nHEADER=END\nDATA=END\n";
+        assert!(matches!(
+            BDBDump::from_reader(hash.as_bytes()),
+            Err(Error::DumpInconsistency {
+                reason: DumpInconsistency::UnsupportedAccessMethod
+            })
+        ));
     }
 }
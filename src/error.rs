@@ -30,6 +30,11 @@ pub enum Error {
     #[error(transparent)]
     Zewif(#[from] zewif::Error),
 
+    /// JSON serialization failures.
+    #[cfg(feature = "serde")]
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
     /// Unified address parsing errors from `zcash_address`.
     #[error(transparent)]
     UnifiedAddressParse(#[from] zcash_address::unified::ParseError),
@@ -42,6 +47,10 @@ pub enum Error {
     #[error(transparent)]
     AddressGeneration(#[from] zcash_keys::keys::AddressGenerationError),
 
+    /// Memo bytes that are neither empty, text nor a recognised format.
+    #[error(transparent)]
+    Memo(#[from] zcash_protocol::memo::Error),
+
     /// Parser consumed less data than expected.
     #[error("buffer not fully consumed: {remaining} bytes remain")]
     BufferNotConsumed { remaining: usize },
@@ -74,6 +83,10 @@ pub enum Error {
         count: usize,
     },
 
+    /// Two wallets hold state that cannot be combined into one.
+    #[error("cannot merge wallets: {reason}")]
+    UnmergeableWallets { reason: &'static str },
+
     /// Failure running an external command.
     #[error("{command} failed: {message}")]
     CommandFailure {
@@ -86,6 +99,15 @@ pub enum Error {
     #[error("inconsistent Berkeley DB dump: {reason}")]
     DumpInconsistency { reason: DumpInconsistency },
 
+    /// A key metadata record has no key record for the same key.
+    #[error("metadata record {key} has no matching key record")]
+    UnmatchedMetadata { key: String },
+
+    /// A Berkeley DB file could not be read natively.
+    #[cfg(feature = "native-bdb")]
+    #[error("unsupported or corrupt Berkeley DB file: {reason}")]
+    InvalidDatabaseFile { reason: String },
+
     /// Boolean value outside the accepted range.
     #[error("invalid boolean value: {value}")]
     InvalidBoolean { value: u8 },
@@ -153,6 +175,44 @@ pub enum Error {
     /// CompactSize encoding used an invalid prefix/value combination.
     #[error("invalid CompactSize prefix {prefix:#04x} with value {value}")]
     InvalidCompactSize { prefix: u8, value: u64 },
+
+    /// A seed's recorded fingerprint does not match the one derived from it.
+    #[error("seed fingerprint mismatch: record has {recorded}, seed derives {derived}")]
+    SeedFingerprintMismatch { recorded: String, derived: String },
+
+    /// A mnemonic phrase has a word outside the BIP 39 wordlists or fails its
+    /// checksum.
+    #[error("mnemonic phrase has an unknown word or an invalid BIP 39 checksum")]
+    InvalidMnemonicChecksum,
+
+    /// A ZIP 32 key cannot be derived for the account index.
+    #[error("cannot derive a ZIP 32 key for account {account}")]
+    KeyDerivation { account: u32 },
+
+    /// A record was written in a version newer than this crate knows.
+    #[error("unsupported {kind} version {version}")]
+    UnsupportedVersion { kind: &'static str, version: i32 },
+
+    /// An HD keypath is not of the form `m/<index>[']/...`.
+    #[error("invalid derivation path: {path}")]
+    InvalidDerivationPath { path: String },
+
+    /// A line of a `z_exportwallet` dump cannot be imported.
+    #[error("invalid export line {line}: {reason}")]
+    InvalidExportLine { line: usize, reason: String },
+
+    /// A `networkinfo` record names a network other than main, test or regtest.
+    #[error("unknown network identifier: {identifier}")]
+    UnknownNetwork { identifier: String },
+
+    /// No Merkle path can be built for a note commitment, usually because the
+    /// wallet did not mark its position in the note commitment tree.
+    #[error("no witness available for the note commitment at position {position}")]
+    WitnessUnavailable { position: u64 },
+
+    /// Every error collected while parsing in `StrictCollectAll` mode.
+    #[error("{} errors encountered while parsing", .errors.len())]
+    Multiple { errors: Vec<Error> },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -210,6 +270,16 @@ impl<T> OptionExt<T> for Option<T> {
 pub enum DumpInconsistency {
     UnmatchedKeyValue,
     NonUniqueKeys,
+    /// The dump ends partway through a line or before `DATA=END`.
+    Truncated,
+    /// A line before `HEADER=END` is not a `field=value` pair.
+    InvalidHeaderLine,
+    /// The header's `format` is not the hex-encoded `bytevalue`.
+    UnsupportedFormat,
+    /// The header's `type` is not `btree`.
+    UnsupportedAccessMethod,
+    /// The header's `db_lorder` is not little-endian.
+    UnsupportedByteOrder,
 }
 
 impl fmt::Display for DumpInconsistency {
@@ -221,6 +291,21 @@ impl fmt::Display for DumpInconsistency {
             DumpInconsistency::NonUniqueKeys => {
                 write!(f, "non-unique keys detected")
             }
+            DumpInconsistency::Truncated => {
+                write!(f, "dump is truncated; db_dump may have been interrupted")
+            }
+            DumpInconsistency::InvalidHeaderLine => {
+                write!(f, "header line is not a field=value pair")
+            }
+            DumpInconsistency::UnsupportedFormat => {
+                write!(f, "unsupported format; expected bytevalue")
+            }
+            DumpInconsistency::UnsupportedAccessMethod => {
+                write!(f, "unsupported access method; expected btree")
+            }
+            DumpInconsistency::UnsupportedByteOrder => {
+                write!(f, "unsupported byte order; expected little-endian (1234)")
+            }
         }
     }
 }
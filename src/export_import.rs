@@ -0,0 +1,310 @@
+use std::{collections::HashMap, path::Path};
+
+use chrono::DateTime;
+use zcash_protocol::consensus::NetworkConstants;
+use zewif::{Bip39Mnemonic, Network, sapling::SaplingIncomingViewingKey};
+
+use crate::{
+    Error, Result, ZcashdWallet,
+    migrate::primitives::address_encoding_network,
+    zcashd_wallet::{
+        Address, AddressNames, KeyMetadata, NetworkInfo, SecondsSinceEpoch,
+        sapling::{SaplingKey, SaplingKeys},
+        transparent::{KeyPair, Keys, PubKey},
+    },
+};
+
+impl ZcashdWallet {
+    /// Reads the dump file written by zcashd's `z_exportwallet` into a
+    /// wallet. See [`ZcashdWallet::from_export_str`].
+    pub fn from_export_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_export_str(&std::fs::read_to_string(path)?)
+    }
+
+    /// Reads the text of a `z_exportwallet` dump into a wallet holding only
+    /// what the dump records: transparent keys given in WIF, Sapling extended
+    /// spending keys, the labels of transparent addresses and the recovery
+    /// phrase of mnemonic wallets.
+    ///
+    /// The network is taken from the keys, and every key must belong to the
+    /// same one. Sprout keys cannot be imported, as the wallet keys them by a
+    /// payment address the dump does not give.
+    pub fn from_export_str(text: &str) -> Result<Self> {
+        let mut import = ExportImport::default();
+        for (index, line) in text.lines().enumerate() {
+            import.read_line(index + 1, line.trim())?;
+        }
+        Ok(import.into_wallet())
+    }
+}
+
+#[derive(Default)]
+struct ExportImport {
+    /// The network named by the Sapling keys.
+    network: Option<Network>,
+    /// The network named by the WIF keys, which share a prefix between the
+    /// test and regtest networks.
+    wif_network: Option<Network>,
+    keys: HashMap<PubKey, KeyPair>,
+    sapling_keys: HashMap<SaplingIncomingViewingKey, SaplingKey>,
+    address_names: HashMap<Address, String>,
+    bip39_mnemonic: Option<Bip39Mnemonic>,
+}
+
+impl ExportImport {
+    fn read_line(&mut self, number: usize, line: &str) -> Result<()> {
+        if line.is_empty() {
+            return Ok(());
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            if let Some(phrase) = comment.trim().strip_prefix("- recovery_phrase=") {
+                let phrase = phrase.trim_matches('"').to_string();
+                self.bip39_mnemonic = Some(Bip39Mnemonic::new(phrase, None));
+            }
+            return Ok(());
+        }
+        let invalid = |reason: &str| Error::InvalidExportLine {
+            line: number,
+            reason: reason.to_string(),
+        };
+
+        // `<key> <time> [label=<name>|change=1|reserve=1] # addr=<address> ...`
+        let (fields, comment) = line.split_once('#').unwrap_or((line, ""));
+        let mut fields = fields.split_whitespace();
+        let (Some(key), Some(time)) = (fields.next(), fields.next()) else {
+            return Err(invalid("expected a key and a timestamp"));
+        };
+        let create_time = DateTime::parse_from_rfc3339(time)
+            .ok()
+            .and_then(|time| u64::try_from(time.timestamp()).ok())
+            .map(SecondsSinceEpoch::from)
+            .ok_or_else(|| invalid("invalid timestamp"))?;
+        let label = fields
+            .find_map(|field| field.strip_prefix("label="))
+            .map(decode_dump_string);
+        let mut address = None;
+        let mut hd_keypath = None;
+        for field in comment.split_whitespace() {
+            let field = field.trim_matches(|c| c == '(' || c == ')');
+            if let Some(value) = field.strip_prefix("addr=") {
+                address = Some(value);
+            } else if let Some(value) = field.strip_prefix("hdkeypath=") {
+                hd_keypath = Some(value.to_string());
+            }
+        }
+        let metadata = KeyMetadata::new(Some(create_time), hd_keypath, None);
+
+        if key.starts_with("SK") || key.starts_with("ST") {
+            return Err(invalid("Sprout keys cannot be imported"));
+        }
+        if let Some((network, extsk)) = decode_sapling_key(key) {
+            self.set_network(network)
+                .ok_or_else(|| invalid("keys from more than one network"))?;
+            let key = SaplingKey::from_spending_key(extsk, metadata);
+            self.sapling_keys.insert(*key.ivk(), key);
+            return Ok(());
+        }
+        let (network, secret, compressed) =
+            decode_wif(key).ok_or_else(|| invalid("unrecognized key encoding"))?;
+        if self
+            .network
+            .is_some_and(|sapling| wif_network(sapling) != network)
+            || self.wif_network.is_some_and(|wif| wif != network)
+        {
+            return Err(invalid("keys from more than one network"));
+        }
+        self.wif_network = Some(network);
+        let keypair = KeyPair::from_secret(&secret, compressed, metadata)?;
+        if let (Some(address), Some(label)) = (address, label) {
+            self.address_names
+                .insert(Address::from(address.to_string()), label);
+        }
+        self.keys.insert(keypair.pubkey().clone(), keypair);
+        Ok(())
+    }
+
+    /// Records the network of a Sapling key, or returns `None` if it
+    /// conflicts with the keys read so far.
+    fn set_network(&mut self, network: Network) -> Option<()> {
+        if self.network.is_some_and(|current| current != network)
+            || self
+                .wif_network
+                .is_some_and(|wif| wif != wif_network(network))
+        {
+            return None;
+        }
+        self.network = Some(network);
+        Some(())
+    }
+
+    fn into_wallet(self) -> ZcashdWallet {
+        let network = self.network.or(self.wif_network).unwrap_or(Network::Main);
+        ZcashdWallet::builder()
+            .network_info(NetworkInfo::new(network))
+            .keys(Keys::new(self.keys))
+            .sapling_keys(SaplingKeys::new(self.sapling_keys))
+            .address_names(AddressNames::new(self.address_names))
+            .bip39_mnemonic(self.bip39_mnemonic)
+            .build()
+    }
+}
+
+/// The network whose WIF prefix keys for `network` use.
+fn wif_network(network: Network) -> Network {
+    match network {
+        Network::Main => Network::Main,
+        Network::Test | Network::Regtest => Network::Test,
+    }
+}
+
+fn decode_sapling_key(key: &str) -> Option<(Network, ::sapling::zip32::ExtendedSpendingKey)> {
+    [Network::Main, Network::Test, Network::Regtest]
+        .into_iter()
+        .find_map(|network| {
+            let hrp = address_encoding_network(network).hrp_sapling_extended_spending_key();
+            key.strip_prefix(hrp)?.strip_prefix('1')?;
+            let extsk = zcash_keys::encoding::decode_extended_spending_key(hrp, key).ok()?;
+            Some((network, extsk))
+        })
+}
+
+/// Decodes a WIF private key into its network, secret and whether its public
+/// key is compressed. The test network's prefix stands for regtest as well.
+fn decode_wif(key: &str) -> Option<(Network, [u8; 32], bool)> {
+    let payload = bs58::decode(key).with_check(None).into_vec().ok()?;
+    let (prefix, rest) = payload.split_first()?;
+    let network = match prefix {
+        0x80 => Network::Main,
+        0xef => Network::Test,
+        _ => return None,
+    };
+    let (secret, compressed) = match rest {
+        [secret @ .., 0x01] if secret.len() == 32 => (secret, true),
+        secret => (secret, false),
+    };
+    Some((network, secret.try_into().ok()?, compressed))
+}
+
+/// Reverses zcashd's `EncodeDumpString`, which writes bytes outside printable
+/// ASCII, and `%` itself, as `%XX`.
+fn decode_dump_string(encoded: &str) -> String {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = encoded
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        if let Some(byte) = escaped {
+            decoded.push(byte);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use zewif::Network;
+
+    use crate::{
+        Error, ZcashdWallet,
+        zcashd_wallet::{Address, KeyMetadata, sapling::SaplingKey, transparent::KeyPair},
+    };
+
+    const WIF: &str = "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn";
+
+    fn sapling_export_key(network: Network) -> String {
+        let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]);
+        SaplingKey::from_spending_key(extsk, KeyMetadata::new(None, None, None))
+            .to_export_string(network)
+    }
+
+    #[test]
+    fn test_from_export_str() {
+        let export = format!(
+            "# Wallet dump created by Zcash v5.10.0\n\
+             # * Created on 2024-01-01T00:00:00Z\n\
+             #\n\
+             # Emergency Recovery Information:\n\
+             # - recovery_phrase=\"abandon abandon abandon abandon abandon abandon abandon \
+             abandon abandon abandon abandon about\"\n\
+             \n\
+             {WIF} 2023-05-01T12:00:00Z label=my%20savings # \
+             addr=t1UYsZVJkLPeMjxEtACvSxfWuNmddpWfxzs hdkeypath=m/0'/0'/1'\n\
+             \n\
+             # Sapling keys\n\
+             {} 2023-05-02T12:00:00Z # zaddr=zs1example\n\
+             \n\
+             # End of dump\n",
+            sapling_export_key(Network::Main)
+        );
+        let wallet = ZcashdWallet::from_export_str(&export).unwrap();
+
+        assert_eq!(wallet.network(), Network::Main);
+        let keypair = wallet.keys().keypairs().next().unwrap();
+        assert_eq!(keypair.to_wif(Network::Main).unwrap(), WIF);
+        assert_eq!(
+            keypair.metadata().hd_keypath(),
+            Some(&"m/0'/0'/1'".to_string())
+        );
+        assert_eq!(
+            keypair.metadata().create_time().map(u64::from),
+            Some(1_682_942_400)
+        );
+        let address = Address::from("t1UYsZVJkLPeMjxEtACvSxfWuNmddpWfxzs".to_string());
+        assert_eq!(wallet.address_names().get(&address).unwrap(), "my savings");
+
+        let sapling_key = wallet.sapling_keys().keypairs().next().unwrap();
+        assert_eq!(
+            sapling_key.to_export_string(Network::Main),
+            sapling_export_key(Network::Main)
+        );
+        assert!(
+            wallet
+                .bip39_mnemonic()
+                .unwrap()
+                .mnemonic()
+                .ends_with("abandon about")
+        );
+    }
+
+    #[test]
+    fn test_from_export_str_rejects_mixed_networks() {
+        let export = format!(
+            "{WIF} 2023-05-01T12:00:00Z # addr=t1UYsZVJkLPeMjxEtACvSxfWuNmddpWfxzs\n\
+             {} 2023-05-02T12:00:00Z # zaddr=ztestsapling1example\n",
+            sapling_export_key(Network::Test)
+        );
+        assert!(matches!(
+            ZcashdWallet::from_export_str(&export),
+            Err(Error::InvalidExportLine { line: 2, .. })
+        ));
+
+        let mut secret = [0u8; 32];
+        secret[31] = 1;
+        let test_wif = KeyPair::from_secret(&secret, true, KeyMetadata::new(None, None, None))
+            .unwrap()
+            .to_wif(Network::Test)
+            .unwrap();
+        let export = format!(
+            "{WIF} 2023-05-01T12:00:00Z # addr=t1UYsZVJkLPeMjxEtACvSxfWuNmddpWfxzs\n\
+             {test_wif} 2023-05-02T12:00:00Z # addr=tmexample\n"
+        );
+        assert!(matches!(
+            ZcashdWallet::from_export_str(&export),
+            Err(Error::InvalidExportLine { line: 2, .. })
+        ));
+
+        let sprout = "SKxt8pwrQipUL5KgZUcBAqyLj9R1YwMuRRR3ijGMCwCCqchmi8ut \
+                      2023-05-01T12:00:00Z # zaddr=zcexample\n";
+        assert!(matches!(
+            ZcashdWallet::from_export_str(sprout),
+            Err(Error::InvalidExportLine { line: 1, .. })
+        ));
+    }
+}
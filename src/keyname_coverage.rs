@@ -0,0 +1,165 @@
+use std::fmt::Write;
+
+use crate::{KNOWN_UNSUPPORTED_KEYNAMES, ZcashdDump};
+
+/// Keynames whose records [`ZcashdParser`](crate::ZcashdParser) reads.
+pub const PARSED_KEYNAMES: &[&str] = &[
+    "acc",
+    "acentry",
+    "bestblock",
+    "bestblock_nomerkle",
+    "defaultkey",
+    "destdata",
+    "hdchain",
+    "hdseed",
+    "key",
+    "keymeta",
+    "minversion",
+    "mnemonichdchain",
+    "mnemonicphrase",
+    "name",
+    "networkinfo",
+    "orchard_note_commitment_tree",
+    "orderposnext",
+    "pool",
+    "purpose",
+    "recipientmapping",
+    "sapextfvk",
+    "sapzaddr",
+    "sapzkey",
+    "sapzkeymeta",
+    "tx",
+    "unifiedaccount",
+    "unifiedaddrmeta",
+    "unifiedfvk",
+    "version",
+    "vkey",
+    "watchs",
+    "witnesscachesize",
+    "wkey",
+    "zkey",
+    "zkeymeta",
+];
+
+/// How the parser handles a keyname.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeynameSupport {
+    /// The parser reads records under this keyname.
+    Parsed,
+    /// zcashd writes this keyname, but the parser deliberately skips it.
+    KnownUnsupported,
+    /// The keyname is not one this crate knows about.
+    Unknown,
+}
+
+impl KeynameSupport {
+    pub fn for_keyname(keyname: &str) -> Self {
+        if PARSED_KEYNAMES.contains(&keyname) {
+            KeynameSupport::Parsed
+        } else if KNOWN_UNSUPPORTED_KEYNAMES.contains(&keyname) {
+            KeynameSupport::KnownUnsupported
+        } else {
+            KeynameSupport::Unknown
+        }
+    }
+}
+
+/// The number of records under one keyname in a dump, and whether the parser
+/// handles them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeynameCoverage {
+    keyname: String,
+    count: usize,
+    support: KeynameSupport,
+}
+
+impl KeynameCoverage {
+    pub fn keyname(&self) -> &str {
+        &self.keyname
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn support(&self) -> KeynameSupport {
+        self.support
+    }
+}
+
+impl ZcashdDump {
+    /// Every keyname in the dump with its record count and how the parser
+    /// handles it, in keyname order.
+    pub fn keyname_coverage(&self) -> Vec<KeynameCoverage> {
+        self.keyname_histogram()
+            .into_iter()
+            .map(|(keyname, count)| KeynameCoverage {
+                support: KeynameSupport::for_keyname(&keyname),
+                keyname,
+                count,
+            })
+            .collect()
+    }
+
+    /// Returns `true` if the parser reads every keyname in the dump.
+    pub fn is_fully_supported(&self) -> bool {
+        self.keyname_coverage()
+            .iter()
+            .all(|entry| entry.support() == KeynameSupport::Parsed)
+    }
+
+    pub fn coverage_summary(&self) -> String {
+        let mut output = String::new();
+        for entry in self.keyname_coverage() {
+            let status = match entry.support() {
+                KeynameSupport::Parsed => "parsed",
+                KeynameSupport::KnownUnsupported => "unsupported",
+                KeynameSupport::Unknown => "unknown",
+            };
+            writeln!(
+                output,
+                "{}: {} ({})",
+                entry.keyname(),
+                entry.count(),
+                status
+            )
+            .unwrap();
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::KeynameSupport;
+    use crate::test_support::{compact, dump_from_records, minimal_wallet_records};
+
+    #[test]
+    fn test_keyname_coverage() {
+        let mut records = minimal_wallet_records();
+        records.push(("pool", 2i64.to_le_bytes().to_vec(), vec![]));
+        let dump = dump_from_records(&records);
+        let histogram = dump.keyname_histogram();
+        assert_eq!(histogram.len(), 15);
+        assert_eq!(histogram["pool"], 2);
+        assert_eq!(histogram["key"], 1);
+        assert_eq!(histogram.values().sum::<usize>(), records.len());
+        assert!(dump.is_fully_supported());
+
+        records.push(("ckey", compact(&[0x03; 33]), vec![]));
+        records.push(("mystery", vec![], vec![]));
+        let dump = dump_from_records(&records);
+        let support: BTreeMap<_, _> = dump
+            .keyname_coverage()
+            .iter()
+            .map(|entry| (entry.keyname().to_string(), entry.support()))
+            .collect();
+        assert_eq!(support["pool"], KeynameSupport::Parsed);
+        assert_eq!(support["ckey"], KeynameSupport::KnownUnsupported);
+        assert_eq!(support["mystery"], KeynameSupport::Unknown);
+        assert!(!dump.is_fully_supported());
+        assert!(dump.coverage_summary().contains("mystery: 1 (unknown)\n"));
+    }
+}
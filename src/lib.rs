@@ -4,8 +4,23 @@ pub mod error;
 pub use error::{Error, OptionExt, Result, ResultExt};
 
 mod_use!(bdb_dump);
+#[cfg(feature = "native-bdb")]
+mod bdb_file;
 mod_use!(zcashd_dump);
+mod_use!(parse_mode);
+mod_use!(parser_observer);
+mod_use!(parse_progress);
 mod_use!(zcashd_parser);
+mod_use!(unparsed_report);
+mod_use!(keyname_coverage);
+mod_use!(export_import);
+#[cfg(feature = "tokio")]
+mod async_parse;
+
+#[cfg(feature = "serde")]
+pub(crate) mod serde_support;
+#[cfg(test)]
+pub(crate) mod test_support;
 
 pub mod migrate;
 pub mod parser;
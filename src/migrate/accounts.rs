@@ -5,7 +5,8 @@ use zewif::{
 };
 
 use super::{
-    AddressId, AddressRegistry, keys::find_sapling_key_for_ivk,
+    AddressId, AddressRegistry, addresses::set_address_metadata,
+    keys::find_sapling_key_for_ivk,
     transaction_addresses::extract_transaction_addresses,
 };
 use crate::{
@@ -46,7 +47,7 @@ pub fn convert_unified_accounts(
     // Step 3: Process all addresses and assign them to the appropriate accounts
 
     // Process transparent addresses
-    for (zcashd_address, name) in wallet.address_names() {
+    for zcashd_address in wallet.address_names().keys() {
         // Create an AddressId for this transparent address
         let addr_id = AddressId::Transparent(zcashd_address.clone().into());
 
@@ -61,13 +62,7 @@ pub fn convert_unified_accounts(
             let protocol_address =
                 ProtocolAddress::Transparent(transparent_address);
             let mut zewif_address = zewif::Address::new(protocol_address);
-            zewif_address.set_name(name.clone());
-
-            // Set purpose if available
-            if let Some(purpose) = wallet.address_purposes().get(zcashd_address)
-            {
-                zewif_address.set_purpose(purpose.clone());
-            }
+            set_address_metadata(wallet, zcashd_address, &mut zewif_address);
 
             // Add the address to the account
             account.add_address(zewif_address);
@@ -108,13 +103,11 @@ pub fn convert_unified_accounts(
                 zewif::ProtocolAddress::Sapling(Box::new(shielded_address));
             let mut zewif_address = zewif::Address::new(protocol_address);
 
-            // Set purpose if available - convert to Address type for lookup
-            let zcashd_address = Address::from(address_str);
-            if let Some(purpose) =
-                wallet.address_purposes().get(&zcashd_address)
-            {
-                zewif_address.set_purpose(purpose.clone());
-            }
+            set_address_metadata(
+                wallet,
+                &Address::from(address_str),
+                &mut zewif_address,
+            );
 
             // Add the address to the account
             account.add_address(zewif_address);
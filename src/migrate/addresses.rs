@@ -1,22 +1,38 @@
 use std::collections::HashMap;
 
-use zcash_keys::keys::UnifiedAddressRequest;
 use zewif::{
     Account, ProtocolAddress, UnifiedAddress,
     sapling::SaplingExtendedSpendingKey,
 };
-use zip32::DiversifierIndex;
 
-use super::keys::find_sapling_key_for_ivk;
+use super::keys::{convert_transparent_keypair, find_sapling_key_for_ivk};
 use crate::{
-    Error, Result, ZcashdWallet,
+    Result, ZcashdWallet,
     migrate::{AddressId, AddressRegistry},
-    zcashd_wallet::{Address, ReceiverType, UfvkFingerprint},
+    zcashd_wallet::{Address, UfvkFingerprint},
 };
 
+/// Attaches the label and purpose the zcashd address book records for
+/// `address`, if any.
+pub(crate) fn set_address_metadata(
+    wallet: &ZcashdWallet,
+    address: &Address,
+    zewif_address: &mut zewif::Address,
+) {
+    if let Some(name) = wallet.address_names().get(address) {
+        zewif_address.set_name(name.clone());
+    }
+    if let Some(purpose) = wallet.address_purpose(address) {
+        zewif_address.set_purpose(purpose.to_string());
+    }
+}
+
 /// Convert ZCashd transparent addresses to Zewif format
 ///
 /// This function handles transparent address assignment:
+/// - Covers the address book entries and the addresses of the wallet's
+///   transparent keys
+/// - Carries the private key and HD derivation info of `key` records
 /// - If registry is available, tries to map addresses to accounts
 /// - Otherwise assigns all addresses to the default account
 pub(crate) fn convert_transparent_addresses(
@@ -29,20 +45,30 @@ pub(crate) fn convert_transparent_addresses(
     let multi_account_mode =
         address_registry.is_some() && accounts_map.is_some();
 
-    // Process address_names which contain transparent addresses
-    for (zcashd_address, name) in wallet.address_names() {
+    // Process address_names which contain transparent addresses, then the
+    // addresses of keys that have no address book entry
+    let key_addresses = wallet.transparent_addresses();
+    let zcashd_addresses = wallet.address_names().keys().chain(
+        key_addresses
+            .iter()
+            .filter(|address| !wallet.address_names().contains_key(address)),
+    );
+    for zcashd_address in zcashd_addresses {
         // Create address components
-        let transparent_address =
-            zewif::transparent::Address::new(zcashd_address.clone());
+        let keypair = wallet
+            .transparent_address_index()
+            .get(zcashd_address)
+            .and_then(|pubkey| wallet.keys().keypair_for_pubkey(pubkey));
+        let transparent_address = match keypair {
+            Some(keypair) => {
+                convert_transparent_keypair(keypair, wallet.network())?
+            }
+            None => zewif::transparent::Address::new(zcashd_address.clone()),
+        };
         let protocol_address =
             ProtocolAddress::Transparent(transparent_address);
         let mut zewif_address = zewif::Address::new(protocol_address);
-        zewif_address.set_name(name.clone());
-
-        // Set purpose if available
-        if let Some(purpose) = wallet.address_purposes().get(zcashd_address) {
-            zewif_address.set_purpose(purpose.clone());
-        }
+        set_address_metadata(wallet, zcashd_address, &mut zewif_address);
 
         // In multi-account mode, try to assign to the correct account
         let mut assigned = false;
@@ -110,11 +136,11 @@ pub(crate) fn convert_sapling_addresses(
             zewif::ProtocolAddress::Sapling(Box::new(shielded_address));
         let mut zewif_address = zewif::Address::new(protocol_address);
 
-        // Set purpose if available - convert to Address type for lookup
-        let zcashd_address = Address::from(address_str.clone());
-        if let Some(purpose) = wallet.address_purposes().get(&zcashd_address) {
-            zewif_address.set_purpose(purpose.clone());
-        }
+        set_address_metadata(
+            wallet,
+            &Address::from(address_str.clone()),
+            &mut zewif_address,
+        );
 
         // In multi-account mode, try to assign to the correct account
         let mut assigned = false;
@@ -166,55 +192,33 @@ pub(crate) fn convert_unified_addresses(
     // Process unified address metadata entries
     for metadata in &unified_accounts.address_metadata {
         let account = unified_accounts.account_metadata.get(&metadata.key_id);
-        let ufvk = unified_accounts
-            .full_viewing_keys
-            .get(&metadata.key_id)
-            .ok_or_else(|| Error::MissingUfvk {
-                fingerprint: metadata.key_id.to_hex(),
-            })?;
-
-        let ua_str = {
-            let j = DiversifierIndex::from(<[u8; 11]>::from(
-                metadata.diversifier_index.clone(),
-            ));
-            let Some(request) = UnifiedAddressRequest::new(
-                metadata.receiver_types.contains(&ReceiverType::P2PKH),
-                metadata.receiver_types.contains(&ReceiverType::Sapling),
-                metadata.receiver_types.contains(&ReceiverType::Orchard),
-            ) else {
-                // Skip malformed receiver combinations instead of aborting the
-                // entire migration.
-                continue;
-            };
-
-            ufvk.address(j, request)?
-                .encode(&wallet.network_info().to_address_encoding_network())
+        let Some(ua_str) =
+            unified_accounts.encode_address(metadata, wallet.network_info())?
+        else {
+            // Skip malformed receiver combinations instead of aborting the
+            // entire migration.
+            continue;
         };
 
         // Construct the unified address with its derivation metadata.
         let unified_address = UnifiedAddress::from_parts(
             ua_str.clone(),
             Some(metadata.diversifier_index.clone()),
-            account.map(|a| {
-                format!(
-                    "m/32'/{}'/{}'",
-                    a.bip_44_coin_type(),
-                    a.zip32_account_id()
-                )
-            }),
+            account.map(|a| a.hd_keypath()),
         );
 
         // Try to find transparent and sapling components for this unified
         // address from already processed addresses in the wallet
 
         // Create a unified address protocol address
-        let zewif_address = zewif::Address::new(ProtocolAddress::Unified(
+        let mut zewif_address = zewif::Address::new(ProtocolAddress::Unified(
             Box::new(unified_address),
         ));
-
-        // Set purpose if available - though we may not have explicit purposes
-        // for unified addresses in current wallet structure, this is
-        // here for future compatibility
+        set_address_metadata(
+            wallet,
+            &Address::from(ua_str.clone()),
+            &mut zewif_address,
+        );
 
         // In multi-account mode, try to assign to the correct account
         let mut assigned = false;
@@ -1,6 +1,26 @@
-use zewif::sapling::SaplingIncomingViewingKey;
+use bc_envelope::Envelope;
+use zewif::{
+    Blob32, DerivationInfo, Network, NonHardenedChildIndex,
+    sapling::SaplingIncomingViewingKey,
+    transparent::{self, SpendAuthority, TransparentSpendingKey},
+};
 
-use crate::{zcashd_wallet::sapling::SaplingKey, ZcashdWallet};
+use crate::{
+    Result, ZcashdWallet,
+    zcashd_wallet::{
+        ChildNumber, DerivationPath,
+        sapling::SaplingKey,
+        sprout::{SproutPaymentAddress, SproutSpendingKey},
+        transparent::KeyPair,
+    },
+};
+
+/// The vendor of the account attachments that carry Sprout spending keys.
+pub const SPROUT_KEY_ATTACHMENT_VENDOR: &str = "com.blockchaincommons.zewif-zcashd";
+
+/// The format of the account attachments that carry Sprout spending keys, as
+/// built by [`convert_sprout_spending_key`].
+pub const SPROUT_KEY_ATTACHMENT_FORMAT: &str = "sprout-spending-key";
 
 /// Find a SaplingKey for a given incoming viewing key
 pub fn find_sapling_key_for_ivk<'a>(
@@ -9,3 +29,69 @@ pub fn find_sapling_key_for_ivk<'a>(
 ) -> Option<&'a SaplingKey> {
     wallet.sapling_keys().get(ivk)
 }
+
+/// Convert a zcashd transparent keypair to the zewif transparent address of
+/// its public key on `network`, holding the private key and, for keys
+/// derived from the HD seed, the position the keypath records.
+pub fn convert_transparent_keypair(
+    keypair: &KeyPair,
+    network: Network,
+) -> Result<transparent::Address> {
+    let mut address = transparent::Address::new(keypair.address(network).to_string());
+    let secret = keypair.privkey().secret_key_bytes()?;
+    address.set_spend_authority(SpendAuthority::SpendingKey(TransparentSpendingKey::new(
+        Blob32::new(secret),
+    )));
+    if let Some(derivation_info) = keypair
+        .metadata()
+        .derivation_path()
+        .as_ref()
+        .and_then(derivation_info)
+    {
+        address.set_derivation_info(derivation_info);
+    }
+    Ok(address)
+}
+
+/// Convert a zcashd Sprout spending key to an envelope whose subject is the
+/// key as `z_exportkey` encodes it, so that zcashd's `z_importkey` can read
+/// it back, with the key's payment address and creation time as assertions.
+///
+/// zewif has no Sprout address or spending-key type, so
+/// [`migrate_to_zewif`](super::migrate_to_zewif) carries these envelopes as
+/// attachments on the account instead.
+pub fn convert_sprout_spending_key(
+    address: &SproutPaymentAddress,
+    key: &SproutSpendingKey,
+    network: Network,
+) -> Envelope {
+    // zcashd's Base58Check prefixes for Sprout spending keys, which encode as
+    // `SK...` on mainnet and `ST...` elsewhere.
+    let mut payload = match network {
+        Network::Main => vec![0xab, 0x36],
+        Network::Test | Network::Regtest => vec![0xac, 0x08],
+    };
+    payload.extend_from_slice(key.key().as_ref());
+    let encoded = bs58::encode(payload).with_check().into_string();
+    let mut envelope = Envelope::new(encoded).add_assertion("address", address.to_string(network));
+    if let Some(create_time) = key.metadata().create_time() {
+        envelope = envelope.add_assertion("createTime", u64::from(create_time));
+    }
+    envelope
+}
+
+/// The change and address index that end a BIP 44 keypath such as
+/// `m/44'/133'/0'/0/5`, or `None` if either step is hardened or missing.
+fn derivation_info(path: &DerivationPath) -> Option<DerivationInfo> {
+    match path.components() {
+        [
+            ..,
+            ChildNumber::Normal(change),
+            ChildNumber::Normal(address_index),
+        ] => Some(DerivationInfo::new(
+            NonHardenedChildIndex::from(*change),
+            NonHardenedChildIndex::from(*address_index),
+        )),
+        _ => None,
+    }
+}
@@ -0,0 +1,204 @@
+use ::orchard::keys::IncomingViewingKey;
+use ::sapling::{SaplingIvk, zip32::DiversifiableFullViewingKey};
+use zcash_protocol::memo::Memo;
+use zewif::TxId;
+use zip32::Scope;
+
+use crate::{
+    Error, Result, ResultExt, ZcashdWallet,
+    zcashd_wallet::{OrchardActionView, SaplingOutputView},
+};
+
+/// Decrypts the memo of output `output_index` of transaction `txid`, trying
+/// the Sapling output at that index and then the Orchard action.
+///
+/// Returns `Ok(None)` when none of the wallet's viewing keys decrypt the
+/// output, and an `Error::Context` naming the output when the decrypted memo
+/// is malformed.
+pub fn decrypt_memo(wallet: &ZcashdWallet, txid: &TxId, output_index: u32) -> Result<Option<Memo>> {
+    let tx = wallet
+        .transactions()
+        .get(txid)
+        .ok_or_else(|| Error::MissingRecord {
+            kind: "transaction",
+            key: txid.to_string(),
+        })?;
+    let index = output_index as usize;
+    let sapling_output = tx.sapling_outputs().get(index).copied();
+    let orchard_action = tx.orchard_actions().get(index).copied();
+    if sapling_output.is_none() && orchard_action.is_none() {
+        return Err(Error::MissingRecord {
+            kind: "shielded output",
+            key: format!("{txid}:{output_index}"),
+        });
+    }
+
+    let decryptor = MemoDecryptor::new(wallet);
+    let sapling_memo = sapling_output.and_then(|output| decryptor.sapling_memo_bytes(&output));
+    let memo = sapling_memo.or_else(|| decryptor.orchard_memo_bytes(&orchard_action?));
+    memo.map(|bytes| decode_memo(&bytes, txid, output_index))
+        .transpose()
+}
+
+/// The wallet's incoming viewing keys, derived once so that the memos of
+/// many outputs can be decrypted.
+pub(crate) struct MemoDecryptor {
+    sapling_ivks: Vec<SaplingIvk>,
+    orchard_ivks: Vec<IncomingViewingKey>,
+}
+
+impl MemoDecryptor {
+    pub(crate) fn new(wallet: &ZcashdWallet) -> Self {
+        Self {
+            sapling_ivks: sapling_ivks(wallet),
+            orchard_ivks: orchard_ivks(wallet),
+        }
+    }
+
+    /// The memo of a Sapling output, or `None` if no key decrypts it.
+    pub(crate) fn sapling_memo(
+        &self,
+        txid: &TxId,
+        output_index: u32,
+        output: &SaplingOutputView<'_>,
+    ) -> Result<Option<Memo>> {
+        self.sapling_memo_bytes(output)
+            .map(|bytes| decode_memo(&bytes, txid, output_index))
+            .transpose()
+    }
+
+    /// The memo of an Orchard action, or `None` if no key decrypts it.
+    pub(crate) fn orchard_memo(
+        &self,
+        txid: &TxId,
+        action_index: u32,
+        action: &OrchardActionView<'_>,
+    ) -> Result<Option<Memo>> {
+        self.orchard_memo_bytes(action)
+            .map(|bytes| decode_memo(&bytes, txid, action_index))
+            .transpose()
+    }
+
+    fn sapling_memo_bytes(&self, output: &SaplingOutputView<'_>) -> Option<[u8; 512]> {
+        self.sapling_ivks
+            .iter()
+            .find_map(|ivk| output.decrypt(ivk))
+            .map(|note| *note.memo())
+    }
+
+    fn orchard_memo_bytes(&self, action: &OrchardActionView<'_>) -> Option<[u8; 512]> {
+        action
+            .decrypt()
+            .or_else(|| {
+                self.orchard_ivks
+                    .iter()
+                    .find_map(|ivk| action.decrypt_with(ivk))
+            })
+            .map(|note| *note.memo())
+    }
+}
+
+fn decode_memo(bytes: &[u8; 512], txid: &TxId, output_index: u32) -> Result<Memo> {
+    Memo::from_bytes(bytes)
+        .with_context(|| format!("Decoding the memo of output {output_index} of {txid}"))
+}
+
+// Both scopes are tried, since zcashd sends change to internal addresses.
+pub(crate) fn sapling_ivks(wallet: &ZcashdWallet) -> Vec<SaplingIvk> {
+    let spending_keys = wallet
+        .sapling_keys()
+        .keypairs()
+        .map(|key| key.extsk().to_diversifiable_full_viewing_key());
+    let viewing_keys = wallet
+        .viewing_keys()
+        .iter()
+        .chain(wallet.sapling_extended_fvks().values())
+        .map(|extfvk| extfvk.to_diversifiable_full_viewing_key());
+    let unified_keys = wallet
+        .unified_accounts()
+        .full_viewing_keys
+        .values()
+        .filter_map(|ufvk| ufvk.sapling().cloned());
+    spending_keys
+        .chain(viewing_keys)
+        .chain(unified_keys)
+        .flat_map(|dfvk: DiversifiableFullViewingKey| {
+            [Scope::External, Scope::Internal].map(|scope| dfvk.to_ivk(scope))
+        })
+        .collect()
+}
+
+pub(crate) fn orchard_ivks(wallet: &ZcashdWallet) -> Vec<IncomingViewingKey> {
+    wallet
+        .unified_accounts()
+        .full_viewing_keys
+        .values()
+        .filter_map(|ufvk| ufvk.orchard())
+        .flat_map(|fvk| [Scope::External, Scope::Internal].map(|scope| fvk.to_ivk(scope)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use zcash_protocol::memo::Memo;
+
+    use super::decrypt_memo;
+    use crate::{
+        Error,
+        test_support::{
+            minimal_wallet_records, parse_records, sapling_key_records, sapling_output_transaction,
+            txid, wallet_tx_value,
+        },
+    };
+
+    #[test]
+    fn test_decrypt_memo() {
+        let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]);
+        let (_, own_address) = extsk.default_address();
+        let (_, other_address) =
+            ::sapling::zip32::ExtendedSpendingKey::master(&[0x08; 32]).default_address();
+        let text = "Moving to a new wallet";
+        let mut memo = [0u8; 512];
+        memo[..text.len()].copy_from_slice(text.as_bytes());
+
+        let mut records = minimal_wallet_records();
+        records.extend(sapling_key_records(&extsk));
+        // A send to the wallet's own address, and one to another wallet. Both
+        // have empty Sapling note data.
+        for (txid_byte, recipient) in [(0x10, &own_address), (0x20, &other_address)] {
+            let mut value =
+                wallet_tx_value(&sapling_output_transaction(recipient, 10_000, memo), None);
+            value.push(0);
+            records.push(("tx", vec![txid_byte; 32], value));
+        }
+        // A text memo that is not valid UTF-8.
+        let mut malformed = [0u8; 512];
+        malformed[0] = 0xc0;
+        let mut value = wallet_tx_value(
+            &sapling_output_transaction(&own_address, 10_000, malformed),
+            None,
+        );
+        value.push(0);
+        records.push(("tx", vec![0x40; 32], value));
+        let wallet = parse_records(&records);
+
+        assert_eq!(
+            decrypt_memo(&wallet, &txid(0x10), 0).unwrap(),
+            Some(Memo::from_str(text).unwrap())
+        );
+        assert_eq!(decrypt_memo(&wallet, &txid(0x20), 0).unwrap(), None);
+        assert!(matches!(
+            decrypt_memo(&wallet, &txid(0x10), 1),
+            Err(Error::MissingRecord { kind: "shielded output", .. })
+        ));
+        assert!(matches!(
+            decrypt_memo(&wallet, &txid(0x30), 0),
+            Err(Error::MissingRecord { kind: "transaction", .. })
+        ));
+        let err = decrypt_memo(&wallet, &txid(0x40), 0).unwrap_err();
+        assert!(matches!(err, Error::Context { .. }));
+        assert!(err.to_string().contains("Decoding the memo of output 0"));
+    }
+}
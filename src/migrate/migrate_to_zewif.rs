@@ -2,17 +2,28 @@ use std::collections::HashMap;
 
 use crate::Result;
 
-use crate::{ZcashdWallet, zcashd_wallet::UfvkFingerprint};
+use crate::{
+    ZcashdWallet,
+    zcashd_wallet::{UfvkFingerprint, sprout::SproutKeys},
+};
 
+use bc_envelope::prelude::*;
 use zewif::{self, Account, BlockHeight, TxId, Zewif, ZewifWallet};
 
 use super::{
-    convert_sapling_addresses, convert_seed_material, convert_transactions,
+    SPROUT_KEY_ATTACHMENT_FORMAT, SPROUT_KEY_ATTACHMENT_VENDOR, convert_sapling_addresses,
+    convert_seed_material, convert_sprout_spending_key, convert_transactions,
     convert_transparent_addresses, convert_unified_accounts, convert_unified_addresses,
     initialize_address_registry,
 };
 
 /// Migrate a ZCashd wallet to the Zewif wallet format
+///
+/// zewif has no Sprout address or spending-key type, so Sprout keys are
+/// carried as attachments on the default account, one for each key, from
+/// [`convert_sprout_spending_key`](super::convert_sprout_spending_key).
+/// Tools that do not know the attachment cannot spend Sprout funds;
+/// [`migration_report`](super::migration_report) lists the keys.
 pub fn migrate_to_zewif(wallet: &ZcashdWallet, export_height: BlockHeight) -> Result<Zewif> {
     // Create a new Zewif
     let mut zewif = Zewif::new(export_height);
@@ -107,6 +118,15 @@ pub fn migrate_to_zewif(wallet: &ZcashdWallet, export_height: BlockHeight) -> Re
         // Convert sapling addresses (single account mode)
         convert_sapling_addresses(wallet, &mut default_account, None, &mut accounts_map_ref)?;
 
+        // Carry the Sprout keys, which have no zewif address type.
+        for (address, key) in wallet.sprout_keys().into_iter().flat_map(SproutKeys::iter) {
+            default_account.add_attachment(
+                convert_sprout_spending_key(address, key, wallet.network()),
+                SPROUT_KEY_ATTACHMENT_VENDOR,
+                Some(SPROUT_KEY_ATTACHMENT_FORMAT),
+            );
+        }
+
         // Add all transaction IDs to the default account's relevant transactions
         for txid in transactions.keys() {
             default_account.add_relevant_transaction(*txid);
@@ -164,3 +184,163 @@ fn set_received_output_witnesses(
 
     //Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bc_envelope::prelude::*;
+    use zewif::{
+        Blob32, BlockHeight, Network, NonHardenedChildIndex, ProtocolAddress,
+        transparent::SpendAuthority,
+    };
+
+    use super::migrate_to_zewif;
+    use crate::{
+        ZcashdWallet,
+        migrate::{
+            AddressId, SPROUT_KEY_ATTACHMENT_FORMAT, SPROUT_KEY_ATTACHMENT_VENDOR,
+            convert_sprout_spending_key,
+        },
+        test_support::{
+            compact, minimal_wallet, minimal_wallet_records, named_address, parse_records,
+        },
+        zcashd_wallet::{
+            KeyMetadata,
+            transparent::{KeyPair, Keys},
+        },
+    };
+
+    #[test]
+    fn test_migration_keeps_address_labels() {
+        let sapling_address = minimal_wallet()
+            .sapling_z_addresses()
+            .keys()
+            .next()
+            .unwrap()
+            .to_string(Network::Main);
+        let mut records = minimal_wallet_records();
+        records.push((
+            "name",
+            compact(sapling_address.as_bytes()),
+            compact(b"shielded savings"),
+        ));
+        records.push((
+            "purpose",
+            compact(sapling_address.as_bytes()),
+            compact(b"receive"),
+        ));
+        let wallet = parse_records(&records);
+        let zewif = migrate_to_zewif(&wallet, BlockHeight::from(5_000_150)).unwrap();
+
+        let addresses: Vec<_> = zewif
+            .wallets()
+            .values()
+            .flat_map(|wallet| wallet.accounts().values())
+            .flat_map(|account| account.addresses().values())
+            .collect();
+        for (id, name) in [
+            (AddressId::Transparent(named_address()), "savings"),
+            (AddressId::Sapling(sapling_address), "shielded savings"),
+        ] {
+            let labeled: Vec<_> = addresses
+                .iter()
+                .filter(|address| AddressId::from_protocol_address(address.address()) == id)
+                .collect();
+            assert!(!labeled.is_empty(), "{id:?} was not migrated");
+            for address in labeled {
+                assert_eq!(address.name(), name);
+                assert_eq!(address.purpose(), Some("receive"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_migration_keeps_unified_accounts() {
+        let mut records = minimal_wallet_records();
+        for (account_id, fingerprint) in [(0u32, 0xaa), (1, 0xbb)] {
+            // Seed fingerprint, coin type, account ID and UFVK fingerprint.
+            let mut key = vec![0x01; 32];
+            key.extend_from_slice(&133u32.to_le_bytes());
+            key.extend_from_slice(&account_id.to_le_bytes());
+            key.extend_from_slice(&[fingerprint; 32]);
+            records.push(("unifiedaccount", key, 0u32.to_le_bytes().to_vec()));
+        }
+        let wallet = parse_records(&records);
+        let zewif = migrate_to_zewif(&wallet, BlockHeight::from(5_000_150)).unwrap();
+
+        let mut accounts: Vec<_> = zewif
+            .wallets()
+            .values()
+            .flat_map(|wallet| wallet.accounts().values())
+            .filter_map(|account| Some((account.zip32_account_id()?, account.name().to_string())))
+            .collect();
+        accounts.sort();
+        assert_eq!(
+            accounts,
+            [(0, "Account #0".to_string()), (1, "Account #1".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_migration_keeps_transparent_keys_and_hd_paths() {
+        let metadata =
+            KeyMetadata::with_hd_keypath("m/44'/133'/0'/1/7".to_string(), Blob32::new([0x01; 32]));
+        let keypair = KeyPair::from_secret(&[0x11; 32], true, metadata).unwrap();
+        let keys = Keys::new(HashMap::from([(keypair.pubkey().clone(), keypair.clone())]));
+        let wallet = ZcashdWallet::builder().keys(keys).build();
+        let zewif = migrate_to_zewif(&wallet, BlockHeight::from(5_000_150)).unwrap();
+
+        let expected = keypair.address(wallet.network()).to_string();
+        let migrated: Vec<_> = zewif
+            .wallets()
+            .values()
+            .flat_map(|wallet| wallet.accounts().values())
+            .flat_map(|account| account.addresses().values())
+            .filter_map(|address| match address.address() {
+                ProtocolAddress::Transparent(address) if address.address() == expected => {
+                    Some(address)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(migrated.len(), 1);
+        assert!(matches!(
+            migrated[0].spend_authority(),
+            Some(SpendAuthority::SpendingKey(_))
+        ));
+        let derivation_info = migrated[0].derivation_info().unwrap();
+        assert_eq!(derivation_info.change(), NonHardenedChildIndex::from(1));
+        assert_eq!(
+            derivation_info.address_index(),
+            NonHardenedChildIndex::from(7)
+        );
+    }
+
+    #[test]
+    fn test_migration_keeps_sprout_keys() {
+        let wallet = minimal_wallet();
+        let zewif = migrate_to_zewif(&wallet, BlockHeight::from(5_000_150)).unwrap();
+
+        let (address, key) = wallet.sprout_keys().unwrap().iter().next().unwrap();
+        let envelope = convert_sprout_spending_key(address, key, wallet.network());
+        let attachment = Envelope::new_attachment(
+            envelope.clone(),
+            SPROUT_KEY_ATTACHMENT_VENDOR,
+            Some(SPROUT_KEY_ATTACHMENT_FORMAT),
+        );
+        let carrying: Vec<_> = zewif
+            .wallets()
+            .values()
+            .flat_map(|wallet| wallet.accounts().values())
+            .filter(|account| account.get_attachment(&attachment.digest()).is_some())
+            .collect();
+        assert_eq!(carrying.len(), 1);
+
+        // The key is encoded as `z_exportkey` writes it.
+        let encoded: String = envelope.extract_subject().unwrap();
+        assert!(encoded.starts_with("SK"));
+        let payload = bs58::decode(&encoded).with_check(None).into_vec().unwrap();
+        assert_eq!(payload, [&[0xab, 0x36][..], &[0x0a; 32]].concat());
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt::Write;
+
+use crate::ZcashdWallet;
+
+/// How a category of zcashd data fares in [`migrate_to_zewif`].
+///
+/// [`migrate_to_zewif`]: crate::migrate::migrate_to_zewif
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LossKind {
+    /// The data is not carried into zewif at all.
+    Dropped,
+    /// The data is carried into zewif with part of it missing.
+    Converted,
+}
+
+/// A category of wallet data that migration drops or converts lossily.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LossyCategory {
+    category: &'static str,
+    count: usize,
+    kind: LossKind,
+    description: &'static str,
+}
+
+impl LossyCategory {
+    pub fn category(&self) -> &'static str {
+        self.category
+    }
+
+    /// The number of affected records in the wallet.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn kind(&self) -> LossKind {
+        self.kind
+    }
+
+    /// What is lost and what the user can do about it.
+    pub fn description(&self) -> &'static str {
+        self.description
+    }
+}
+
+/// The wallet data that migrating to zewif would lose, as produced by
+/// [`migration_report`].
+///
+/// Only data that reached the parsed wallet is covered; records the parser
+/// does not read, such as those of encrypted wallets, are listed by the
+/// [`UnparsedReport`](crate::UnparsedReport) instead.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    lossy: Vec<LossyCategory>,
+}
+
+impl MigrationReport {
+    pub fn is_empty(&self) -> bool {
+        self.lossy.is_empty()
+    }
+
+    /// The categories of data that would be dropped or lossily converted.
+    pub fn lossy(&self) -> &[LossyCategory] {
+        &self.lossy
+    }
+
+    pub fn category(&self, category: &str) -> Option<&LossyCategory> {
+        self.lossy.iter().find(|entry| entry.category == category)
+    }
+
+    pub fn summary(&self) -> String {
+        let mut output = String::new();
+        for entry in &self.lossy {
+            let kind = match entry.kind {
+                LossKind::Dropped => "dropped",
+                LossKind::Converted => "converted",
+            };
+            writeln!(
+                output,
+                "{}: {} ({}): {}",
+                entry.category, entry.count, kind, entry.description
+            )
+            .unwrap();
+        }
+        output
+    }
+
+    fn add(
+        &mut self,
+        category: &'static str,
+        count: usize,
+        kind: LossKind,
+        description: &'static str,
+    ) {
+        if count > 0 {
+            self.lossy.push(LossyCategory {
+                category,
+                count,
+                kind,
+                description,
+            });
+        }
+    }
+}
+
+/// Reports the data in `wallet` that [`migrate_to_zewif`] would drop or
+/// convert lossily, without performing the migration.
+///
+/// [`migrate_to_zewif`]: crate::migrate::migrate_to_zewif
+pub fn migration_report(wallet: &ZcashdWallet) -> MigrationReport {
+    let mut report = MigrationReport::default();
+    report.add(
+        "sprout keys",
+        wallet
+            .sprout_keys()
+            .map_or(0, |keys| keys.keypairs().count()),
+        LossKind::Converted,
+        "zewif cannot represent Sprout, so the keys are only carried as account attachments; \
+         move Sprout funds to a Sapling or unified address first",
+    );
+    report.add(
+        "transparent wallet keys",
+        wallet
+            .wallet_keys()
+            .map_or(0, |keys| keys.keypairs().count()),
+        LossKind::Converted,
+        "addresses of `wkey` records are migrated without their private keys",
+    );
+    report.add(
+        "legacy HD seed",
+        usize::from(wallet.legacy_hd_seed().is_some()),
+        LossKind::Dropped,
+        "only mnemonic seeds are migrated",
+    );
+    report.add(
+        "sapling viewing keys",
+        wallet.viewing_keys().len(),
+        LossKind::Converted,
+        "imported full viewing keys are reduced to their incoming viewing keys",
+    );
+    report.add(
+        "watch-only scripts",
+        wallet.watch_only_scripts().len(),
+        LossKind::Dropped,
+        "zewif has no representation for watch-only scripts",
+    );
+    report.add(
+        "legacy accounts",
+        wallet.legacy_accounts().len(),
+        LossKind::Dropped,
+        "the accounting system removed in zcashd 4.5.0 has no zewif equivalent",
+    );
+    report.add(
+        "key pool",
+        wallet.key_pool().len(),
+        LossKind::Dropped,
+        "pre-generated keys not yet handed out are not migrated",
+    );
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LossKind, migration_report};
+    use crate::test_support::{minimal_wallet, minimal_wallet_records, parse_records};
+
+    #[test]
+    fn test_migration_report_lists_sprout_keys() {
+        let wallet = minimal_wallet();
+        let report = migration_report(&wallet);
+        let sprout = report.category("sprout keys").unwrap();
+        assert_eq!(sprout.count(), 1);
+        assert_eq!(sprout.kind(), LossKind::Converted);
+        assert!(report.lossy().contains(sprout));
+        assert!(report.summary().contains("sprout keys: 1 (converted)"));
+
+        let mut records = minimal_wallet_records();
+        records.retain(|(keyname, _, _)| !matches!(*keyname, "zkey" | "zkeymeta"));
+        let wallet = parse_records(&records);
+        assert!(migration_report(&wallet).category("sprout keys").is_none());
+    }
+}
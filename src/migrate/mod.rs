@@ -8,5 +8,8 @@ mod_use!(addresses);
 mod_use!(keys);
 mod_use!(transaction_addresses);
 mod_use!(accounts);
+mod_use!(witnesses);
+mod_use!(memos);
+mod_use!(migration_report);
 
 pub(crate) mod primitives;
@@ -9,3 +9,16 @@ pub(crate) fn address_network_from_zewif(
         Network::Regtest => zcash_address::Network::Regtest,
     }
 }
+
+/// The consensus parameters used to encode addresses for `network`. Regtest
+/// addresses are encoded as for the test network.
+pub(crate) fn address_encoding_network(
+    network: Network,
+) -> zcash_protocol::consensus::Network {
+    match network {
+        Network::Main => zcash_protocol::consensus::Network::MainNetwork,
+        Network::Test | Network::Regtest => {
+            zcash_protocol::consensus::Network::TestNetwork
+        }
+    }
+}
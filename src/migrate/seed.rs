@@ -1,14 +1,310 @@
+use bip0039::{Language, Mnemonic};
+use sha2::Sha512;
+use unicode_normalization::UnicodeNormalization;
+use zcash_protocol::consensus::NetworkConstants;
+use zewif::{Bip39Mnemonic, Blob32, SeedFingerprint};
+use zip32::ChildIndex;
+
 use crate::ZcashdWallet;
-use crate::Result;
+use crate::migrate::primitives::address_encoding_network;
+use crate::zcashd_wallet::{KeyMetadata, sapling::SaplingKey};
+use crate::{Error, Result};
+
+/// The ZIP 32 account zcashd derives legacy Sapling keys under in mnemonic
+/// wallets.
+const LEGACY_ACCOUNT: u32 = 0x7fff_ffff;
 
 /// Convert ZCashd mnemonic seed to Zewif SeedMaterial
 pub fn convert_seed_material(wallet: &ZcashdWallet) -> Result<Option<zewif::SeedMaterial>> {
     // Check if we have a mnemonic phrase
-    if !wallet.bip39_mnemonic().mnemonic().is_empty() {
+    if let Some(bip39_mnemonic) = wallet.bip39_mnemonic()
+        && !bip39_mnemonic.mnemonic().is_empty()
+    {
         return Ok(Some(zewif::SeedMaterial::Bip39Mnemonic(
-            wallet.bip39_mnemonic().clone(),
+            bip39_mnemonic.clone(),
         )));
     }
-    // If no mnemonic, return None
+    // If no mnemonic (e.g. a pre-5.0.0 wallet), return None
     Ok(None)
 }
+
+/// The BIP 39 seed of `mnemonic`, which zcashd derives with an empty
+/// passphrase.
+///
+/// If the mnemonic has a recorded seed fingerprint, the derived seed is checked
+/// against it.
+pub fn bip39_seed(mnemonic: &Bip39Mnemonic) -> Result<[u8; 64]> {
+    let phrase: String = mnemonic.mnemonic().nfkd().collect();
+    let mut seed = [0u8; 64];
+    pbkdf2::pbkdf2_hmac::<Sha512>(phrase.as_bytes(), b"mnemonic", 2048, &mut seed);
+    if let Some(recorded) = mnemonic.fingerprint() {
+        let derived = SeedFingerprint::new(seed_fingerprint(&seed));
+        if &derived != recorded {
+            return Err(Error::SeedFingerprintMismatch {
+                recorded: recorded.to_hex(),
+                derived: derived.to_hex(),
+            });
+        }
+    }
+    Ok(seed)
+}
+
+/// Checks that `mnemonic` is a valid BIP 39 phrase, with every word from one
+/// of the wordlists zcashd supports and a matching checksum, returning
+/// [`Error::InvalidMnemonicChecksum`] if not.
+pub fn validate_mnemonic_checksum(mnemonic: &Bip39Mnemonic) -> Result<()> {
+    const LANGUAGES: [Language; 10] = [
+        Language::English,
+        Language::SimplifiedChinese,
+        Language::TraditionalChinese,
+        Language::Czech,
+        Language::French,
+        Language::Italian,
+        Language::Japanese,
+        Language::Korean,
+        Language::Portuguese,
+        Language::Spanish,
+    ];
+    let phrase: String = mnemonic.mnemonic().nfkd().collect();
+    if LANGUAGES
+        .into_iter()
+        .any(|language| Mnemonic::validate_in(language, &phrase).is_ok())
+    {
+        Ok(())
+    } else {
+        Err(Error::InvalidMnemonicChecksum)
+    }
+}
+
+fn seed_fingerprint(seed: &[u8; 64]) -> [u8; 32] {
+    zip32::fingerprint::SeedFingerprint::from_seed(seed)
+        .expect("a 64-byte seed is a valid ZIP 32 seed")
+        .to_bytes()
+}
+
+fn wallet_seed(wallet: &ZcashdWallet) -> Result<[u8; 64]> {
+    let mnemonic = wallet
+        .bip39_mnemonic()
+        .ok_or_else(|| Error::MissingRecord {
+            kind: "mnemonic seed",
+            key: "mnemonicphrase".to_string(),
+        })?;
+    bip39_seed(mnemonic)
+}
+
+/// Derives the Sapling keys that zcashd has handed out from the wallet's
+/// mnemonic seed, in derivation order.
+///
+/// Mnemonic wallets derive each new Sapling address at
+/// `m/32'/coin_type'/2147483647'/i'`, for `i` up to the mnemonic HD chain's
+/// legacy Sapling key counter.
+pub fn derive_sapling_keys(wallet: &ZcashdWallet) -> Result<Vec<SaplingKey>> {
+    let chain = wallet
+        .mnemonic_hd_chain()
+        .ok_or_else(|| Error::MissingRecord {
+            kind: "mnemonic HD chain",
+            key: "mnemonichdchain".to_string(),
+        })?;
+    let seed = wallet_seed(wallet)?;
+    let seed_fp = Blob32::new(seed_fingerprint(&seed));
+    let coin_type = address_encoding_network(wallet.network()).coin_type();
+    let master = ::sapling::zip32::ExtendedSpendingKey::master(&seed);
+    Ok((0..chain.legacy_sapling_key_counter())
+        .map(|index| {
+            let extsk = ::sapling::zip32::ExtendedSpendingKey::from_path(
+                &master,
+                &[
+                    ChildIndex::hardened(32),
+                    ChildIndex::hardened(coin_type),
+                    ChildIndex::hardened(LEGACY_ACCOUNT),
+                    ChildIndex::hardened(index),
+                ],
+            );
+            let metadata = KeyMetadata::with_hd_keypath(
+                format!("m/32'/{coin_type}'/{LEGACY_ACCOUNT}'/{index}'"),
+                seed_fp.clone(),
+            );
+            SaplingKey::from_spending_key(extsk, metadata)
+        })
+        .collect())
+}
+
+/// Derives the Orchard spending key of the unified account `account_index`
+/// from the wallet's mnemonic seed.
+///
+/// zcashd stores no Orchard spending keys, only the seed they are derived
+/// from, so this is the only way to recover Orchard spend authority.
+pub fn derive_orchard_spending_key(
+    wallet: &ZcashdWallet,
+    account_index: u32,
+) -> Result<::orchard::keys::SpendingKey> {
+    let seed = wallet_seed(wallet)?;
+    let coin_type = address_encoding_network(wallet.network()).coin_type();
+    zip32::AccountId::try_from(account_index)
+        .ok()
+        .and_then(|account| {
+            ::orchard::keys::SpendingKey::from_zip32_seed(&seed, coin_type, account).ok()
+        })
+        .ok_or(Error::KeyDerivation {
+            account: account_index,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use zcash_keys::keys::UnifiedSpendingKey;
+    use zcash_protocol::consensus::MainNetwork;
+    use zewif::{Bip39Mnemonic, Blob32, SeedFingerprint, sapling::SaplingIncomingViewingKey};
+
+    use super::{bip39_seed, derive_orchard_spending_key, derive_sapling_keys};
+    use crate::{
+        Error, ZcashdWallet, parse,
+        zcashd_wallet::{
+            KeyMetadata, MnemonicHDChain, UfvkFingerprint, UnifiedAccountMetadata, UnifiedAccounts,
+            sapling::{SaplingKey, SaplingKeys},
+        },
+    };
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon \
+                          abandon abandon abandon about";
+
+    fn mnemonic(phrase: &str) -> Bip39Mnemonic {
+        let mut mnemonic = Bip39Mnemonic::new(phrase.to_string(), None);
+        let seed = bip39_seed(&mnemonic).unwrap();
+        let fingerprint = zip32::fingerprint::SeedFingerprint::from_seed(&seed).unwrap();
+        mnemonic.set_fingerprint(SeedFingerprint::new(fingerprint.to_bytes()));
+        mnemonic
+    }
+
+    #[test]
+    fn test_bip39_seed() {
+        // The BIP 39 test vector with an empty passphrase.
+        let seed = bip39_seed(&Bip39Mnemonic::new(PHRASE.to_string(), None)).unwrap();
+        assert_eq!(
+            hex::encode(seed),
+            "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc1\
+             9a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4"
+        );
+
+        let mut mismatched = mnemonic(PHRASE);
+        mismatched.set_fingerprint(SeedFingerprint::new([0; 32]));
+        assert!(matches!(
+            bip39_seed(&mismatched),
+            Err(Error::SeedFingerprintMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_derive_orchard_spending_key() {
+        let mnemonic = mnemonic(PHRASE);
+        let seed = bip39_seed(&mnemonic).unwrap();
+
+        // Account 1's UFVK as zcashd would have recorded it. The builder's
+        // wallet is on the main network.
+        let account = zip32::AccountId::try_from(1).unwrap();
+        let ufvk = UnifiedSpendingKey::from_seed(&MainNetwork, &seed, account)
+            .unwrap()
+            .to_unified_full_viewing_key();
+        let fingerprint = UfvkFingerprint::new([0x11; 32]);
+        let mut data = vec![0x01; 32];
+        data.extend_from_slice(&133u32.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&[0x11; 32]);
+        let metadata = parse!(buf = &data, UnifiedAccountMetadata, "account").unwrap();
+        let wallet = ZcashdWallet::builder()
+            .bip39_mnemonic(Some(mnemonic))
+            .unified_accounts(UnifiedAccounts::new(
+                vec![],
+                HashMap::from([(fingerprint, ufvk)]),
+                HashMap::from([(fingerprint, metadata)]),
+            ))
+            .build();
+
+        let accounts = wallet.unified_accounts().accounts();
+        let account_ufvk = accounts[0].ufvk().unwrap();
+        let spending_key = derive_orchard_spending_key(&wallet, accounts[0].account_id()).unwrap();
+        assert_eq!(
+            ::orchard::keys::FullViewingKey::from(&spending_key).to_bytes(),
+            account_ufvk.orchard().unwrap().to_bytes()
+        );
+
+        let without_seed = ZcashdWallet::builder().build();
+        assert!(matches!(
+            derive_orchard_spending_key(&without_seed, 0),
+            Err(Error::MissingRecord { .. })
+        ));
+    }
+
+    #[test]
+    fn test_derive_sapling_keys() {
+        let mnemonic = mnemonic(PHRASE);
+        let seed = bip39_seed(&mnemonic).unwrap();
+        let seed_fp = Blob32::new(
+            zip32::fingerprint::SeedFingerprint::from_seed(&seed)
+                .unwrap()
+                .to_bytes(),
+        );
+
+        let mut chain = 1i32.to_le_bytes().to_vec();
+        chain.extend_from_slice(&[0x01; 32]);
+        chain.extend_from_slice(&1_650_000_000u64.to_le_bytes());
+        // One account, no transparent keys and two Sapling keys handed out.
+        for counter in [1u32, 0, 0, 2] {
+            chain.extend_from_slice(&counter.to_le_bytes());
+        }
+        chain.push(1);
+        let chain = parse!(buf = &chain, MnemonicHDChain, "chain").unwrap();
+
+        // The `sapzkey` records of a hybrid wallet: the two keys zcashd derived
+        // from the seed and one imported key.
+        let master = ::sapling::zip32::ExtendedSpendingKey::master(&seed);
+        let recorded: Vec<_> = (0..2)
+            .map(|index| {
+                ::sapling::zip32::ExtendedSpendingKey::from_path(
+                    &master,
+                    &[
+                        zip32::ChildIndex::hardened(32),
+                        zip32::ChildIndex::hardened(133),
+                        zip32::ChildIndex::hardened(0x7fff_ffff),
+                        zip32::ChildIndex::hardened(index),
+                    ],
+                )
+            })
+            .chain([::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32])])
+            .map(|extsk| {
+                let ivk = extsk.to_diversifiable_full_viewing_key().fvk().vk.ivk();
+                let ivk = SaplingIncomingViewingKey::new(ivk.to_repr());
+                let mut meta = 1i32.to_le_bytes().to_vec();
+                meta.extend_from_slice(&1_650_000_000u64.to_le_bytes());
+                let meta = parse!(buf = &meta, KeyMetadata, "keymeta").unwrap();
+                (ivk, SaplingKey::new(ivk, extsk, meta).unwrap())
+            })
+            .collect();
+        let wallet = ZcashdWallet::builder()
+            .bip39_mnemonic(Some(mnemonic))
+            .mnemonic_hd_chain(Some(chain))
+            .sapling_keys(SaplingKeys::new(recorded.iter().cloned().collect()))
+            .build();
+
+        let derived = derive_sapling_keys(&wallet).unwrap();
+        assert_eq!(derived.len(), 2);
+        for (index, key) in derived.iter().enumerate() {
+            let (ivk, recorded) = &recorded[index];
+            assert_eq!(key.ivk(), ivk);
+            assert_eq!(key.extsk().to_bytes(), recorded.extsk().to_bytes());
+            let keypath = format!("m/32'/133'/2147483647'/{index}'");
+            assert_eq!(key.metadata().hd_keypath(), Some(&keypath));
+            assert_eq!(key.metadata().seed_fp(), Some(&seed_fp));
+        }
+
+        let without_chain = ZcashdWallet::builder()
+            .bip39_mnemonic(wallet.bip39_mnemonic().cloned())
+            .build();
+        assert!(matches!(
+            derive_sapling_keys(&without_chain),
+            Err(Error::MissingRecord { .. })
+        ));
+    }
+}
@@ -426,8 +426,8 @@ fn is_likely_change_output(wallet: &ZcashdWallet, address: &str) -> bool {
             return false;
         }
 
-        if let Some(purpose) = wallet.address_purposes().get(addr)
-            && !purpose.is_empty()
+        if let Some(purpose) = wallet.address_purpose(addr)
+            && !purpose.is_unknown()
         {
             return false;
         }
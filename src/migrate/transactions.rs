@@ -1,15 +1,22 @@
 use crate::{Result, ResultExt};
 use std::collections::HashMap;
-use zewif::{BlockHash, TxBlockPosition, TxId};
+use zewif::{
+    BlockHash, BlockHeight, Data, OrchardActionDescription, TxBlockPosition, TxId,
+    sapling::SaplingOutputDescription,
+};
 
+use super::MemoDecryptor;
 use crate::{ZcashdWallet, zcashd_wallet::WalletTx};
 
 /// Convert ZCashd transactions to Zewif format
 pub fn convert_transactions(wallet: &ZcashdWallet) -> Result<HashMap<TxId, zewif::Transaction>> {
     let mut transactions = HashMap::new();
+    let heights = wallet.tx_heights();
+    let decryptor = MemoDecryptor::new(wallet);
 
     for (tx_id, wallet_tx) in wallet.transactions() {
-        let zewif_tx = convert_transaction(*tx_id, wallet_tx)
+        let height = heights.get(tx_id).copied().flatten();
+        let zewif_tx = convert_transaction(*tx_id, wallet_tx, height, &decryptor)
             .with_context(|| format!("Failed to convert transaction {}", tx_id))?;
         transactions.insert(*tx_id, zewif_tx);
     }
@@ -18,7 +25,17 @@ pub fn convert_transactions(wallet: &ZcashdWallet) -> Result<HashMap<TxId, zewif
 }
 
 /// Convert a single ZCashd transaction to Zewif format
-fn convert_transaction(tx_id: TxId, tx: &WalletTx) -> Result<zewif::Transaction> {
+///
+/// Alongside the raw transaction and its block position, this carries the
+/// height of the block it was mined in, when the wallet's note tracking
+/// records it, and the time zcashd received it. Each shielded output the
+/// wallet's keys decrypt is added with its memo.
+fn convert_transaction(
+    tx_id: TxId,
+    tx: &WalletTx,
+    height: Option<BlockHeight>,
+    decryptor: &MemoDecryptor,
+) -> Result<zewif::Transaction> {
     let mut zewif_tx = zewif::Transaction::new(tx_id);
 
     // Set raw transaction data
@@ -27,6 +44,11 @@ fn convert_transaction(tx_id: TxId, tx: &WalletTx) -> Result<zewif::Transaction>
     }
 
     // Add transaction metadata
+    if let Some(height) = height {
+        zewif_tx.set_mined_height(height);
+    }
+    // zcashd stores the unsigned `nTimeReceived` in a signed field.
+    zewif_tx.set_timestamp(u64::from(tx.time_received() as u32));
 
     // Extract block hash if available
     if tx.hash_block() != BlockHash::from_bytes([0u8; 32]) {
@@ -36,45 +58,75 @@ fn convert_transaction(tx_id: TxId, tx: &WalletTx) -> Result<zewif::Transaction>
         )))
     };
 
-    // TODO
-    //    //
-    //    // Access sapling note data hashmap for witness information if available
-    //    let sapling_note_data = tx.sapling_note_data();
-    //
-    //    // Find the matching note data for this output if available
-    //    if let Some(note_data_map) = sapling_note_data {
-    //        for (outpoint, note_data) in note_data_map {
-    //            // Match output by commitment and position in the transaction
-    //            // (Finding exact output match may require more complex lookups in practice)
-    //            if outpoint.vout() == idx as u32 && outpoint.txid() == tx_id {
-    //                // Add witness data if available
-    //                if !note_data.witnesses().is_empty() {
-    //                    // Get the best witness (the last one)
-    //                    if let Some(witness) = note_data.witnesses().last() {
-    //                        // For the anchor, we would normally use the Merkle root
-    //                        // Since we don't have direct access to it, we'll create a placeholder
-    //                        // for now and improve it in a future implementation
-    //                        let anchor = u256::default();
-    //                        sapling_output.set_witness(Some(SaplingAnchorWitness::new(
-    //                            anchor,
-    //                            witness.clone(),
-    //                        )));
-    //                    }
-    //                }
-    //
-    //                // We don't extract or decrypt memo fields during migration
-    //                // The memo is inside the encrypted ciphertext, but we preserve
-    //                // the whole ciphertext in the output description
-    //                // The receiving wallet is responsible for decrypting
-    //                // and extracting the memo with the appropriate keys
-    //                sapling_output.set_memo(None);
-    //
-    //                break;
-    //            }
-    //        }
-    //
-    //        zewif_tx.add_sapling_output(sapling_output);
-    //    }
+    // Add the shielded outputs whose memos the wallet can decrypt
+    for (index, output) in (0u32..).zip(tx.sapling_outputs()) {
+        let memo = decryptor
+            .sapling_memo(&tx_id, index, &output)
+            .with_context(|| format!("Decrypting the memo of Sapling output {index}"))?;
+        if let Some(memo) = memo {
+            let mut sapling_output = SaplingOutputDescription::new(index);
+            sapling_output.set_memo(Some(Data::from_slice(memo.encode().as_slice())));
+            zewif_tx.add_sapling_output(sapling_output);
+        }
+    }
+    for (index, action) in (0u32..).zip(tx.orchard_actions()) {
+        let memo = decryptor
+            .orchard_memo(&tx_id, index, &action)
+            .with_context(|| format!("Decrypting the memo of Orchard action {index}"))?;
+        if let Some(memo) = memo {
+            let mut orchard_action = OrchardActionDescription::new(index);
+            orchard_action.set_memo(Some(Data::from_slice(memo.encode().as_slice())));
+            zewif_tx.add_orchard_action(orchard_action);
+        }
+    }
 
     Ok(zewif_tx)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use zewif::Data;
+
+    use super::convert_transactions;
+    use crate::test_support::{
+        Record, minimal_wallet_records, parse_records, sapling_key_records,
+        sapling_output_transaction, txid, wallet_tx_value,
+    };
+
+    fn records_with_memo(memo: [u8; 512]) -> Vec<Record> {
+        let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]);
+        let (_, address) = extsk.default_address();
+        let mut records = minimal_wallet_records();
+        records.extend(sapling_key_records(&extsk));
+        let mut value = wallet_tx_value(&sapling_output_transaction(&address, 10_000, memo), None);
+        value.push(0);
+        records.push(("tx", vec![0x10; 32], value));
+        records
+    }
+
+    #[test]
+    fn test_memo_and_timestamp_survive_migration() {
+        let text = "Moving to a new wallet";
+        let mut memo = [0u8; 512];
+        memo[..text.len()].copy_from_slice(text.as_bytes());
+        let wallet = parse_records(&records_with_memo(memo));
+
+        let transactions = convert_transactions(&wallet).unwrap();
+        let tx = &transactions[&txid(0x10)];
+        assert_eq!(tx.timestamp(), Some(1_600_000_000));
+        let outputs = tx.sapling_outputs();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].memo(), Some(&Data::from_slice(&memo)));
+
+        // A text memo that is not valid UTF-8.
+        let mut malformed = [0u8; 512];
+        malformed[0] = 0xc0;
+        let wallet = parse_records(&records_with_memo(malformed));
+        let err = convert_transactions(&wallet).unwrap_err();
+        assert!(err.to_string().starts_with("Failed to convert transaction"));
+        let source = err.source().unwrap().to_string();
+        assert_eq!(source, "Decrypting the memo of Sapling output 0");
+    }
+}
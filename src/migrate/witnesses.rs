@@ -0,0 +1,49 @@
+use ::orchard::tree::MerkleHashOrchard;
+use incrementalmerkletree::MerklePath;
+use zewif::TxId;
+
+use crate::{Error, Result, ZcashdWallet};
+
+/// Builds the Merkle path needed to spend the Orchard note created by action
+/// `note_index` of transaction `txid`, against the root of the wallet's
+/// Orchard note commitment tree.
+pub fn build_witness(
+    wallet: &ZcashdWallet,
+    txid: &TxId,
+    note_index: u32,
+) -> Result<MerklePath<MerkleHashOrchard, 32>> {
+    let tree = wallet.orchard_note_commitment_tree();
+    let position = tree
+        .note_position(txid, note_index)
+        .ok_or_else(|| Error::MissingRecord {
+            kind: "orchard note position",
+            key: format!("{txid}:{note_index}"),
+        })?;
+    tree.witness(position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::build_witness;
+    use crate::{
+        Error,
+        test_support::{minimal_wallet, txid},
+    };
+
+    #[test]
+    fn test_build_witness_errors() {
+        let wallet = minimal_wallet();
+        let txid = txid(0x55);
+
+        // The fixture records a position for action 0, but its tree is empty
+        // and has nothing marked to witness.
+        assert!(matches!(
+            build_witness(&wallet, &txid, 0),
+            Err(Error::WitnessUnavailable { position: 42 })
+        ));
+        assert!(matches!(
+            build_witness(&wallet, &txid, 1),
+            Err(Error::MissingRecord { .. })
+        ));
+    }
+}
@@ -0,0 +1,30 @@
+/// Controls how `ZcashdParser` handles records it cannot decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Skip malformed records, reporting each one to the parser's observer.
+    #[default]
+    Lenient,
+    /// Stop at the first malformed record and return its error.
+    StrictFailFast,
+    /// Keep parsing past malformed records, then return every error at once
+    /// as an [`Error::Multiple`](crate::Error::Multiple).
+    StrictCollectAll,
+}
+
+impl ParseMode {
+    pub fn is_strict(&self) -> bool {
+        !matches!(self, ParseMode::Lenient)
+    }
+}
+
+/// Maps the legacy `strict` flag: `true` is [`ParseMode::StrictFailFast`] and
+/// `false` is [`ParseMode::Lenient`].
+impl From<bool> for ParseMode {
+    fn from(strict: bool) -> Self {
+        if strict {
+            ParseMode::StrictFailFast
+        } else {
+            ParseMode::Lenient
+        }
+    }
+}
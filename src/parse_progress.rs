@@ -0,0 +1,16 @@
+/// How far [`ZcashdParser`](crate::ZcashdParser) has got through a dump,
+/// reported to a progress callback after each record category.
+///
+/// Categories are parsed in a fixed order, so `categories_parsed` counts up to
+/// `categories_total` over a parse. `records_parsed` counts the dump's records
+/// consumed so far and never decreases, though records the parser does not
+/// read keep it below `records_total`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseProgress {
+    /// The keyname of the category just parsed, such as `key` or `tx`.
+    pub category: &'static str,
+    pub categories_parsed: usize,
+    pub categories_total: usize,
+    pub records_parsed: usize,
+    pub records_total: usize,
+}
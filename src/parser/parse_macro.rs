@@ -54,7 +54,9 @@
 /// 
 /// # Error Handling
 /// The macro automatically adds context to errors, making debugging easier by
-/// describing what was being parsed when an error occurred.
+/// describing what was being parsed when an error occurred. With the
+/// `parse-diagnostics` feature, errors from the parser forms also show the
+/// offset and the bytes around it.
 ///
 /// # Relation to ZCash Data Formats
 /// This macro is particularly useful when parsing ZCash wallet and transaction
@@ -99,42 +101,60 @@ macro_rules! parse {
         })
     }};
     ($parser:expr, $type:ty, $context:expr) => {{
-        <$type as $crate::parser::Parse>::parse($parser).map_err(|err| {
-            $crate::Error::with_context(err, format!("Parsing {}", $context))
+        let parser: &mut $crate::parser::Parser = $parser;
+        <$type as $crate::parser::Parse>::parse(&mut *parser).map_err(|err| {
+            $crate::Error::with_context(
+                err,
+                $crate::parser::parse_error_message(parser, $context),
+            )
         })
     }};
     ($parser:expr, $type:ty, param = $param:expr, $context:expr) => {{
-        <$type as $crate::parser::ParseWithParam<_>>::parse($parser, $param)
+        let parser: &mut $crate::parser::Parser = $parser;
+        <$type as $crate::parser::ParseWithParam<_>>::parse(&mut *parser, $param)
             .map_err(|err| {
                 $crate::Error::with_context(
                     err,
-                    format!("Parsing {}", $context),
+                    $crate::parser::parse_error_message(parser, $context),
                 )
             })
     }};
     ($parser:expr, bytes = $length:expr, $context:expr) => {{
-        $crate::parser::Parser::next($parser, $length).map_err(|err| {
-            $crate::Error::with_context(err, format!("Parsing {}", $context))
+        let parser: &mut $crate::parser::Parser = $parser;
+        $crate::parser::Parser::next(&mut *parser, $length).map_err(|err| {
+            $crate::Error::with_context(
+                err,
+                $crate::parser::parse_error_message(parser, $context),
+            )
         })
     }};
     ($parser:expr, data = $length:expr, $context:expr) => {{
-        $crate::parser::Parser::next($parser, $length)
+        let parser: &mut $crate::parser::Parser = $parser;
+        $crate::parser::Parser::next(&mut *parser, $length)
             .map(zewif::Data::from_slice)
             .map_err(|err| {
                 $crate::Error::with_context(
                     err,
-                    format!("Parsing {}", $context),
+                    $crate::parser::parse_error_message(parser, $context),
                 )
             })
     }};
     ($parser:expr, $context:expr) => {{
-        $crate::parser::Parse::parse($parser).map_err(|err| {
-            $crate::Error::with_context(err, format!("Parsing {}", $context))
+        let parser: &mut $crate::parser::Parser = $parser;
+        $crate::parser::Parse::parse(&mut *parser).map_err(|err| {
+            $crate::Error::with_context(
+                err,
+                $crate::parser::parse_error_message(parser, $context),
+            )
         })
     }};
     ($parser:expr, param = $param:expr, $context:expr) => {{
-        $crate::parser::ParseWithParam::parse($parser, $param).map_err(|err| {
-            $crate::Error::with_context(err, format!("Parsing {}", $context))
+        let parser: &mut $crate::parser::Parser = $parser;
+        $crate::parser::ParseWithParam::parse(&mut *parser, $param).map_err(|err| {
+            $crate::Error::with_context(
+                err,
+                $crate::parser::parse_error_message(parser, $context),
+            )
         })
     }};
 }
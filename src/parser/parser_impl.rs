@@ -189,6 +189,14 @@ pub struct Parser<'a> {
 
     /// Whether to print debug information during parsing
     pub trace: bool,
+
+    /// The largest element count accepted for a length-prefixed collection,
+    /// checked before anything is allocated for it
+    pub max_collection_length: usize,
+
+    /// Whether to reject encodings zcashd itself would never write, such as
+    /// a non-canonical `CompactSize`
+    pub strict: bool,
 }
 
 impl std::fmt::Debug for Parser<'_> {
@@ -201,12 +209,25 @@ impl std::fmt::Debug for Parser<'_> {
     }
 }
 
+/// A saved position in a [`Parser`], for backing out of a speculative parse
+/// with [`Parser::restore`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    offset: usize,
+}
+
 impl<'a> Parser<'a> {
+    /// The default for [`max_collection_length`](Self::max_collection_length),
+    /// matching zcashd's `MAX_SIZE` limit on `CompactSize` values.
+    pub const DEFAULT_MAX_COLLECTION_LENGTH: usize = 0x0200_0000;
+
     pub fn new(buffer: &'a dyn AsRef<[u8]>) -> Self {
         Self {
             buffer: buffer.as_ref(),
             offset: 0,
             trace: false,
+            max_collection_length: Self::DEFAULT_MAX_COLLECTION_LENGTH,
+            strict: true,
         }
     }
 
@@ -218,6 +239,11 @@ impl<'a> Parser<'a> {
         self.buffer.is_empty()
     }
 
+    /// The number of bytes consumed so far.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
     pub fn remaining(&self) -> usize {
         self.len() - self.offset
     }
@@ -231,31 +257,69 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
+    /// Like [`check_finished`](Self::check_finished), naming `context` (such
+    /// as the record being parsed) in the error.
+    pub fn check_finished_ctx(&self, context: &str) -> Result<()> {
+        self.check_finished()
+            .map_err(|err| Error::with_context(err, format!("Trailing bytes in {context}")))
+    }
+
     pub fn next(&mut self, n: usize) -> Result<&'a [u8]> {
-        if self.offset + n > self.buffer.len() {
-            return Err(Error::BufferUnderflow {
-                offset: self.offset,
-                needed: n,
-                remaining: self.remaining(),
-            });
-        }
-        let bytes = &self.buffer[self.offset..self.offset + n];
+        let bytes = self.peek(n)?;
         self.offset += n;
         if self.trace {
+            let ahead = std::cmp::min(100, self.remaining());
             println!(
                 "\t🟢 next({}): {:?} remaining: {} peek: {:?}",
                 n,
                 hex::encode(bytes),
                 self.remaining(),
-                hex::encode(self.peek(100))
+                hex::encode(&self.buffer[self.offset..self.offset + ahead])
             );
         }
         Ok(bytes)
     }
 
-    pub fn peek(&self, n: usize) -> &'a [u8] {
-        let available = std::cmp::min(n, self.remaining());
-        &self.buffer[self.offset..self.offset + available]
+    /// Returns the next `n` bytes without consuming them, so a `Parse` impl
+    /// can look at a discriminant before deciding how to parse.
+    pub fn peek(&self, n: usize) -> Result<&'a [u8]> {
+        if n > self.remaining() {
+            return Err(Error::BufferUnderflow {
+                offset: self.offset,
+                needed: n,
+                remaining: self.remaining(),
+            });
+        }
+        Ok(&self.buffer[self.offset..self.offset + n])
+    }
+
+    /// Carves the next `length` bytes off into a parser of their own and
+    /// advances past them, so a length-prefixed payload can be checked with
+    /// [`check_finished`](Self::check_finished) independently of what follows.
+    pub fn sub_parser(&mut self, length: usize) -> Result<Parser<'a>> {
+        let buffer = self.next(length)?;
+        Ok(Parser {
+            buffer,
+            offset: 0,
+            trace: self.trace,
+            max_collection_length: self.max_collection_length,
+            strict: self.strict,
+        })
+    }
+
+    /// Saves the current position, so that a parse that turns out to be the
+    /// wrong guess can be undone with [`restore`](Self::restore).
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            offset: self.offset,
+        }
+    }
+
+    /// Rewinds to a position saved by [`checkpoint`](Self::checkpoint) on this
+    /// parser.
+    pub fn restore(&mut self, cp: Checkpoint) {
+        debug_assert!(cp.offset <= self.buffer.len());
+        self.offset = cp.offset;
     }
 
     pub fn rest(&mut self) -> Data {
@@ -268,6 +332,14 @@ impl<'a> Parser<'a> {
         Data::from_slice(&self.buffer[self.offset..])
     }
 
+    pub fn set_max_collection_length(&mut self, max: usize) {
+        self.max_collection_length = max;
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
     pub fn set_trace(&mut self, trace: bool) {
         self.trace = trace;
     }
@@ -279,6 +351,34 @@ impl<'a> Parser<'a> {
     }
 }
 
+/// The context message for a failure parsing `context`, used by
+/// [`parse!`](crate::parse).
+///
+/// With the `parse-diagnostics` feature the message also shows the offset the
+/// parser stopped at and up to 16 bytes either side of it, as
+/// `[before|after]` in hex.
+#[doc(hidden)]
+pub fn parse_error_message(p: &Parser, context: impl std::fmt::Display) -> String {
+    #[cfg(feature = "parse-diagnostics")]
+    {
+        const WINDOW: usize = 16;
+        let before = &p.buffer[p.offset.saturating_sub(WINDOW)..p.offset];
+        let after = &p.buffer[p.offset..p.offset + std::cmp::min(WINDOW, p.remaining())];
+        format!(
+            "Parsing {} at offset {} [{}|{}]",
+            context,
+            p.offset,
+            hex::encode(before),
+            hex::encode(after)
+        )
+    }
+    #[cfg(not(feature = "parse-diagnostics"))]
+    {
+        let _ = p;
+        format!("Parsing {}", context)
+    }
+}
+
 impl std::io::Read for &mut Parser<'_> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         let parser = &mut **self;
@@ -288,3 +388,133 @@ impl std::io::Read for &mut Parser<'_> {
         Ok(n)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_and_remaining() {
+        let buffer = [0u8; 10];
+        let mut p = Parser::new(&buffer);
+        assert_eq!((p.offset(), p.remaining()), (0, 10));
+
+        p.next(3).unwrap();
+        assert_eq!((p.offset(), p.remaining()), (3, 7));
+        p.next(0).unwrap();
+        assert_eq!((p.offset(), p.remaining()), (3, 7));
+        p.next(7).unwrap();
+        assert_eq!((p.offset(), p.remaining()), (10, 0));
+        // A failed read consumes nothing.
+        assert!(p.next(1).is_err());
+        assert_eq!((p.offset(), p.remaining()), (10, 0));
+    }
+
+    #[cfg(feature = "parse-diagnostics")]
+    #[test]
+    fn test_parse_error_shows_byte_window() {
+        use crate::parse;
+
+        // A u32 that runs past the end of the buffer after two bytes.
+        let buffer: Vec<u8> = (0u8..20).collect();
+        let mut p = Parser::new(&buffer);
+        p.next(18).unwrap();
+        let err = parse!(&mut p, u32, "height").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parsing height at offset 18 [02030405060708090a0b0c0d0e0f1011|1213]"
+        );
+    }
+
+    #[test]
+    fn test_peek_mid_buffer() {
+        let buffer = [1u8, 2, 3, 4];
+        let mut p = Parser::new(&buffer);
+        p.next(1).unwrap();
+
+        assert_eq!(p.peek(2).unwrap(), &[2, 3]);
+        assert_eq!(p.peek(0).unwrap(), &[] as &[u8]);
+        // Peeking leaves the offset where it was.
+        assert_eq!(p.offset, 1);
+        assert_eq!(p.next(3).unwrap(), &[2, 3, 4]);
+    }
+
+    #[test]
+    fn test_restore_after_partial_parse() {
+        // A u16 count followed by that many bytes, which fails to parse as a
+        // u32 count.
+        let buffer = [2u8, 0, 0xaa, 0xbb];
+        let mut p = Parser::new(&buffer);
+        let cp = p.checkpoint();
+        let count = u32::parse(&mut p).unwrap();
+        assert!(p.next(count as usize).is_err());
+
+        p.restore(cp);
+        assert_eq!(p.offset, 0);
+        let count = u16::parse(&mut p).unwrap();
+        assert_eq!(p.next(count as usize).unwrap(), &[0xaa, 0xbb]);
+        p.check_finished().unwrap();
+    }
+
+    #[test]
+    fn test_sub_parser() {
+        // A 3-byte payload of which only the first byte is read, then a
+        // trailing u16.
+        let buffer = [3u8, 0xaa, 0xbb, 0xcc, 0x01, 0x02];
+        let mut p = Parser::new(&buffer);
+        let length = u8::parse(&mut p).unwrap() as usize;
+        let mut payload = p.sub_parser(length).unwrap();
+        assert_eq!(p.offset, 4);
+
+        assert_eq!(u8::parse(&mut payload).unwrap(), 0xaa);
+        assert!(matches!(
+            payload.check_finished(),
+            Err(Error::BufferNotConsumed { remaining: 2 })
+        ));
+        assert_eq!(u16::parse(&mut p).unwrap(), 0x0201);
+        p.check_finished().unwrap();
+
+        let mut p = Parser::new(&buffer);
+        assert!(matches!(
+            p.sub_parser(7),
+            Err(Error::BufferUnderflow { .. })
+        ));
+        assert_eq!(p.offset, 0);
+    }
+
+    #[test]
+    fn test_check_finished_ctx() {
+        let buffer = [1u8, 2];
+        let mut p = Parser::new(&buffer);
+        p.next(1).unwrap();
+        let err = p.check_finished_ctx("'key' record").unwrap_err();
+        assert_eq!(err.to_string(), "Trailing bytes in 'key' record");
+
+        p.next(1).unwrap();
+        p.check_finished_ctx("'key' record").unwrap();
+    }
+
+    #[test]
+    fn test_peek_at_end_of_buffer() {
+        let buffer = [1u8, 2, 3];
+        let mut p = Parser::new(&buffer);
+        p.next(2).unwrap();
+
+        assert_eq!(p.peek(1).unwrap(), &[3]);
+        assert!(matches!(
+            p.peek(2),
+            Err(Error::BufferUnderflow {
+                offset: 2,
+                needed: 2,
+                remaining: 1
+            })
+        ));
+        p.next(1).unwrap();
+        assert_eq!(p.peek(0).unwrap(), &[] as &[u8]);
+        assert!(matches!(
+            p.peek(1),
+            Err(Error::BufferUnderflow { remaining: 0, .. })
+        ));
+        assert_eq!(p.offset, 3);
+    }
+}
@@ -0,0 +1,30 @@
+use crate::Error;
+
+/// Receives notifications about problems the parser recovered from.
+///
+/// In [`ParseMode::Lenient`](crate::ParseMode::Lenient) `ZcashdParser` skips
+/// records it cannot decode rather than failing. Implement this trait to route
+/// those warnings into your own logging instead of stderr.
+pub trait ParserObserver {
+    /// Called when the parser skips or tolerates malformed data. `context`
+    /// describes what was being parsed when `err` occurred.
+    fn on_recoverable_error(&self, context: &str, err: &Error);
+}
+
+/// An observer that ignores all notifications.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopObserver;
+
+impl ParserObserver for NoopObserver {
+    fn on_recoverable_error(&self, _context: &str, _err: &Error) {}
+}
+
+/// An observer that writes each notification to stderr.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StderrObserver;
+
+impl ParserObserver for StderrObserver {
+    fn on_recoverable_error(&self, context: &str, err: &Error) {
+        eprintln!("{}: {}", context, err);
+    }
+}
@@ -0,0 +1,799 @@
+//! Helpers for the `serde` feature.
+//!
+//! Binary values are written as lowercase hex strings and the 256-bit
+//! integers as base-10 strings. Secret material (private keys, spending keys
+//! and seeds) is replaced with [`REDACTED`] unless serialization happens
+//! inside a scope created with `include_secrets = true`. Deserializing a
+//! redacted value fails, so only output that includes secrets can be loaded
+//! back.
+//!
+//! Map order is left to the serializer; the JSON entry points on
+//! `ZcashdWallet` go through `serde_json::Value`, which sorts object keys.
+
+use std::cell::Cell;
+
+use serde::{Deserialize, Deserializer, Serializer, de::Error as _};
+use zewif::{
+    Blob, BlockHash, Data, Network, Script, SeedFingerprint, TxId,
+    sapling::SaplingIncomingViewingKey,
+};
+
+use crate::{
+    Result,
+    migrate::primitives::address_encoding_network,
+    parse,
+    parser::prelude::*,
+    zcashd_wallet::{Address, u256},
+};
+
+/// The value written in place of secret material in the default JSON output.
+pub const REDACTED: &str = "<redacted>";
+
+thread_local! {
+    static INCLUDE_SECRETS: Cell<bool> = const { Cell::new(false) };
+    static NETWORK: Cell<Network> = const { Cell::new(Network::Main) };
+}
+
+/// Runs `f` with the given serialization settings, restoring the previous
+/// settings afterwards.
+pub(crate) fn with_scope<T>(include_secrets: bool, network: Network, f: impl FnOnce() -> T) -> T {
+    let previous_secrets = INCLUDE_SECRETS.with(|c| c.replace(include_secrets));
+    let previous_network = NETWORK.with(|c| c.replace(network));
+    let result = f();
+    INCLUDE_SECRETS.with(|c| c.set(previous_secrets));
+    NETWORK.with(|c| c.set(previous_network));
+    result
+}
+
+pub(crate) fn secrets_included() -> bool {
+    INCLUDE_SECRETS.with(Cell::get)
+}
+
+pub(crate) fn network() -> Network {
+    NETWORK.with(Cell::get)
+}
+
+/// Types without a serde implementation of their own that are written as a
+/// hex string of their zcashd encoding.
+pub trait JsonBytes: Sized {
+    fn json_bytes(&self) -> Vec<u8>;
+    fn from_json_bytes(bytes: &[u8]) -> Result<Self>;
+}
+
+impl<const N: usize> JsonBytes for Blob<N> {
+    fn json_bytes(&self) -> Vec<u8> {
+        self.as_slice().to_vec()
+    }
+
+    fn from_json_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(Self::from_slice(bytes)?)
+    }
+}
+
+impl JsonBytes for Data {
+    fn json_bytes(&self) -> Vec<u8> {
+        self.as_slice().to_vec()
+    }
+
+    fn from_json_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(Data::from_slice(bytes))
+    }
+}
+
+impl JsonBytes for Script {
+    fn json_bytes(&self) -> Vec<u8> {
+        self.as_ref().to_vec()
+    }
+
+    fn from_json_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(Script::from(Data::from_slice(bytes)))
+    }
+}
+
+impl JsonBytes for u256 {
+    fn json_bytes(&self) -> Vec<u8> {
+        self.as_ref().to_vec()
+    }
+
+    fn from_json_bytes(bytes: &[u8]) -> Result<Self> {
+        u256::try_from(bytes)
+    }
+}
+
+impl JsonBytes for SeedFingerprint {
+    fn json_bytes(&self) -> Vec<u8> {
+        self.as_ref().to_vec()
+    }
+
+    fn from_json_bytes(bytes: &[u8]) -> Result<Self> {
+        parse!(buf = &bytes, SeedFingerprint, "seed fingerprint")
+    }
+}
+
+impl JsonBytes for SaplingIncomingViewingKey {
+    fn json_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_json_bytes(bytes: &[u8]) -> Result<Self> {
+        parse!(buf = &bytes, SaplingIncomingViewingKey, "sapling ivk")
+    }
+}
+
+impl JsonBytes for ::sapling::zip32::ExtendedFullViewingKey {
+    fn json_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        bytes
+    }
+
+    fn from_json_bytes(bytes: &[u8]) -> Result<Self> {
+        parse!(
+            buf = &bytes,
+            ::sapling::zip32::ExtendedFullViewingKey,
+            "extended fvk"
+        )
+    }
+}
+
+impl JsonBytes for ::sapling::zip32::ExtendedSpendingKey {
+    fn json_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        bytes
+    }
+
+    fn from_json_bytes(bytes: &[u8]) -> Result<Self> {
+        parse!(
+            buf = &bytes,
+            ::sapling::zip32::ExtendedSpendingKey,
+            "extended spending key"
+        )
+    }
+}
+
+impl JsonBytes for ::orchard::keys::IncomingViewingKey {
+    fn json_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+
+    fn from_json_bytes(bytes: &[u8]) -> Result<Self> {
+        parse!(
+            buf = &bytes,
+            ::orchard::keys::IncomingViewingKey,
+            "orchard ivk"
+        )
+    }
+}
+
+impl JsonBytes for zcash_primitives::transaction::Transaction {
+    fn json_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        bytes
+    }
+
+    fn from_json_bytes(bytes: &[u8]) -> Result<Self> {
+        // As when parsing a `WalletTx`, the branch ID is only needed to drive
+        // the reader and is not retained.
+        Ok(zcash_primitives::transaction::Transaction::read(
+            bytes,
+            zcash_primitives::consensus::BranchId::Nu5,
+        )?)
+    }
+}
+
+/// Types without a serde implementation of their own that are written as a
+/// string, typically because they are used as map keys.
+pub trait JsonKey: Sized {
+    fn json_key(&self) -> String;
+    fn from_json_key(key: &str) -> Result<Self>;
+}
+
+impl JsonKey for Address {
+    fn json_key(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_json_key(key: &str) -> Result<Self> {
+        Ok(Address::from(key.to_string()))
+    }
+}
+
+/// Decodes the byte-reversed hex used by `Display` for hashes.
+fn parse_reversed_hex<T: Parse>(key: &str, context: &str) -> Result<T> {
+    let mut bytes = ::hex::decode(key)?;
+    bytes.reverse();
+    parse!(buf = &bytes, T, context)
+}
+
+impl JsonKey for TxId {
+    fn json_key(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_json_key(key: &str) -> Result<Self> {
+        parse_reversed_hex(key, "transaction ID")
+    }
+}
+
+impl JsonKey for BlockHash {
+    fn json_key(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_json_key(key: &str) -> Result<Self> {
+        parse_reversed_hex(key, "block hash")
+    }
+}
+
+impl JsonKey for SaplingIncomingViewingKey {
+    fn json_key(&self) -> String {
+        ::hex::encode(self.json_bytes())
+    }
+
+    fn from_json_key(key: &str) -> Result<Self> {
+        Self::from_json_bytes(&::hex::decode(key)?)
+    }
+}
+
+fn decode_hex<'de, D: Deserializer<'de>, T: JsonBytes>(
+    value: &str,
+) -> std::result::Result<T, D::Error> {
+    let bytes = ::hex::decode(value).map_err(D::Error::custom)?;
+    T::from_json_bytes(&bytes).map_err(D::Error::custom)
+}
+
+fn decode_key<'de, D: Deserializer<'de>, T: JsonKey>(
+    value: &str,
+) -> std::result::Result<T, D::Error> {
+    T::from_json_key(value).map_err(D::Error::custom)
+}
+
+/// Rejects [`REDACTED`] placeholders, which cannot be turned back into keys.
+fn reject_redacted<'de, D: Deserializer<'de>>(value: &str) -> std::result::Result<(), D::Error> {
+    if value == REDACTED {
+        return Err(D::Error::custom(
+            "secret material was redacted; export with `to_json_with_secrets` to reload",
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) mod hex {
+    use super::*;
+
+    pub fn serialize<T: JsonBytes, S: Serializer>(
+        value: &T,
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        s.serialize_str(&::hex::encode(value.json_bytes()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: JsonBytes>(
+        d: D,
+    ) -> std::result::Result<T, D::Error> {
+        decode_hex::<D, T>(&String::deserialize(d)?)
+    }
+}
+
+pub(crate) mod opt_hex {
+    use super::*;
+
+    pub fn serialize<T: JsonBytes, S: Serializer>(
+        value: &Option<T>,
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::hex::serialize(value, s),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: JsonBytes>(
+        d: D,
+    ) -> std::result::Result<Option<T>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|value| decode_hex::<D, T>(&value))
+            .transpose()
+    }
+}
+
+/// An ordered sequence of hex strings.
+pub(crate) mod json_bytes_seq {
+    use super::*;
+
+    pub fn serialize<T: JsonBytes, S: Serializer>(
+        values: &[T],
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        s.collect_seq(values.iter().map(|v| ::hex::encode(v.json_bytes())))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: JsonBytes>(
+        d: D,
+    ) -> std::result::Result<Vec<T>, D::Error> {
+        Vec::<String>::deserialize(d)?
+            .iter()
+            .map(|value| decode_hex::<D, T>(value))
+            .collect()
+    }
+}
+
+/// A set of hex strings, sorted so that it serializes deterministically.
+pub(crate) mod json_bytes_set {
+    use std::{collections::HashSet, hash::Hash};
+
+    use super::*;
+
+    pub fn serialize<T: JsonBytes, S: Serializer>(
+        values: &HashSet<T>,
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        let mut encoded: Vec<String> = values
+            .iter()
+            .map(|v| ::hex::encode(v.json_bytes()))
+            .collect();
+        encoded.sort();
+        s.collect_seq(encoded)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: JsonBytes + Eq + Hash>(
+        d: D,
+    ) -> std::result::Result<HashSet<T>, D::Error> {
+        Vec::<String>::deserialize(d)?
+            .iter()
+            .map(|value| decode_hex::<D, T>(value))
+            .collect()
+    }
+}
+
+/// A map whose values have no serde implementation, written as hex strings.
+pub(crate) mod hex_values {
+    use std::{collections::HashMap, hash::Hash};
+
+    use serde::Serialize;
+
+    use super::*;
+
+    pub fn serialize<K: Serialize, V: JsonBytes, S: Serializer>(
+        map: &HashMap<K, V>,
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        s.collect_map(map.iter().map(|(k, v)| (k, ::hex::encode(v.json_bytes()))))
+    }
+
+    pub fn deserialize<'de, D, K, V>(d: D) -> std::result::Result<HashMap<K, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+        K: Deserialize<'de> + Eq + Hash,
+        V: JsonBytes,
+    {
+        HashMap::<K, String>::deserialize(d)?
+            .into_iter()
+            .map(|(k, v)| Ok((k, decode_hex::<D, V>(&v)?)))
+            .collect()
+    }
+}
+
+/// A map whose keys have no serde implementation, keyed by
+/// [`JsonKey::json_key`].
+pub(crate) mod keyed_map {
+    use std::{collections::HashMap, hash::Hash};
+
+    use serde::Serialize;
+
+    use super::*;
+
+    pub fn serialize<K: JsonKey, V: Serialize, S: Serializer>(
+        map: &HashMap<K, V>,
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        s.collect_map(map.iter().map(|(k, v)| (k.json_key(), v)))
+    }
+
+    pub fn deserialize<'de, D, K, V>(d: D) -> std::result::Result<HashMap<K, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+        K: JsonKey + Eq + Hash,
+        V: Deserialize<'de>,
+    {
+        HashMap::<String, V>::deserialize(d)?
+            .into_iter()
+            .map(|(k, v)| Ok((decode_key::<D, K>(&k)?, v)))
+            .collect()
+    }
+}
+
+pub(crate) mod json_key {
+    use super::*;
+
+    pub fn serialize<T: JsonKey, S: Serializer>(
+        value: &T,
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        s.serialize_str(&value.json_key())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: JsonKey>(
+        d: D,
+    ) -> std::result::Result<T, D::Error> {
+        decode_key::<D, T>(&String::deserialize(d)?)
+    }
+}
+
+/// Unified full viewing keys in their encoded form. The network of the
+/// current scope selects the encoding; decoding reads it from the string.
+pub(crate) mod ufvk_values {
+    use std::{collections::HashMap, hash::Hash};
+
+    use serde::Serialize;
+    use zcash_address::unified::Encoding as _;
+    use zcash_keys::keys::UnifiedFullViewingKey;
+
+    use super::*;
+
+    pub fn serialize<K: Serialize, S: Serializer>(
+        map: &HashMap<K, UnifiedFullViewingKey>,
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        let network = address_encoding_network(network());
+        s.collect_map(map.iter().map(|(k, v)| (k, v.encode(&network))))
+    }
+
+    pub fn deserialize<'de, D, K>(
+        d: D,
+    ) -> std::result::Result<HashMap<K, UnifiedFullViewingKey>, D::Error>
+    where
+        D: Deserializer<'de>,
+        K: Deserialize<'de> + Eq + Hash,
+    {
+        HashMap::<K, String>::deserialize(d)?
+            .into_iter()
+            .map(|(k, encoded)| {
+                let (_, ufvk) =
+                    zcash_address::unified::Ufvk::decode(&encoded).map_err(D::Error::custom)?;
+                let ufvk = UnifiedFullViewingKey::parse(&ufvk).map_err(D::Error::custom)?;
+                Ok((k, ufvk))
+            })
+            .collect()
+    }
+}
+
+pub(crate) mod network_name {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        network: &Network,
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        s.serialize_str(match network {
+            Network::Main => "main",
+            Network::Test => "test",
+            Network::Regtest => "regtest",
+        })
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<Network, D::Error> {
+        match String::deserialize(d)?.as_str() {
+            "main" => Ok(Network::Main),
+            "test" => Ok(Network::Test),
+            "regtest" => Ok(Network::Regtest),
+            other => Err(D::Error::custom(format!("unknown network: {}", other))),
+        }
+    }
+}
+
+/// A secret whose own serialization is a string, written only when secrets
+/// are included and [`REDACTED`] otherwise.
+pub(crate) mod secret {
+    use serde::{
+        Serialize,
+        de::{DeserializeOwned, IntoDeserializer},
+    };
+
+    use super::*;
+
+    pub fn serialize<T: Serialize, S: Serializer>(
+        value: &T,
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        if secrets_included() {
+            value.serialize(s)
+        } else {
+            s.serialize_str(REDACTED)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: DeserializeOwned>(
+        d: D,
+    ) -> std::result::Result<T, D::Error> {
+        let value = String::deserialize(d)?;
+        reject_redacted::<D>(&value)?;
+        T::deserialize(IntoDeserializer::<D::Error>::into_deserializer(value))
+    }
+}
+
+pub(crate) mod secret_hex {
+    use super::*;
+
+    pub fn serialize<T: JsonBytes, S: Serializer>(
+        value: &T,
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        if secrets_included() {
+            super::hex::serialize(value, s)
+        } else {
+            s.serialize_str(REDACTED)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: JsonBytes>(
+        d: D,
+    ) -> std::result::Result<T, D::Error> {
+        let value = String::deserialize(d)?;
+        reject_redacted::<D>(&value)?;
+        decode_hex::<D, T>(&value)
+    }
+}
+
+pub(crate) mod opt_legacy_seed {
+    use serde::Serialize;
+    use zewif::LegacySeed;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct LegacySeedJson {
+        #[serde(with = "super::opt_hex")]
+        fingerprint: Option<SeedFingerprint>,
+        #[serde(with = "super::secret_hex")]
+        seed: Data,
+    }
+
+    pub fn serialize<S: Serializer>(
+        seed: &Option<LegacySeed>,
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        seed.as_ref()
+            .map(|seed| LegacySeedJson {
+                fingerprint: seed.fingerprint().cloned(),
+                seed: seed.data().clone(),
+            })
+            .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> std::result::Result<Option<LegacySeed>, D::Error> {
+        Ok(Option::<LegacySeedJson>::deserialize(d)?
+            .map(|json| LegacySeed::new(json.seed, json.fingerprint)))
+    }
+}
+
+/// The mnemonic phrase and its seed fingerprint. The phrase's language is
+/// not written, so a reloaded mnemonic has no language set.
+pub(crate) mod opt_bip39_mnemonic {
+    use serde::Serialize;
+    use zewif::Bip39Mnemonic;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Bip39MnemonicJson {
+        #[serde(with = "super::opt_hex")]
+        fingerprint: Option<SeedFingerprint>,
+        #[serde(with = "super::secret")]
+        mnemonic: String,
+    }
+
+    pub fn serialize<S: Serializer>(
+        mnemonic: &Option<Bip39Mnemonic>,
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        mnemonic
+            .as_ref()
+            .map(|mnemonic| Bip39MnemonicJson {
+                fingerprint: mnemonic.fingerprint().cloned(),
+                mnemonic: mnemonic.mnemonic().to_string(),
+            })
+            .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> std::result::Result<Option<Bip39Mnemonic>, D::Error> {
+        Ok(Option::<Bip39MnemonicJson>::deserialize(d)?.map(|json| {
+            let mut mnemonic = Bip39Mnemonic::new(json.mnemonic, None);
+            if let Some(fingerprint) = json.fingerprint {
+                mnemonic.set_fingerprint(fingerprint);
+            }
+            mnemonic
+        }))
+    }
+}
+
+/// Formats big-endian `bytes` as an unsigned base-10 integer.
+pub(crate) fn be_bytes_to_decimal(bytes: &[u8]) -> String {
+    // Repeatedly divide the big-endian number by 10, collecting remainders.
+    let mut digits = Vec::new();
+    let mut number = bytes.to_vec();
+    while number.iter().any(|&b| b != 0) {
+        let mut remainder = 0u32;
+        for byte in number.iter_mut() {
+            let value = (remainder << 8) | *byte as u32;
+            *byte = (value / 10) as u8;
+            remainder = value % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+    }
+    if digits.is_empty() {
+        digits.push(b'0');
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+/// Parses an unsigned base-10 integer into `N` big-endian bytes, returning
+/// `None` if `decimal` is not a number or does not fit.
+pub(crate) fn decimal_to_be_bytes<const N: usize>(decimal: &str) -> Option<[u8; N]> {
+    if decimal.is_empty() {
+        return None;
+    }
+    let mut bytes = [0u8; N];
+    for digit in decimal.bytes() {
+        if !digit.is_ascii_digit() {
+            return None;
+        }
+        // Multiply the big-endian number by 10 and add the digit.
+        let mut carry = (digit - b'0') as u32;
+        for byte in bytes.iter_mut().rev() {
+            let value = *byte as u32 * 10 + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use zewif::Network;
+
+    use super::{REDACTED, be_bytes_to_decimal, decimal_to_be_bytes, with_scope};
+    use crate::{
+        ZcashdWallet, parse,
+        test_support::{
+            MNEMONIC, minimal_wallet, minimal_wallet_records, mnemonic_record, parse_records,
+            sapling_key_records,
+        },
+        zcashd_wallet::{
+            SecondsSinceEpoch,
+            transparent::{PrivKey, PubKey, WalletKey},
+        },
+    };
+
+    #[test]
+    fn test_be_bytes_to_decimal() {
+        assert_eq!(be_bytes_to_decimal(&[]), "0");
+        assert_eq!(be_bytes_to_decimal(&[0, 0]), "0");
+        assert_eq!(be_bytes_to_decimal(&[0x01, 0x00]), "256");
+        assert_eq!(be_bytes_to_decimal(&[0xff; 16]), u128::MAX.to_string());
+    }
+
+    #[test]
+    fn test_decimal_to_be_bytes() {
+        assert_eq!(decimal_to_be_bytes::<2>("0"), Some([0, 0]));
+        assert_eq!(decimal_to_be_bytes::<2>("256"), Some([0x01, 0x00]));
+        assert_eq!(
+            decimal_to_be_bytes::<16>(&u128::MAX.to_string()),
+            Some([0xff; 16])
+        );
+        assert_eq!(decimal_to_be_bytes::<2>("65536"), None);
+        assert_eq!(decimal_to_be_bytes::<2>("12a"), None);
+        assert_eq!(decimal_to_be_bytes::<2>(""), None);
+    }
+
+    fn wallet_key(privkey_data: &[u8; 214]) -> WalletKey {
+        let mut pubkey_bytes = vec![33u8, 0x02];
+        pubkey_bytes.extend_from_slice(&[0x11; 32]);
+        let pubkey = parse!(buf = &pubkey_bytes, PubKey, "pubkey").unwrap();
+
+        let mut privkey_bytes = vec![214u8];
+        privkey_bytes.extend_from_slice(privkey_data);
+        privkey_bytes.extend_from_slice(&[0x22; 32]);
+        let privkey = parse!(buf = &privkey_bytes, PrivKey, "privkey").unwrap();
+
+        WalletKey::new(
+            pubkey,
+            privkey,
+            SecondsSinceEpoch::from(0u64),
+            SecondsSinceEpoch::from(0u64),
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_secrets_are_redacted_by_default() {
+        let privkey_data = [0x5c; 214];
+        let wallet_key = wallet_key(&privkey_data);
+        let privkey_hex = hex::encode(privkey_data);
+
+        let redacted =
+            with_scope(false, Network::Main, || serde_json::to_string(&wallet_key)).unwrap();
+        assert!(!redacted.contains(&privkey_hex));
+        assert!(redacted.contains(REDACTED));
+
+        let with_secrets =
+            with_scope(true, Network::Main, || serde_json::to_string(&wallet_key)).unwrap();
+        assert!(with_secrets.contains(&privkey_hex));
+    }
+
+    #[cfg(not(feature = "view-only"))]
+    #[test]
+    fn test_wallet_secrets_are_redacted_by_default() {
+        let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]);
+        let mut records = minimal_wallet_records();
+        records.extend(sapling_key_records(&extsk));
+        records.push(mnemonic_record(false));
+        let wallet = parse_records(&records);
+        let secrets = [
+            // The `key` private key, the `zkey` Sprout spending key, the
+            // `sapzkey` extended spending key and the mnemonic phrase.
+            hex::encode([0x5c; 214]),
+            be_bytes_to_decimal(&[0x0a; 32]),
+            hex::encode(extsk.to_bytes()),
+            MNEMONIC.to_string(),
+        ];
+
+        let redacted = wallet.to_json_pretty().unwrap();
+        let with_secrets = wallet.to_json_with_secrets().unwrap();
+        for secret in &secrets {
+            assert!(
+                !redacted.contains(secret.as_str()),
+                "{secret} was not redacted"
+            );
+            assert!(
+                with_secrets.contains(secret.as_str()),
+                "{secret} is missing"
+            );
+        }
+    }
+
+    #[test]
+    fn test_redacted_secrets_are_rejected() {
+        let wallet_key = wallet_key(&[0x5c; 214]);
+
+        let redacted =
+            with_scope(false, Network::Main, || serde_json::to_string(&wallet_key)).unwrap();
+        assert!(serde_json::from_str::<WalletKey>(&redacted).is_err());
+
+        let with_secrets =
+            with_scope(true, Network::Main, || serde_json::to_string(&wallet_key)).unwrap();
+        let decoded: WalletKey = serde_json::from_str(&with_secrets).unwrap();
+        assert_eq!(decoded, wallet_key);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let wallet = minimal_wallet();
+        let json = wallet.to_json_with_secrets().unwrap();
+
+        let reloaded = ZcashdWallet::from_json(&json).unwrap();
+        assert_eq!(reloaded.to_json_with_secrets().unwrap(), json);
+        let keypair = wallet.keys().keypairs().next().unwrap();
+        assert_eq!(
+            reloaded.keys().keypair_for_pubkey(keypair.pubkey()),
+            Some(keypair)
+        );
+
+        let redacted = wallet.to_json_pretty().unwrap();
+        assert!(ZcashdWallet::from_json(&redacted).is_err());
+    }
+}
@@ -0,0 +1,414 @@
+//! Record fixtures shared by the unit tests of the parser, the wallet types
+//! and the migration.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    collections::HashMap,
+};
+
+use sha2::{Digest, Sha256};
+use zewif::{Bip39Mnemonic, Data, Network, TxId};
+
+use crate::{
+    BDBDump, ZcashdDump, ZcashdParser, ZcashdWallet,
+    migrate::bip39_seed,
+    zcashd_wallet::{transparent::KeyId, u160},
+};
+
+/// A `(keyname, key data, value)` triple, as `dump_from_records` takes them.
+pub(crate) type Record = (&'static str, Vec<u8>, Vec<u8>);
+
+/// Prefixes `bytes` with a single-byte CompactSize length.
+pub(crate) fn compact(bytes: &[u8]) -> Vec<u8> {
+    assert!(bytes.len() < 0xfd);
+    let mut result = vec![bytes.len() as u8];
+    result.extend_from_slice(bytes);
+    result
+}
+
+/// Builds a `ZcashdDump` from `(keyname, key data, value)` triples.
+pub(crate) fn dump_from_records(records: &[(&str, Vec<u8>, Vec<u8>)]) -> ZcashdDump {
+    let mut data_records = HashMap::new();
+    for (keyname, key_data, value) in records {
+        let mut key = compact(keyname.as_bytes());
+        key.extend_from_slice(key_data);
+        data_records.insert(Data::from_slice(&key), Data::from_slice(value));
+    }
+    let bdb_dump = BDBDump {
+        header_records: HashMap::new(),
+        data_records,
+    };
+    ZcashdDump::from_bdb_dump(&bdb_dump, true).unwrap()
+}
+
+/// Leniently parses a wallet from `records`.
+pub(crate) fn parse_records(records: &[(&str, Vec<u8>, Vec<u8>)]) -> ZcashdWallet {
+    ZcashdParser::parse_dump(&dump_from_records(records), false)
+        .unwrap()
+        .0
+}
+
+/// The transaction ID whose bytes are all `byte`.
+pub(crate) fn txid(byte: u8) -> TxId {
+    TxId::from_bytes([byte; 32])
+}
+
+/// The address-book entry in `minimal_wallet_records`, which the wallet holds
+/// no key for.
+pub(crate) fn named_address() -> String {
+    KeyId::from(u160::from_slice(&[0x99; 20]).unwrap()).to_string(Network::Main)
+}
+
+/// Builds a minimal but complete wallet dump: one transparent key with HD
+/// metadata, one Sprout and one Sapling address, an address book entry, a
+/// key pool entry and an empty Orchard note commitment tree tracking a
+/// single note.
+pub(crate) fn minimal_wallet_dump() -> ZcashdDump {
+    dump_from_records(&minimal_wallet_records())
+}
+
+/// The wallet parsed from `minimal_wallet_dump`.
+pub(crate) fn minimal_wallet() -> ZcashdWallet {
+    parse_records(&minimal_wallet_records())
+}
+
+pub(crate) fn minimal_wallet_records() -> Vec<Record> {
+    let address = named_address();
+
+    let mut pubkey = vec![0x02];
+    pubkey.extend_from_slice(&[0x11; 32]);
+    let privkey_value = privkey_value(&pubkey, &[0x5c; 214]);
+
+    let mut keymeta = 10i32.to_le_bytes().to_vec();
+    keymeta.extend_from_slice(&1_600_000_000u64.to_le_bytes());
+    keymeta.extend(compact(b"m/0'/0'/0'"));
+    keymeta.extend_from_slice(&[0x33; 32]);
+
+    let mut pool_entry = 5_000_150u32.to_le_bytes().to_vec();
+    pool_entry.extend_from_slice(&1_600_000_000u64.to_le_bytes());
+    pool_entry.extend(compact(&pubkey));
+
+    let mut bestblock = 5_000_150u32.to_le_bytes().to_vec();
+    bestblock.push(1);
+    bestblock.extend_from_slice(&[0x44; 32]);
+
+    let mut network_info = compact(b"Zcash");
+    network_info.extend(compact(b"main"));
+
+    let mut zkeymeta = 1i32.to_le_bytes().to_vec();
+    zkeymeta.extend_from_slice(&0u64.to_le_bytes());
+
+    // Note state v1, no checkpoint, then an empty v3 bridge tree.
+    let mut tree = vec![0u8; 4];
+    tree.extend_from_slice(&[1, 0, 3, 0, 0, 0, 0]);
+    tree.extend_from_slice(&100u64.to_le_bytes());
+    // One txid with one tracked note position.
+    tree.push(1);
+    tree.extend_from_slice(&[0x55; 32]);
+    tree.extend_from_slice(&2_000_000u32.to_le_bytes());
+    tree.push(1);
+    tree.extend_from_slice(&0u32.to_le_bytes());
+    tree.extend_from_slice(&42u64.to_le_bytes());
+
+    vec![
+        ("bestblock", vec![], bestblock),
+        ("defaultkey", vec![], compact(&pubkey)),
+        ("key", compact(&pubkey), privkey_value),
+        ("keymeta", compact(&pubkey), keymeta),
+        ("minversion", vec![], 5_000_150u32.to_le_bytes().to_vec()),
+        ("name", compact(address.as_bytes()), compact(b"savings")),
+        ("networkinfo", vec![], network_info),
+        ("orchard_note_commitment_tree", vec![], tree),
+        ("pool", 1i64.to_le_bytes().to_vec(), pool_entry),
+        ("purpose", compact(address.as_bytes()), compact(b"receive")),
+        ("sapzaddr", vec![0x66; 43], vec![0x77; 32]),
+        ("version", vec![], 5_000_150u32.to_le_bytes().to_vec()),
+        ("witnesscachesize", vec![], 0i64.to_le_bytes().to_vec()),
+        ("zkey", vec![0x88; 64], vec![0x0a; 32]),
+        ("zkeymeta", vec![0x88; 64], zkeymeta),
+    ]
+}
+
+/// The `sapzkey` and `sapzkeymeta` records of `extsk`, filed under the IVK of
+/// the fixture's Sapling address.
+pub(crate) fn sapling_key_records(extsk: &::sapling::zip32::ExtendedSpendingKey) -> [Record; 2] {
+    let mut sapzkeymeta = 1i32.to_le_bytes().to_vec();
+    sapzkeymeta.extend_from_slice(&1_550_000_000u64.to_le_bytes());
+    [
+        ("sapzkey", vec![0x77; 32], extsk.to_bytes().to_vec()),
+        ("sapzkeymeta", vec![0x77; 32], sapzkeymeta),
+    ]
+}
+
+/// Encodes a `key` record value: the private key followed by the hash that
+/// binds it to `pubkey`.
+pub(crate) fn privkey_value(pubkey: &[u8], privkey: &[u8]) -> Vec<u8> {
+    let hash = Sha256::digest(Sha256::digest([pubkey, privkey].concat()));
+    let mut value = compact(privkey);
+    value.extend_from_slice(&hash);
+    value
+}
+
+/// DER-encodes `secret` the way zcashd stores compressed private keys.
+pub(crate) fn der_privkey(secret: &[u8; 32]) -> Vec<u8> {
+    let mut der = vec![0x30, 0x81, 0xd3, 0x02, 0x01, 0x01, 0x04, 0x20];
+    der.extend_from_slice(secret);
+    der.resize(214, 0);
+    der
+}
+
+/// A `wkey` record for `pubkey`, created at `time_created` and never expiring.
+pub(crate) fn wkey_record(pubkey: &[u8], secret: &[u8; 32], time_created: u64) -> Record {
+    let mut value = privkey_value(pubkey, &der_privkey(secret));
+    value.extend_from_slice(&time_created.to_le_bytes());
+    value.extend_from_slice(&0u64.to_le_bytes());
+    value.extend(compact(b""));
+    ("wkey", compact(pubkey), value)
+}
+
+/// A dump holding the single transparent key `secret`, stored under `pubkey`.
+pub(crate) fn keypair_dump(pubkey: &[u8], secret: &[u8; 32]) -> ZcashdDump {
+    let mut keymeta = 1i32.to_le_bytes().to_vec();
+    keymeta.extend_from_slice(&0u64.to_le_bytes());
+    dump_from_records(&[
+        (
+            "key",
+            compact(pubkey),
+            privkey_value(pubkey, &der_privkey(secret)),
+        ),
+        ("keymeta", compact(pubkey), keymeta),
+    ])
+}
+
+/// A dump holding just the `hdseed` record of `seed`.
+pub(crate) fn hdseed_dump(seed: &[u8], corrupt_fingerprint: bool) -> ZcashdDump {
+    let mut fingerprint = zip32::fingerprint::SeedFingerprint::from_seed(seed)
+        .unwrap()
+        .to_bytes();
+    if corrupt_fingerprint {
+        fingerprint[0] ^= 0xff;
+    }
+    dump_from_records(&[("hdseed", fingerprint.to_vec(), compact(seed))])
+}
+
+pub(crate) const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon \
+                                   abandon abandon abandon abandon about";
+
+pub(crate) fn mnemonic_record(corrupt_fingerprint: bool) -> Record {
+    phrase_record(MNEMONIC, corrupt_fingerprint)
+}
+
+pub(crate) fn phrase_record(phrase: &str, corrupt_fingerprint: bool) -> Record {
+    let seed = bip39_seed(&Bip39Mnemonic::new(phrase.to_string(), None)).unwrap();
+    let mut fingerprint = zip32::fingerprint::SeedFingerprint::from_seed(&seed)
+        .unwrap()
+        .to_bytes();
+    if corrupt_fingerprint {
+        fingerprint[0] ^= 0xff;
+    }
+    // English, then the phrase.
+    let mut value = 0u32.to_le_bytes().to_vec();
+    value.extend(compact(phrase.as_bytes()));
+    ("mnemonicphrase", fingerprint.to_vec(), value)
+}
+
+/// An empty v1 transaction: header, no inputs or outputs, lock time.
+pub(crate) fn v1_transaction() -> Vec<u8> {
+    let mut tx = 1u32.to_le_bytes().to_vec();
+    tx.extend_from_slice(&[0, 0]);
+    tx.extend_from_slice(&0u32.to_le_bytes());
+    tx
+}
+
+/// An empty Sapling (v4) transaction.
+pub(crate) fn v4_transaction() -> Vec<u8> {
+    let mut tx = (4u32 | 0x8000_0000).to_le_bytes().to_vec();
+    tx.extend_from_slice(&0x892f_2085u32.to_le_bytes());
+    // No inputs or outputs, lock time, expiry height.
+    tx.extend_from_slice(&[0, 0]);
+    tx.extend_from_slice(&[0; 8]);
+    // Value balance, no spends, outputs or JoinSplits.
+    tx.extend_from_slice(&0i64.to_le_bytes());
+    tx.extend_from_slice(&[0, 0, 0]);
+    tx
+}
+
+/// Serializes a `CWalletTx` around `transaction`, recording `order_pos`
+/// under `n` in its `mapValue`. Sapling note data is left to the caller.
+pub(crate) fn wallet_tx_value(transaction: &[u8], order_pos: Option<i64>) -> Vec<u8> {
+    let mut value = transaction.to_vec();
+    // hashBlock, empty merkle branch, index and unused vtxPrev.
+    value.extend_from_slice(&[0x44; 32]);
+    value.push(0);
+    value.extend_from_slice(&0i32.to_le_bytes());
+    value.push(0);
+    match order_pos {
+        Some(pos) => {
+            value.push(1);
+            value.extend(compact(b"n"));
+            value.extend(compact(pos.to_string().as_bytes()));
+        }
+        None => value.push(0),
+    }
+    // mapSproutNoteData, vOrderForm, times received, fFromMe, fSpent.
+    value.extend_from_slice(&[0, 0]);
+    value.extend_from_slice(&0u32.to_le_bytes());
+    value.extend_from_slice(&1_600_000_000u32.to_le_bytes());
+    value.extend_from_slice(&[0, 0]);
+    value
+}
+
+/// A Sapling wallet transaction holding one note whose only witness was
+/// made against `witness_tree`.
+pub(crate) fn sapling_wallet_tx_value(witness_tree: &[u8], witness_height: i32) -> Vec<u8> {
+    // Nothing filled and no cursor.
+    let mut witness = witness_tree.to_vec();
+    witness.extend_from_slice(&[0, 0]);
+    sapling_witness_wallet_tx_value(&witness, witness_height)
+}
+
+/// A Sapling wallet transaction holding one note whose only witness is the
+/// serialized `witness`.
+pub(crate) fn sapling_witness_wallet_tx_value(witness: &[u8], witness_height: i32) -> Vec<u8> {
+    let mut value = wallet_tx_value(&v4_transaction(), None);
+    value.extend_from_slice(&[1, 1]);
+    value.extend_from_slice(&[0x99; 32]);
+    value.extend_from_slice(&0u32.to_le_bytes());
+    // Note data version, ivk and no nullifier.
+    value.extend_from_slice(&1i32.to_le_bytes());
+    value.extend_from_slice(&[0x12; 32]);
+    value.push(0);
+    value.push(1);
+    value.extend_from_slice(witness);
+    value.extend_from_slice(&witness_height.to_le_bytes());
+    value
+}
+
+/// A Sapling (v4) transaction with one output paying `value` to
+/// `recipient` with `memo`.
+pub(crate) fn sapling_output_transaction(
+    recipient: &::sapling::PaymentAddress,
+    value: u64,
+    memo: [u8; 512],
+) -> Vec<u8> {
+    use ::sapling::{Rseed, note_encryption::SaplingDomain, value::NoteValue};
+    use zcash_note_encryption::{Domain, NoteEncryption};
+
+    let note = recipient.create_note(NoteValue::from_raw(value), Rseed::AfterZip212([0x42; 32]));
+    let cmu = note.cmu().to_bytes();
+    let encryption = NoteEncryption::<SaplingDomain>::new(None, note, memo);
+    let mut tx = (4u32 | 0x8000_0000).to_le_bytes().to_vec();
+    tx.extend_from_slice(&0x892f_2085u32.to_le_bytes());
+    // No transparent inputs or outputs, lock time, expiry height.
+    tx.extend_from_slice(&[0, 0]);
+    tx.extend_from_slice(&[0; 8]);
+    // Value balance and no spends.
+    tx.extend_from_slice(&(-(value as i64)).to_le_bytes());
+    tx.push(0);
+    // One output. Any prime-order point serves as the value commitment,
+    // so the recipient's pk_d stands in for it.
+    tx.push(1);
+    tx.extend_from_slice(&recipient.to_bytes()[11..]);
+    tx.extend_from_slice(&cmu);
+    tx.extend_from_slice(&SaplingDomain::epk_bytes(encryption.epk()).0);
+    tx.extend_from_slice(encryption.encrypt_note_plaintext().as_ref());
+    // The out ciphertext and proof are not checked when parsing.
+    tx.extend_from_slice(&[0; 80 + 192]);
+    // No JoinSplits, then the binding signature.
+    tx.push(0);
+    tx.extend_from_slice(&[0; 64]);
+    tx
+}
+
+/// A Sapling (v4) transaction with one spend revealing `nullifier`.
+pub(crate) fn sapling_spend_transaction(nullifier: [u8; 32]) -> Vec<u8> {
+    // Any prime-order point serves as the value commitment and the
+    // randomized key, so a payment address's pk_d stands in for both.
+    let (_, address) = ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]).default_address();
+    let point = &address.to_bytes()[11..];
+    let mut tx = (4u32 | 0x8000_0000).to_le_bytes().to_vec();
+    tx.extend_from_slice(&0x892f_2085u32.to_le_bytes());
+    // No transparent inputs or outputs, lock time, expiry height.
+    tx.extend_from_slice(&[0, 0]);
+    tx.extend_from_slice(&[0; 8]);
+    // Value balance and one spend: cv, anchor, nullifier, rk, then the
+    // proof and spend authorization signature, which are not checked.
+    tx.extend_from_slice(&0i64.to_le_bytes());
+    tx.push(1);
+    tx.extend_from_slice(point);
+    tx.extend_from_slice(&[0; 32]);
+    tx.extend_from_slice(&nullifier);
+    tx.extend_from_slice(point);
+    tx.extend_from_slice(&[0; 192 + 64]);
+    // No outputs or JoinSplits, then the binding signature.
+    tx.extend_from_slice(&[0, 0]);
+    tx.extend_from_slice(&[0; 64]);
+    tx
+}
+
+/// Serializes a `CWalletTx` around the Sapling `transaction` with note
+/// data for its output 0, recording `nullifier` and no witnesses.
+pub(crate) fn received_note_wallet_tx_value(
+    transaction: &[u8],
+    txid_byte: u8,
+    nullifier: [u8; 32],
+) -> Vec<u8> {
+    let mut value = wallet_tx_value(transaction, None);
+    value.extend_from_slice(&[1, 1]);
+    value.extend_from_slice(&[txid_byte; 32]);
+    value.extend_from_slice(&0u32.to_le_bytes());
+    // Note data version, ivk, nullifier, no witnesses.
+    value.extend_from_slice(&1i32.to_le_bytes());
+    value.extend_from_slice(&[0x12; 32]);
+    value.push(1);
+    value.extend_from_slice(&nullifier);
+    value.push(0);
+    value.extend_from_slice(&(-1i32).to_le_bytes());
+    value
+}
+
+/// Counts the bytes each thread has live, so a test can bound the memory it
+/// uses while other tests run alongside it.
+struct CountingAllocator;
+
+thread_local! {
+    static LIVE: Cell<isize> = const { Cell::new(0) };
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+fn track(delta: isize) {
+    // The counters are gone while the thread is being torn down.
+    let _ = LIVE.try_with(|live| {
+        live.set(live.get() + delta);
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(live.get())));
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            track(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        track(-(layout.size() as isize));
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Runs `f`, returning its result and the peak number of bytes it had
+/// allocated on the current thread at any one time.
+pub(crate) fn peak_allocation<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let baseline = LIVE.with(Cell::get);
+    PEAK.with(|peak| peak.set(baseline));
+    let result = f();
+    let peak = PEAK.with(Cell::get);
+    (result, (peak - baseline) as usize)
+}
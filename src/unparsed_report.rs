@@ -0,0 +1,151 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+
+use crate::DBKey;
+
+/// Keynames that zcashd writes but that this crate deliberately does not
+/// parse (encrypted-wallet records and records removed from zcashd).
+///
+/// Leftover records under these keynames are expected; leftover records under
+/// any other keyname indicate a coverage gap.
+pub const KNOWN_UNSUPPORTED_KEYNAMES: &[&str] = &[
+    "chdseed",
+    "ckey",
+    "cmnemonicphrase",
+    "cscript",
+    "csapzkey",
+    "czkey",
+    "mkey",
+];
+
+/// The records left unparsed under a single keyname.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnparsedKeyname {
+    keyname: String,
+    keys: Vec<DBKey>,
+    known_unsupported: bool,
+}
+
+impl UnparsedKeyname {
+    pub fn keyname(&self) -> &str {
+        &self.keyname
+    }
+
+    /// The unparsed keys, in sorted order.
+    pub fn keys(&self) -> &[DBKey] {
+        &self.keys
+    }
+
+    pub fn count(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if the keyname is one zcashd is known to write but
+    /// which this crate does not parse.
+    pub fn is_known_unsupported(&self) -> bool {
+        self.known_unsupported
+    }
+}
+
+/// A summary of the records that were not consumed while parsing a wallet,
+/// grouped by keyname.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UnparsedReport {
+    keynames: BTreeMap<String, UnparsedKeyname>,
+}
+
+impl UnparsedReport {
+    pub fn new(unparsed_keys: &HashSet<DBKey>) -> Self {
+        let mut keynames: BTreeMap<String, UnparsedKeyname> = BTreeMap::new();
+        for key in unparsed_keys {
+            keynames
+                .entry(key.keyname.clone())
+                .or_insert_with(|| UnparsedKeyname {
+                    keyname: key.keyname.clone(),
+                    keys: Vec::new(),
+                    known_unsupported: KNOWN_UNSUPPORTED_KEYNAMES
+                        .contains(&key.keyname.as_str()),
+                })
+                .keys
+                .push(key.clone());
+        }
+        for entry in keynames.values_mut() {
+            entry.keys.sort();
+        }
+        Self { keynames }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keynames.is_empty()
+    }
+
+    /// The total number of unparsed records across all keynames.
+    pub fn total_count(&self) -> usize {
+        self.keynames.values().map(UnparsedKeyname::count).sum()
+    }
+
+    /// The unparsed keynames, in sorted order.
+    pub fn keynames(&self) -> impl Iterator<Item = &UnparsedKeyname> {
+        self.keynames.values()
+    }
+
+    pub fn keyname(&self, keyname: &str) -> Option<&UnparsedKeyname> {
+        self.keynames.get(keyname)
+    }
+
+    /// The unparsed keynames that are not in the known-unsupported allowlist.
+    pub fn unknown_keynames(&self) -> impl Iterator<Item = &UnparsedKeyname> {
+        self.keynames().filter(|entry| !entry.is_known_unsupported())
+    }
+
+    /// Returns `true` if any unparsed record has a keyname outside the
+    /// known-unsupported allowlist.
+    pub fn has_unknown_keynames(&self) -> bool {
+        self.unknown_keynames().next().is_some()
+    }
+
+    pub fn summary(&self) -> String {
+        let mut output = String::new();
+        for entry in self.keynames() {
+            let status = if entry.is_known_unsupported() {
+                "unsupported"
+            } else {
+                "unknown"
+            };
+            writeln!(output, "{}: {} ({})", entry.keyname(), entry.count(), status).unwrap();
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use zewif::Data;
+
+    use super::UnparsedReport;
+    use crate::DBKey;
+
+    #[test]
+    fn test_unparsed_report() {
+        let keys: HashSet<DBKey> = [
+            DBKey::new("ckey", Data::from_slice(&[1])),
+            DBKey::new("ckey", Data::from_slice(&[2])),
+            DBKey::new("mystery", Data::new()),
+        ]
+        .into_iter()
+        .collect();
+        let report = UnparsedReport::new(&keys);
+
+        assert_eq!(report.total_count(), 3);
+        assert_eq!(report.keyname("ckey").unwrap().count(), 2);
+        assert!(report.keyname("ckey").unwrap().is_known_unsupported());
+        assert!(report.has_unknown_keynames());
+        let unknown: Vec<_> = report.unknown_keynames().map(|e| e.keyname()).collect();
+        assert_eq!(unknown, vec!["mystery"]);
+        assert_eq!(report.summary(), "ckey: 2 (unsupported)\nmystery: 1 (unknown)\n");
+
+        assert!(UnparsedReport::new(&HashSet::new()).is_empty());
+    }
+}
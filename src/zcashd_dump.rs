@@ -1,6 +1,6 @@
 use crate::{Error, OptionExt, Result, ResultExt};
 use hex::ToHex;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Write;
 
 use super::BDBDump;
@@ -85,6 +85,14 @@ impl AsRef<[u8]> for DBValue {
     }
 }
 
+/// Key metadata keynames, each with the keynames of the plaintext and
+/// encrypted key records it can describe.
+const METADATA_KEYNAMES: &[(&str, &[&str])] = &[
+    ("keymeta", &["key", "ckey"]),
+    ("sapzkeymeta", &["sapzkey", "csapzkey"]),
+    ("zkeymeta", &["zkey", "czkey"]),
+];
+
 #[derive(Debug)]
 pub struct ZcashdDump {
     records: HashMap<DBKey, DBValue>,
@@ -125,6 +133,44 @@ impl ZcashdDump {
         })
     }
 
+    /// Parses the text output of `db_dump`, such as output captured from a
+    /// subprocess, without going through a file.
+    ///
+    /// Keys are parsed strictly, as by [`from_bdb_dump`](Self::from_bdb_dump).
+    pub fn from_dump_str(s: &str) -> Result<Self> {
+        let dump = BDBDump::from_reader(s.as_bytes())?;
+        Self::from_bdb_dump(&dump, true)
+    }
+
+    /// Checks the dump for inconsistencies that would otherwise surface
+    /// partway through parsing.
+    ///
+    /// Unmatched keys and values and duplicate keys are already rejected
+    /// when the [`BDBDump`] is read, so this checks that every key metadata
+    /// record has the key record it describes, failing with
+    /// [`Error::UnmatchedMetadata`] otherwise.
+    pub fn verify_integrity(&self) -> Result<()> {
+        for (metakind, keynames) in METADATA_KEYNAMES {
+            let Some(metakeys) = self.keys_by_keyname.get(*metakind) else {
+                continue;
+            };
+            let mut metakeys: Vec<_> = metakeys.iter().collect();
+            metakeys.sort();
+            for metakey in metakeys {
+                let described = keynames.iter().any(|keyname| {
+                    self.records
+                        .contains_key(&DBKey::new(*keyname, &metakey.data))
+                });
+                if !described {
+                    return Err(Error::UnmatchedMetadata {
+                        key: metakey.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn records(&self) -> &HashMap<DBKey, DBValue> {
         &self.records
     }
@@ -176,6 +222,37 @@ impl ZcashdDump {
         Ok(records)
     }
 
+    /// The key data following the keyname and the value of every record
+    /// under `keyname`, unparsed and in key order, for inspecting record
+    /// types this crate does not support.
+    pub fn raw_records(&self, keyname: &str) -> Vec<(&[u8], &[u8])> {
+        let Some(keys) = self.keys_by_keyname.get(keyname) else {
+            return Vec::new();
+        };
+        let mut keys: Vec<_> = keys.iter().collect();
+        keys.sort();
+        keys.into_iter()
+            .filter_map(|key| {
+                let value = self.records.get(key)?;
+                let key_data: &[u8] = key.data.as_ref();
+                Some((key_data, value.as_ref()))
+            })
+            .collect()
+    }
+
+    /// Every record whose keyname starts with `prefix`, such as `sap` for
+    /// all the Sapling record families. Unlike
+    /// [`records_for_keyname`](Self::records_for_keyname), finding no records
+    /// is not an error.
+    pub fn records_for_keyname_prefix(&self, prefix: &str) -> HashMap<DBKey, DBValue> {
+        self.keys_by_keyname
+            .iter()
+            .filter(|(keyname, _)| keyname.starts_with(prefix))
+            .flat_map(|(_, keys)| keys)
+            .filter_map(|key| Some((key.clone(), self.records.get(key)?.clone())))
+            .collect()
+    }
+
     pub fn has_keys_for_keyname(&self, keyname: &str) -> bool {
         self.keys_by_keyname.contains_key(keyname)
     }
@@ -207,6 +284,14 @@ impl ZcashdDump {
         }
     }
 
+    /// The number of records under each keyname in the dump.
+    pub fn keyname_histogram(&self) -> BTreeMap<String, usize> {
+        self.keys_by_keyname
+            .iter()
+            .map(|(keyname, keys)| (keyname.clone(), keys.len()))
+            .collect()
+    }
+
     fn sorted_key_names(&self) -> Vec<String> {
         let mut keynames: Vec<String> = self.keys_by_keyname.keys().cloned().collect();
         keynames.sort();
@@ -258,3 +343,96 @@ impl ZcashdDump {
         output
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use zewif::Data;
+
+    use super::{DBKey, ZcashdDump};
+    use crate::{
+        Error,
+        test_support::{
+            compact, dump_from_records, minimal_wallet_dump, minimal_wallet_records,
+            v4_transaction, wallet_tx_value,
+        },
+    };
+
+    #[test]
+    fn test_from_dump_str() {
+        // "version" with no key data, and "pool" with index 1.
+        let output = "VERSION=3\nformat=bytevalue\ntype=btree\nHEADER=END\n \
+                      0776657273696f6e\n 60ea0000\n \
+                      04706f6f6c0100000000000000\n 00\nDATA=END\n";
+        let dump = ZcashdDump::from_dump_str(output).unwrap();
+        assert_eq!(dump.records().len(), 2);
+        assert_eq!(
+            dump.value_for_keyname("version").unwrap().as_data(),
+            &Data::from_slice(&60000u32.to_le_bytes())
+        );
+        let pool_key = DBKey::new("pool", Data::from_slice(&1i64.to_le_bytes()));
+        assert_eq!(dump.value_for_key(&pool_key).unwrap().len(), 1);
+
+        assert_eq!(
+            dump.raw_records("pool"),
+            [(&1i64.to_le_bytes()[..], &[0u8][..])]
+        );
+        assert!(dump.raw_records("tx").is_empty());
+
+        // A keyname longer than the key is rejected.
+        let output = "VERSION=3\nformat=bytevalue\ntype=btree\nHEADER=END\n \
+                      0a706f6f6c\n 00\nDATA=END\n";
+        assert!(ZcashdDump::from_dump_str(output).is_err());
+    }
+
+    #[test]
+    fn test_records_for_keyname_prefix() {
+        let mut records = minimal_wallet_records();
+        records.push(("sapzkey", vec![0x77; 32], vec![0x01; 169]));
+        let dump = dump_from_records(&records);
+
+        let mut keynames: Vec<_> = dump
+            .records_for_keyname_prefix("sap")
+            .into_keys()
+            .map(|key| key.keyname)
+            .collect();
+        keynames.sort();
+        assert_eq!(keynames, ["sapzaddr", "sapzkey"]);
+        assert!(dump.records_for_keyname_prefix("ckey").is_empty());
+    }
+
+    #[test]
+    fn test_raw_tx_records() {
+        let value = wallet_tx_value(&v4_transaction(), None);
+        let mut records = minimal_wallet_records();
+        records.push(("tx", vec![0x10; 32], value.clone()));
+        let dump = dump_from_records(&records);
+        assert_eq!(
+            dump.raw_records("tx"),
+            [(&[0x10u8; 32][..], value.as_slice())]
+        );
+    }
+
+    #[test]
+    fn test_verify_integrity() {
+        minimal_wallet_dump().verify_integrity().unwrap();
+
+        for (metakind, key_data) in [
+            ("keymeta", compact(&[0x03; 33])),
+            ("sapzkeymeta", vec![0x79; 32]),
+            ("zkeymeta", vec![0x89; 64]),
+        ] {
+            let mut records = minimal_wallet_records();
+            records.push((metakind, key_data.clone(), vec![0; 8]));
+            match dump_from_records(&records).verify_integrity() {
+                Err(Error::UnmatchedMetadata { key }) => assert_eq!(key, format!("{metakind}-{}", hex::encode(&key_data))),
+                other => panic!("unexpected result for {metakind}: {other:?}"),
+            }
+        }
+
+        // Metadata for an encrypted key has its key record under `ckey`.
+        let mut records = minimal_wallet_records();
+        records.push(("ckey", compact(&[0x03; 33]), vec![0; 48]));
+        records.push(("keymeta", compact(&[0x03; 33]), vec![0; 8]));
+        dump_from_records(&records).verify_integrity().unwrap();
+    }
+}
@@ -1,22 +1,29 @@
 use crate::{Error, Result, ResultExt};
 use hex::ToHex as _;
 use std::{
-    cell::RefCell,
-    collections::{HashMap, HashSet},
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap, HashSet},
 };
 use zcash_keys::keys::UnifiedFullViewingKey;
 use zewif::{
-    Bip39Mnemonic, Data, LegacySeed, SeedFingerprint, TxId, sapling::SaplingIncomingViewingKey,
+    Bip39Mnemonic, Blob32, Data, LegacySeed, Script, SeedFingerprint, TxId,
+    sapling::SaplingIncomingViewingKey,
 };
 
 use crate::{
-    DBValue, ZcashdDump, ZcashdWallet, parse,
+    DBValue, NoopObserver, ParseMode, ParseProgress, ParserObserver, StderrObserver,
+    UnparsedReport, ZcashdDump, ZcashdWallet,
+    migrate::{bip39_seed, validate_mnemonic_checksum},
+    parse,
     parser::prelude::*,
     zcashd_dump::DBKey,
     zcashd_wallet::{
-        Address, BlockLocator, ClientVersion, KeyMetadata, MnemonicHDChain, NetworkInfo,
-        RecipientAddress, RecipientMapping, UfvkFingerprint, UnifiedAccountMetadata,
-        UnifiedAccounts, UnifiedAddressMetadata,
+        AccountingEntry, Address, AddressNames, AddressPurposes, BlockLocator, ChainState,
+        ClientVersion, KeyMaterial,
+        KeyMetadata, LegacyAccount, LegacyHDChain, Metadata, MnemonicHDChain,
+        NetworkInfo, Purpose,
+        RecipientAddress, RecipientMapping, Transactions, UfvkFingerprint,
+        UnifiedAccountMetadata, UnifiedAccounts, UnifiedAddressMetadata,
         orchard::OrchardNoteCommitmentTree,
         sapling::{SaplingKey, SaplingKeys, SaplingZPaymentAddress},
         sprout::{SproutKeys, SproutPaymentAddress, SproutSpendingKey},
@@ -25,31 +32,313 @@ use crate::{
     },
 };
 
-#[derive(Debug)]
 pub struct ZcashdParser<'a> {
     pub dump: &'a ZcashdDump,
     pub unparsed_keys: RefCell<HashSet<DBKey>>,
-    pub strict: bool,
+    pub mode: ParseMode,
+    track_unparsed_keys: bool,
+    collected_errors: RefCell<Vec<Error>>,
+    observer: Box<dyn ParserObserver>,
+    progress: RefCell<Option<Box<dyn FnMut(ParseProgress) + 'a>>>,
+    categories_parsed: Cell<usize>,
+}
+
+// The number of record categories `parse_wallet` reports progress for.
+const CATEGORY_COUNT: usize = 30;
+
+// Parses all of `data`, a record's key or value, as `T`. Trailing bytes are
+// reported with the record's `keyname`. Non-canonical encodings are only
+// rejected when `strict` is set.
+fn parse_record<T: Parse>(
+    data: &dyn AsRef<[u8]>,
+    keyname: &str,
+    context: impl std::fmt::Display,
+    strict: bool,
+) -> Result<T> {
+    let mut p = Parser::new(data);
+    p.set_strict(strict);
+    let parsed = parse!(&mut p, T, context)?;
+    p.check_finished_ctx(&format!("'{keyname}' record"))?;
+    Ok(parsed)
+}
+
+// A `tx` record with its decoded transaction ID and transaction.
+type DecodedTx = (DBKey, DBValue, Result<TxId>, Result<WalletTx>);
+
+// Decodes the transaction ID and transaction of each `tx` record. Records
+// decode independently, so with the `parallel` feature the work is spread
+// across threads; either way the results are in the order of `records`.
+fn decode_tx_records(records: Vec<(DBKey, DBValue)>, strict: bool) -> Vec<DecodedTx> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        records
+            .into_par_iter()
+            .map(|record| decode_tx_record(record, strict))
+            .collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        records
+            .into_iter()
+            .map(|record| decode_tx_record(record, strict))
+            .collect()
+    }
+}
+
+fn decode_tx_record((key, value): (DBKey, DBValue), strict: bool) -> DecodedTx {
+    let txid = parse_record::<TxId>(&key.data, "tx", "transaction ID", strict);
+    let transaction = parse_record::<WalletTx>(value.as_data(), "tx", "transaction", strict);
+    (key, value, txid, transaction)
+}
+
+// The height of the wallet's best block. zcashd writes the best block in the
+// same batch as the Orchard tree's checkpoint and the notes' witness heights,
+// which all advance to each block the wallet connects.
+fn best_block_height(
+    orchard_note_commitment_tree: &OrchardNoteCommitmentTree,
+    transactions: &HashMap<TxId, WalletTx>,
+) -> Option<u32> {
+    if let Some(height) = orchard_note_commitment_tree.last_checkpoint() {
+        return Some(u32::from(height));
+    }
+    let sapling = transactions
+        .values()
+        .flat_map(|tx| tx.sapling_note_data().into_iter().flatten())
+        .map(|(_, note_data)| note_data.witness_height());
+    let sprout = transactions
+        .values()
+        .flat_map(|tx| tx.map_sprout_note_data())
+        .map(|(_, note_data)| note_data.witness_height());
+    sapling
+        .chain(sprout)
+        .filter_map(|height| u32::try_from(height).ok())
+        .max()
+}
+
+impl std::fmt::Debug for ZcashdParser<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZcashdParser")
+            .field("dump", &self.dump)
+            .field("unparsed_keys", &self.unparsed_keys)
+            .field("mode", &self.mode)
+            .field("track_unparsed_keys", &self.track_unparsed_keys)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<'a> ZcashdParser<'a> {
-    pub fn parse_dump(dump: &ZcashdDump, strict: bool) -> Result<(ZcashdWallet, HashSet<DBKey>)> {
-        let parser = ZcashdParser::new(dump, strict);
+    /// Parses the dump in the given mode. Passing a `bool` selects
+    /// [`ParseMode::StrictFailFast`] (`true`) or [`ParseMode::Lenient`] (`false`).
+    pub fn parse_dump(
+        dump: &ZcashdDump,
+        mode: impl Into<ParseMode>,
+    ) -> Result<(ZcashdWallet, HashSet<DBKey>)> {
+        let parser = ZcashdParser::new(dump, mode);
+        parser.parse()
+    }
+
+    /// Parses the dump, reporting recoverable errors to `observer` instead of
+    /// stderr. Passing `None` discards them.
+    pub fn parse_dump_with_observer(
+        dump: &ZcashdDump,
+        mode: impl Into<ParseMode>,
+        observer: Option<Box<dyn ParserObserver>>,
+    ) -> Result<(ZcashdWallet, HashSet<DBKey>)> {
+        let parser = ZcashdParser::new_with_observer(dump, mode, observer);
         parser.parse()
     }
 
-    fn new(dump: &'a ZcashdDump, strict: bool) -> Self {
-        let unparsed_keys = RefCell::new(dump.records().keys().cloned().collect());
+    /// Parses the dump, calling `progress` after each record category so that
+    /// front-ends can show how far the parse has got.
+    pub fn parse_dump_with_progress<'d>(
+        dump: &'d ZcashdDump,
+        mode: impl Into<ParseMode>,
+        progress: impl FnMut(ParseProgress) + 'd,
+    ) -> Result<(ZcashdWallet, HashSet<DBKey>)> {
+        let mut parser = ZcashdParser::new(dump, mode);
+        parser.set_progress(Some(Box::new(progress)));
+        parser.parse()
+    }
+
+    /// Parses the dump, grouping any records that were not consumed by keyname
+    /// so callers can tell known-unsupported records from unknown ones.
+    pub fn parse_with_report(
+        dump: &ZcashdDump,
+        mode: impl Into<ParseMode>,
+    ) -> Result<(ZcashdWallet, UnparsedReport)> {
+        let (wallet, unparsed_keys) = Self::parse_dump(dump, mode)?;
+        Ok((wallet, UnparsedReport::new(&unparsed_keys)))
+    }
+
+    /// Parses the dump without tracking which records were left unparsed.
+    ///
+    /// This avoids copying every key in the dump up front, which matters for
+    /// very large wallets when the caller has no use for the unparsed keys.
+    pub fn parse_dump_fast(dump: &ZcashdDump, mode: impl Into<ParseMode>) -> Result<ZcashdWallet> {
+        let parser =
+            ZcashdParser::with_options(dump, mode.into(), Box::new(StderrObserver), false);
+        let (wallet, _) = parser.parse()?;
+        Ok(wallet)
+    }
+
+    /// Parses as much of the dump as possible, for recovering data from a
+    /// damaged wallet.
+    ///
+    /// A record category that fails to parse, such as `key` or `pool`, does
+    /// not abort the parse: that part of the wallet is left at its default and
+    /// the error is returned under the category's keyname. Within a category,
+    /// parsing is lenient and recoverable errors are written to stderr.
+    pub fn parse_best_effort(dump: &ZcashdDump) -> (ZcashdWallet, Vec<(&'static str, Error)>) {
+        let parser =
+            ZcashdParser::with_options(dump, ParseMode::Lenient, Box::new(StderrObserver), false);
+        let mut failures = Vec::new();
+        let wallet = parser
+            .parse_wallet(&mut |category, err| {
+                failures.push((category, err));
+                Ok(())
+            })
+            .expect("every failed category is accepted");
+        (wallet, failures)
+    }
+
+    /// Creates a parser over `dump`, tracking every record as unparsed until it
+    /// is consumed. Recoverable errors are written to stderr.
+    pub fn new(dump: &'a ZcashdDump, mode: impl Into<ParseMode>) -> Self {
+        Self::new_with_observer(dump, mode, Some(Box::new(StderrObserver)))
+    }
+
+    /// Creates a parser that reports recoverable errors to `observer`. Passing
+    /// `None` installs a [`NoopObserver`].
+    pub fn new_with_observer(
+        dump: &'a ZcashdDump,
+        mode: impl Into<ParseMode>,
+        observer: Option<Box<dyn ParserObserver>>,
+    ) -> Self {
+        let observer = observer.unwrap_or_else(|| Box::new(NoopObserver));
+        Self::with_options(dump, mode.into(), observer, true)
+    }
+
+    /// Installs a callback to be told of progress after each record category,
+    /// or removes it with `None`. There is none by default.
+    pub fn set_progress(&mut self, progress: Option<Box<dyn FnMut(ParseProgress) + 'a>>) {
+        *self.progress.get_mut() = progress;
+    }
+
+    fn with_options(
+        dump: &'a ZcashdDump,
+        mode: ParseMode,
+        observer: Box<dyn ParserObserver>,
+        track_unparsed_keys: bool,
+    ) -> Self {
+        let unparsed_keys = if track_unparsed_keys {
+            dump.records().keys().cloned().collect()
+        } else {
+            HashSet::new()
+        };
         Self {
             dump,
-            unparsed_keys,
-            strict,
+            unparsed_keys: RefCell::new(unparsed_keys),
+            mode,
+            track_unparsed_keys,
+            collected_errors: RefCell::new(Vec::new()),
+            observer,
+            progress: RefCell::new(None),
+            categories_parsed: Cell::new(0),
+        }
+    }
+
+    // Returns the parsed value of `category` or, when `on_failure` accepts its
+    // error, the default value. Either way the category counts as parsed.
+    fn recover<T: Default>(
+        &self,
+        category: &'static str,
+        result: Result<T>,
+        on_failure: &mut dyn FnMut(&'static str, Error) -> Result<()>,
+    ) -> Result<T> {
+        let value = result.or_else(|err| {
+            on_failure(category, err)?;
+            Ok(T::default())
+        })?;
+        self.report_progress(category);
+        Ok(value)
+    }
+
+    // Returns `parse()`, unless the `view-only` feature is enabled. Then the
+    // `keynames` records, which hold spending keys or seeds, are marked as
+    // parsed without being decoded and the category is left at its default.
+    fn parse_secrets<T: Default>(
+        &self,
+        keynames: &[&str],
+        parse: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        if !cfg!(feature = "view-only") {
+            return parse();
+        }
+        let keys_by_keyname = self.dump.keys_by_keyname();
+        for keyname in keynames {
+            for key in keys_by_keyname.get(*keyname).into_iter().flatten() {
+                self.mark_key_parsed(key);
+            }
+        }
+        Ok(T::default())
+    }
+
+    fn report_progress(&self, category: &'static str) {
+        let mut progress = self.progress.borrow_mut();
+        let Some(progress) = progress.as_mut() else {
+            return;
+        };
+        let categories_parsed = self.categories_parsed.get() + 1;
+        self.categories_parsed.set(categories_parsed);
+        let records_total = self.dump.records().len();
+        progress(ParseProgress {
+            category,
+            categories_parsed,
+            categories_total: CATEGORY_COUNT,
+            records_parsed: records_total - self.unparsed_keys.borrow().len(),
+            records_total,
+        });
+    }
+
+    fn report_recoverable_error(&self, context: &str, err: &Error) {
+        self.observer.on_recoverable_error(context, err);
+    }
+
+    // In `StrictCollectAll` mode, errors are held until the end of `parse`
+    fn collect_error(&self, context: String, err: Error) {
+        self.collected_errors
+            .borrow_mut()
+            .push(Error::with_context(err, context));
+    }
+
+    // Keypairs are only verified in the strict modes, since deriving each
+    // public key is comparatively expensive.
+    fn verify_in_strict_mode(
+        &self,
+        pubkey: &PubKey,
+        verify: impl FnOnce() -> Result<()>,
+    ) -> Result<()> {
+        if !self.mode.is_strict() {
+            return Ok(());
+        }
+        match verify() {
+            Err(e) if self.mode == ParseMode::StrictCollectAll => {
+                self.collect_error(
+                    format!("Verifying key {}", pubkey.as_slice().encode_hex::<String>()),
+                    e,
+                );
+                Ok(())
+            }
+            result => result,
         }
     }
 
     // Keep track of which keys have been parsed
     fn mark_key_parsed(&self, key: &DBKey) {
-        self.unparsed_keys.borrow_mut().remove(key);
+        if self.track_unparsed_keys {
+            self.unparsed_keys.borrow_mut().remove(key);
+        }
     }
 
     fn value_for_keyname(&self, keyname: &str) -> Result<&DBValue> {
@@ -58,16 +347,57 @@ impl<'a> ZcashdParser<'a> {
         self.dump.value_for_keyname(keyname)
     }
 
+    // `parse_record` with the parse mode's strictness.
+    fn parse_record<T: Parse>(
+        &self,
+        data: &dyn AsRef<[u8]>,
+        keyname: &str,
+        context: impl std::fmt::Display,
+    ) -> Result<T> {
+        parse_record(data, keyname, context, self.mode.is_strict())
+    }
+
     fn parse(&self) -> Result<(ZcashdWallet, HashSet<DBKey>)> {
+        let wallet = self.parse_wallet(&mut |category, err| {
+            if self.mode != ParseMode::StrictCollectAll {
+                return Err(err);
+            }
+            self.collect_error(format!("Parsing '{category}' records"), err);
+            Ok(())
+        })?;
+
+        let errors = self.collected_errors.take();
+        if !errors.is_empty() {
+            return Err(Error::Multiple { errors });
+        }
+
+        Ok((wallet, self.unparsed_keys.borrow().clone()))
+    }
+
+    // Parses each record category in turn. A category that fails is passed to
+    // `on_failure`, which either returns the error to abort the parse or
+    // accepts it, leaving that part of the wallet at its default.
+    fn parse_wallet(
+        &self,
+        on_failure: &mut dyn FnMut(&'static str, Error) -> Result<()>,
+    ) -> Result<ZcashdWallet> {
         //
         // Since version 3
         //
 
         // ~~acc~~: Removed in 4.5.0
-        // ~~acentry~~: Removed in 4.5.0
+        let legacy_accounts = self.recover("acc", self.parse_legacy_accounts(), on_failure)?;
 
-        // **bestblock**: Empty in 6.0.0
-        let bestblock = self.parse_block_locator("bestblock")?;
+        // ~~acentry~~: Removed in 4.5.0
+        let accounting_entries =
+            self.recover("acentry", self.parse_accounting_entries(), on_failure)?;
+
+        // **bestblock**: Empty in 6.0.0, superseded by `bestblock_nomerkle`
+        let bestblock = self.recover(
+            "bestblock",
+            self.parse_block_locator("bestblock"),
+            on_failure,
+        )?;
 
         // ~~**chdseed**~~: Removed in 5.0.0
 
@@ -80,131 +410,209 @@ impl<'a> ZcashdParser<'a> {
         // czkey
 
         // **defaultkey**
-        let default_key = self.parse_default_key()?;
+        let default_key = self.recover("defaultkey", self.parse_default_key(), on_failure)?;
 
         // destdata
+        let dest_data = self.recover("destdata", self.parse_dest_data(), on_failure)?;
 
         // **hdchain**
+        let legacy_hd_chain =
+            self.recover("hdchain", self.parse_opt_legacy_hd_chain(), on_failure)?;
 
         // hdseed
-        let legacy_hd_seed = self.parse_hdseed()?;
+        let legacy_hd_seed = self.recover(
+            "hdseed",
+            self.parse_secrets(&["hdseed"], || self.parse_hdseed()),
+            on_failure,
+        )?;
 
         // key
         // keymeta
-        let keys = self.parse_keys()?;
+        let keys = self.recover(
+            "key",
+            self.parse_secrets(&["key", "keymeta"], || self.parse_keys()),
+            on_failure,
+        )?;
 
         // **minversion**
-        let min_version = self.parse_client_version("minversion")?;
+        let min_version = self.recover(
+            "minversion",
+            self.parse_client_version("minversion"),
+            on_failure,
+        )?;
 
         // **mkey**
 
         // name
-        let address_names = self.parse_address_names()?;
+        let address_names = self.recover("name", self.parse_address_names(), on_failure)?;
 
         // **orderposnext**
-        let orderposnext = self.parse_opt_i64("orderposnext")?;
+        let orderposnext = self.recover(
+            "orderposnext",
+            self.parse_opt_i64("orderposnext"),
+            on_failure,
+        )?;
 
         // pool
-        let key_pool = self.parse_key_pool()?;
+        let key_pool = self.recover("pool", self.parse_key_pool(), on_failure)?;
 
         // purpose
-        let address_purposes = self.parse_address_purposes()?;
+        let address_purposes =
+            self.recover("purpose", self.parse_address_purposes(), on_failure)?;
 
         // sapzaddr
-        let sapling_z_addresses = self.parse_sapling_z_addresses()?;
+        let sapling_z_addresses =
+            self.recover("sapzaddr", self.parse_sapling_z_addresses(), on_failure)?;
 
         // sapextfvk
+        let sapling_extended_fvks =
+            self.recover("sapextfvk", self.parse_sapling_extended_fvks(), on_failure)?;
 
         // sapzkey
-        let sapling_keys = self.parse_sapling_keys()?;
+        let sapling_keys = self.recover(
+            "sapzkey",
+            self.parse_secrets(&["sapzkey", "sapzkeymeta"], || self.parse_sapling_keys()),
+            on_failure,
+        )?;
 
         // tx
-        let transactions = self.parse_transactions(self.strict)?;
+        let transactions = self.recover("tx", self.parse_transactions(), on_failure)?;
 
         // **version**
-        let client_version = self.parse_client_version("version")?;
+        let client_version =
+            self.recover("version", self.parse_client_version("version"), on_failure)?;
 
         // vkey
+        let viewing_keys = self.recover("vkey", self.parse_viewing_keys(), on_failure)?;
 
         // watchs
+        let watch_only_scripts = self.recover("watchs", self.parse_watch_only(), on_failure)?;
 
         // **witnesscachesize**
-        let witnesscachesize = self.parse_i64("witnesscachesize")?;
+        let witnesscachesize = self.recover(
+            "witnesscachesize",
+            self.parse_i64("witnesscachesize"),
+            on_failure,
+        )?;
 
         // wkey
-        let wallet_keys = self.parse_wallet_keys()?;
+        let wallet_keys = self.recover(
+            "wkey",
+            self.parse_secrets(&["wkey"], || self.parse_wallet_keys()),
+            on_failure,
+        )?;
 
         // zkey
         // zkeymeta
-        let sprout_keys = self.parse_sprout_keys()?;
+        let sprout_keys = self.recover(
+            "zkey",
+            self.parse_secrets(&["zkey", "zkeymeta"], || self.parse_sprout_keys()),
+            on_failure,
+        )?;
 
         //
         // Since version 5
         //
 
         // **networkinfo**
-        let network_info = self.parse_network_info()?;
+        let network_info = self.recover("networkinfo", self.parse_network_info(), on_failure)?;
 
         // **orchard_note_commitment_tree**
-        let orchard_note_commitment_tree = self.parse_orchard_note_commitment_tree()?;
+        let orchard_note_commitment_tree = self.recover(
+            "orchard_note_commitment_tree",
+            self.parse_orchard_note_commitment_tree(),
+            on_failure,
+        )?;
 
         // unifiedaccount
 
         // unifiedfvk
 
         // unifiedaddrmeta
-        let unified_accounts = self.parse_unified_accounts()?;
+        let unified_accounts =
+            self.recover("unifiedaccount", self.parse_unified_accounts(), on_failure)?;
 
         // **mnemonicphrase**
-        let mnemonic_phrase = self.parse_mnemonic_phrase()?;
+        let mnemonic_phrase = self.recover(
+            "mnemonicphrase",
+            self.parse_secrets(&["mnemonicphrase"], || self.parse_opt_mnemonic_phrase()),
+            on_failure,
+        )?;
 
         // **cmnemonicphrase**
 
         // **mnemonichdchain**
-        let mnemonic_hd_chain = self.parse_mnemonic_hd_chain()?;
+        let mnemonic_hd_chain = self.recover(
+            "mnemonichdchain",
+            self.parse_opt_mnemonic_hd_chain(),
+            on_failure,
+        )?;
 
         // recipientmapping
-        let send_recipients = self.parse_send_recipients()?;
+        let send_recipients =
+            self.recover("recipientmapping", self.parse_send_recipients(), on_failure)?;
 
         //
         // Since version 6
         //
 
         // **bestblock_nomerkle**
-        let bestblock_nomerkle = self.parse_opt_block_locator("bestblock_nomerkle")?;
+        let bestblock_nomerkle = self.recover(
+            "bestblock_nomerkle",
+            self.parse_opt_block_locator("bestblock_nomerkle"),
+            on_failure,
+        )?;
 
-        let wallet = ZcashdWallet::new(
-            address_names,
-            address_purposes,
-            bestblock_nomerkle,
-            bestblock,
-            client_version,
+        let key_material = KeyMaterial {
+            bip39_mnemonic: mnemonic_phrase,
             default_key,
             key_pool,
             keys,
-            min_version,
+            legacy_hd_chain,
             legacy_hd_seed,
             mnemonic_hd_chain,
-            mnemonic_phrase,
-            network_info,
-            orchard_note_commitment_tree,
-            orderposnext,
+            sapling_extended_fvks,
             sapling_keys,
             sapling_z_addresses,
-            send_recipients,
             sprout_keys,
-            wallet_keys,
-            transactions,
             unified_accounts,
+            viewing_keys,
+            wallet_keys,
+            watch_only_scripts,
+        };
+        let metadata = Metadata {
+            address_names,
+            address_purposes,
+            client_version,
+            dest_data,
+            legacy_accounts,
+            min_version,
+            network_info,
+        };
+        let best_height = best_block_height(&orchard_note_commitment_tree, &transactions);
+        let chain_state = ChainState {
+            bestblock: bestblock.with_height(best_height),
+            bestblock_nomerkle: bestblock_nomerkle.map(|locator| locator.with_height(best_height)),
+            orchard_note_commitment_tree,
             witnesscachesize,
-        );
-
-        Ok((wallet, self.unparsed_keys.borrow().clone()))
+        };
+        let transactions = Transactions {
+            accounting_entries,
+            orderposnext,
+            send_recipients,
+            transactions,
+        };
+        Ok(ZcashdWallet::new(
+            key_material,
+            metadata,
+            chain_state,
+            transactions,
+        ))
     }
 
     fn parse_i64(&self, keyname: &str) -> Result<i64> {
         let value = self.value_for_keyname(keyname)?;
-        parse!(buf = value, i64, format!("i64 for keyname: {}", keyname))
+        self.parse_record::<i64>(value, keyname, format!("i64 for keyname: {}", keyname))
     }
 
     fn parse_opt_i64(&self, keyname: &str) -> Result<Option<i64>> {
@@ -217,19 +625,24 @@ impl<'a> ZcashdParser<'a> {
 
     fn parse_client_version(&self, keyname: &str) -> Result<ClientVersion> {
         let value = self.value_for_keyname(keyname)?;
-        parse!(
-            buf = value,
-            ClientVersion,
-            format!("client version for keyname: {}", keyname)
+        self.parse_record::<ClientVersion>(
+            value,
+            keyname,
+            format!("client version for keyname: {}", keyname),
         )
     }
 
     fn parse_block_locator(&self, keyname: &str) -> Result<BlockLocator> {
         let value = self.value_for_keyname(keyname)?;
-        parse!(
-            buf = value,
-            BlockLocator,
-            format!("block locator for keyname: {}", keyname)
+        // zcashd 6.0.0 and later can leave `bestblock` without a value, as
+        // they keep the best block in `bestblock_nomerkle`.
+        if value.as_data().is_empty() {
+            return Ok(BlockLocator::empty());
+        }
+        self.parse_record::<BlockLocator>(
+            value,
+            keyname,
+            format!("block locator for keyname: {}", keyname),
         )
     }
 
@@ -241,32 +654,58 @@ impl<'a> ZcashdParser<'a> {
         }
     }
 
+    // Finds the metadata record keyed like `key`, such as the `keymeta` of a
+    // `key`. A missing record is reported under `identifier`, so the error
+    // names the key that lacks metadata.
+    fn key_metadata(
+        &self,
+        key: &DBKey,
+        metakind: &'static str,
+        identifier: impl FnOnce() -> String,
+    ) -> Result<(DBKey, KeyMetadata)> {
+        let metakey = DBKey::new(metakind, &key.data);
+        let Ok(metadata_binary) = self.dump.value_for_key(&metakey) else {
+            return Err(Error::MissingRecord {
+                kind: metakind,
+                key: identifier(),
+            });
+        };
+        let metadata = self.parse_record::<KeyMetadata>(
+            metadata_binary,
+            metakind,
+            format!("{metakind} metadata"),
+        )?;
+        // Lenient parsing keeps what a newer version adds as opaque bytes;
+        // the strict modes refuse to guess at its layout.
+        if self.mode.is_strict() && metadata.is_future_version() {
+            let e = Error::UnsupportedVersion {
+                kind: metakind,
+                version: metadata.version(),
+            };
+            match self.mode {
+                ParseMode::StrictCollectAll => {
+                    self.collect_error(format!("Checking {metakind} version"), e)
+                }
+                _ => return Err(e),
+            }
+        }
+        Ok((metakey, metadata))
+    }
+
     fn parse_keys(&self) -> Result<Keys> {
         let key_records = self
             .dump
             .records_for_keyname("key")
             .context("Getting 'key' records")?;
-        let keymeta_records = self
-            .dump
-            .records_for_keyname("keymeta")
-            .context("Getting 'keymeta' records")?;
-        if key_records.len() != keymeta_records.len() {
-            return Err(Error::MismatchedRecords {
-                kind: "key/keymeta",
-            });
-        }
         let mut keys_map = HashMap::new();
         for (key, value) in key_records {
-            let pubkey = parse!(buf = &key.data, PubKey, "pubkey")?;
-            let privkey = parse!(buf = value.as_data(), PrivKey, "privkey")?;
-            let metakey = DBKey::new("keymeta", &key.data);
-            let metadata_binary = self
-                .dump
-                .value_for_key(&metakey)
-                .context("Getting metadata")?;
-            let metadata = parse!(buf = metadata_binary, KeyMetadata, "metadata")?;
+            let pubkey = self.parse_record::<PubKey>(&key.data, "key", "pubkey")?;
+            let privkey = self.parse_record::<PrivKey>(value.as_data(), "key", "privkey")?;
+            let (metakey, metadata) =
+                self.key_metadata(&key, "keymeta", || pubkey.as_slice().encode_hex::<String>())?;
             let keypair = KeyPair::new(pubkey.clone(), privkey.clone(), metadata)
                 .context("Creating keypair")?;
+            self.verify_in_strict_mode(&pubkey, || keypair.verify())?;
             keys_map.insert(pubkey, keypair);
 
             self.mark_key_parsed(&key);
@@ -288,8 +727,9 @@ impl<'a> ZcashdParser<'a> {
         }
         let mut keys_map = HashMap::new();
         for (key, value) in key_records {
-            let pubkey = parse!(buf = &key.data, PubKey, "pubkey")?;
+            let pubkey = self.parse_record::<PubKey>(&key.data, "wkey", "pubkey")?;
             let mut parser = Parser::new(value.as_data());
+            parser.set_strict(self.mode.is_strict());
             let privkey = parse!(&mut parser, PrivKey, "privkey")?;
             let time_created = parse!(&mut parser, SecondsSinceEpoch, "time_created")?;
             let time_expires = parse!(&mut parser, SecondsSinceEpoch, "time_expires")?;
@@ -301,6 +741,7 @@ impl<'a> ZcashdParser<'a> {
                 time_expires,
                 comment,
             );
+            self.verify_in_strict_mode(&pubkey, || wallet_key.verify())?;
             keys_map.insert(pubkey, wallet_key);
 
             self.mark_key_parsed(&key);
@@ -308,6 +749,125 @@ impl<'a> ZcashdParser<'a> {
         Ok(Some(WalletKeys::new(keys_map)))
     }
 
+    fn parse_legacy_accounts(&self) -> Result<HashMap<String, LegacyAccount>> {
+        let mut accounts = HashMap::new();
+        if !self.dump.has_keys_for_keyname("acc") {
+            return Ok(accounts);
+        }
+        let records = self
+            .dump
+            .records_for_keyname("acc")
+            .context("Getting 'acc' records")?;
+        for (key, value) in records {
+            let name = self.parse_record::<String>(&key.data, "acc", "account name")?;
+            let account = self.parse_record::<LegacyAccount>(&value, "acc", "account")?;
+            accounts.insert(name, account);
+            self.mark_key_parsed(&key);
+        }
+        Ok(accounts)
+    }
+
+    fn parse_accounting_entries(&self) -> Result<HashMap<String, BTreeMap<u64, AccountingEntry>>> {
+        let mut entries: HashMap<String, BTreeMap<u64, AccountingEntry>> = HashMap::new();
+        if !self.dump.has_keys_for_keyname("acentry") {
+            return Ok(entries);
+        }
+        let records = self
+            .dump
+            .records_for_keyname("acentry")
+            .context("Getting 'acentry' records")?;
+        for (key, value) in records {
+            let mut p = Parser::new(&key.data);
+            p.set_strict(self.mode.is_strict());
+            let account = parse!(&mut p, String, "account name")?;
+            let entry_number = parse!(&mut p, u64, "entry number")?;
+            p.check_finished_ctx("'acentry' record")?;
+            let entry =
+                self.parse_record::<AccountingEntry>(&value, "acentry", "accounting entry")?;
+            entries
+                .entry(account)
+                .or_default()
+                .insert(entry_number, entry);
+            self.mark_key_parsed(&key);
+        }
+        Ok(entries)
+    }
+
+    fn parse_viewing_keys(&self) -> Result<Vec<::sapling::zip32::ExtendedFullViewingKey>> {
+        let mut viewing_keys = Vec::new();
+        if !self.dump.has_keys_for_keyname("vkey") {
+            return Ok(viewing_keys);
+        }
+        let records = self
+            .dump
+            .records_for_keyname("vkey")
+            .context("Getting 'vkey' records")?;
+        let mut sorted_records: Vec<_> = records.into_iter().collect();
+        sorted_records.sort_by(|(key1, _), (key2, _)| key1.data.cmp(&key2.data));
+        for (key, value) in sorted_records {
+            match self.parse_record::<::sapling::zip32::ExtendedFullViewingKey>(
+                &key.data,
+                "vkey",
+                "viewing key",
+            ) {
+                Ok(viewing_key) => {
+                    // zcashd only loads the key when the value is the character '1'
+                    let flag =
+                        self.parse_record::<u8>(value.as_data(), "vkey", "viewing key flag")?;
+                    if flag == b'1' {
+                        viewing_keys.push(viewing_key);
+                    }
+                }
+                Err(e) if self.mode == ParseMode::Lenient => {
+                    self.report_recoverable_error(
+                        &format!(
+                            "Unable to parse viewing key {}",
+                            key.data.encode_hex::<String>()
+                        ),
+                        &e,
+                    );
+                }
+                Err(e) if self.mode == ParseMode::StrictCollectAll => {
+                    self.collect_error(
+                        format!(
+                            "Unable to parse viewing key {}",
+                            key.data.encode_hex::<String>()
+                        ),
+                        e,
+                    );
+                }
+                err => {
+                    err?;
+                }
+            }
+
+            self.mark_key_parsed(&key);
+        }
+        Ok(viewing_keys)
+    }
+
+    fn parse_watch_only(&self) -> Result<HashSet<Script>> {
+        let mut watch_only_scripts = HashSet::new();
+        if !self.dump.has_keys_for_keyname("watchs") {
+            return Ok(watch_only_scripts);
+        }
+        let records = self
+            .dump
+            .records_for_keyname("watchs")
+            .context("Getting 'watchs' records")?;
+        for (key, value) in records {
+            let script = self.parse_record::<Script>(&key.data, "watchs", "watch-only script")?;
+            // zcashd only loads the script when the value is the character '1'
+            let flag = self.parse_record::<u8>(value.as_data(), "watchs", "watch-only flag")?;
+            if flag == b'1' {
+                watch_only_scripts.insert(script);
+            }
+
+            self.mark_key_parsed(&key);
+        }
+        Ok(watch_only_scripts)
+    }
+
     fn parse_sapling_keys(&self) -> Result<SaplingKeys> {
         let mut keys_map = HashMap::new();
         if !self.dump.has_keys_for_keyname("sapzkey") {
@@ -317,28 +877,16 @@ impl<'a> ZcashdParser<'a> {
             .dump
             .records_for_keyname("sapzkey")
             .context("Getting 'sapzkey' records")?;
-        let keymeta_records = self
-            .dump
-            .records_for_keyname("sapzkeymeta")
-            .context("Getting 'sapzkeymeta' records")?;
-        if key_records.len() != keymeta_records.len() {
-            return Err(Error::MismatchedRecords {
-                kind: "sapzkey/sapzkeymeta",
-            });
-        }
         for (key, value) in key_records {
-            let ivk = parse!(buf = &key.data, SaplingIncomingViewingKey, "ivk")?;
-            let spending_key = parse!(
-                buf = value.as_data(),
-                ::sapling::zip32::ExtendedSpendingKey,
-                "spending_key"
+            let ivk =
+                self.parse_record::<SaplingIncomingViewingKey>(&key.data, "sapzkey", "ivk")?;
+            let spending_key = self.parse_record::<::sapling::zip32::ExtendedSpendingKey>(
+                value.as_data(),
+                "sapzkey",
+                "spending_key",
             )?;
-            let metakey = DBKey::new("sapzkeymeta", &key.data);
-            let metadata_binary = self
-                .dump
-                .value_for_key(&metakey)
-                .context("Getting sapzkeymeta metadata")?;
-            let metadata = parse!(buf = metadata_binary, KeyMetadata, "sapzkeymeta metadata")?;
+            let (metakey, metadata) =
+                self.key_metadata(&key, "sapzkeymeta", || key.data.encode_hex::<String>())?;
             let keypair =
                 SaplingKey::new(ivk, spending_key.clone(), metadata).context("Creating keypair")?;
             keys_map.insert(ivk, keypair);
@@ -357,25 +905,14 @@ impl<'a> ZcashdParser<'a> {
             .dump
             .records_for_keyname("zkey")
             .context("Getting 'zkey' records")?;
-        let zkeymeta_records = self
-            .dump
-            .records_for_keyname("zkeymeta")
-            .context("Getting 'zkeymeta' records")?;
-        if zkey_records.len() != zkeymeta_records.len() {
-            return Err(Error::MismatchedRecords {
-                kind: "zkey/zkeymeta",
-            });
-        }
         let mut zkeys_map = HashMap::new();
         for (key, value) in zkey_records {
-            let payment_address = parse!(buf = &key.data, SproutPaymentAddress, "payment_address")?;
-            let spending_key = parse!(buf = value.as_data(), u252, "spending_key")?;
-            let metakey = DBKey::new("zkeymeta", &key.data);
-            let metadata_binary = self
-                .dump
-                .value_for_key(&metakey)
-                .context("Getting metadata")?;
-            let metadata = parse!(buf = metadata_binary, KeyMetadata, "metadata")?;
+            let payment_address =
+                self.parse_record::<SproutPaymentAddress>(&key.data, "zkey", "payment_address")?;
+            let spending_key =
+                self.parse_record::<u252>(value.as_data(), "zkey", "spending_key")?;
+            let (metakey, metadata) =
+                self.key_metadata(&key, "zkeymeta", || key.data.encode_hex::<String>())?;
             let keypair = SproutSpendingKey::new(spending_key, metadata);
             zkeys_map.insert(payment_address, keypair);
 
@@ -387,12 +924,34 @@ impl<'a> ZcashdParser<'a> {
 
     fn parse_default_key(&self) -> Result<PubKey> {
         let value = self.value_for_keyname("defaultkey")?;
-        parse!(buf = value, PubKey, "defaultkey")
+        self.parse_record::<PubKey>(value, "defaultkey", "defaultkey")
+    }
+
+    fn parse_legacy_hd_chain(&self) -> Result<LegacyHDChain> {
+        let value = self.value_for_keyname("hdchain")?;
+        self.parse_record::<LegacyHDChain>(value, "hdchain", "hdchain")
+    }
+
+    fn parse_opt_legacy_hd_chain(&self) -> Result<Option<LegacyHDChain>> {
+        if self.dump.has_value_for_keyname("hdchain") {
+            self.parse_legacy_hd_chain().map(Some)
+        } else {
+            Ok(None)
+        }
     }
 
     fn parse_mnemonic_hd_chain(&self) -> Result<MnemonicHDChain> {
         let value = self.value_for_keyname("mnemonichdchain")?;
-        parse!(buf = value, MnemonicHDChain, "mnemonichdchain")
+        self.parse_record::<MnemonicHDChain>(value, "mnemonichdchain", "mnemonichdchain")
+    }
+
+    // Wallets created before zcashd 5.0.0 have no mnemonic HD chain.
+    fn parse_opt_mnemonic_hd_chain(&self) -> Result<Option<MnemonicHDChain>> {
+        if self.dump.has_value_for_keyname("mnemonichdchain") {
+            self.parse_mnemonic_hd_chain().map(Some)
+        } else {
+            Ok(None)
+        }
     }
 
     fn parse_send_recipients(&self) -> Result<HashMap<TxId, Vec<RecipientMapping>>> {
@@ -406,10 +965,12 @@ impl<'a> ZcashdParser<'a> {
             .context("Getting 'recipientmapping' records")?;
         for (key, value) in records {
             let mut p = Parser::new(&key.data);
+            p.set_strict(self.mode.is_strict());
             let txid = parse!(&mut p, TxId, "txid")?;
             let recipient_address = parse!(&mut p, RecipientAddress, "recipient_address")?;
-            p.check_finished()?;
-            let unified_address = parse!(buf = &value, String, "unified_address")?;
+            p.check_finished_ctx("'recipientmapping' record")?;
+            let unified_address =
+                self.parse_record::<String>(&value, "recipientmapping", "unified_address")?;
             let recipient_mapping = RecipientMapping::new(recipient_address, unified_address);
             send_recipients
                 .entry(txid)
@@ -428,13 +989,17 @@ impl<'a> ZcashdParser<'a> {
         let address_metadata_records = self.dump.records_for_keyname("unifiedaddrmeta")?;
         let mut address_metadata = vec![];
         for (key, value) in address_metadata_records {
-            let metadata = parse!(
-                buf = &key.data,
-                UnifiedAddressMetadata,
-                "UnifiedAddressMetadata key"
+            let metadata = self.parse_record::<UnifiedAddressMetadata>(
+                &key.data,
+                "unifiedaddrmeta",
+                "UnifiedAddressMetadata key",
             )?;
             address_metadata.push(metadata);
-            let v: u32 = parse!(buf = value.as_data(), u32, "UnifiedAddressMetadata value")?;
+            let v: u32 = self.parse_record::<u32>(
+                value.as_data(),
+                "unifiedaddrmeta",
+                "UnifiedAddressMetadata value",
+            )?;
             if v != 0 {
                 return Err(Error::UnexpectedValue {
                     kind: "UnifiedAddressMetadata",
@@ -447,13 +1012,17 @@ impl<'a> ZcashdParser<'a> {
         let account_metadata_records = self.dump.records_for_keyname("unifiedaccount")?;
         let mut account_metadata = HashMap::new();
         for (key, value) in account_metadata_records {
-            let metadata = parse!(
-                buf = &key.data,
-                UnifiedAccountMetadata,
-                "UnifiedAccountMetadata key"
+            let metadata = self.parse_record::<UnifiedAccountMetadata>(
+                &key.data,
+                "unifiedaccount",
+                "UnifiedAccountMetadata key",
             )?;
             account_metadata.insert(*metadata.ufvk_fingerprint(), metadata);
-            let v: u32 = parse!(buf = value.as_data(), u32, "UnifiedAccountMetadata value")?;
+            let v: u32 = self.parse_record::<u32>(
+                value.as_data(),
+                "unifiedaccount",
+                "UnifiedAccountMetadata value",
+            )?;
             if v != 0 {
                 return Err(Error::UnexpectedValue {
                     kind: "UnifiedAccountMetadata",
@@ -466,15 +1035,15 @@ impl<'a> ZcashdParser<'a> {
         let full_viewing_keys_records = self.dump.records_for_keyname("unifiedfvk")?;
         let mut full_viewing_keys = HashMap::new();
         for (key, value) in full_viewing_keys_records {
-            let key_id = parse!(
-                buf = &key.data,
-                UfvkFingerprint,
-                "UnifiedFullViewingKey key"
+            let key_id = self.parse_record::<UfvkFingerprint>(
+                &key.data,
+                "unifiedfvk",
+                "UnifiedFullViewingKey key",
             )?;
-            let fvk = parse!(
-                buf = value.as_data(),
-                UnifiedFullViewingKey,
-                "UnifiedFullViewingKey value"
+            let fvk = self.parse_record::<UnifiedFullViewingKey>(
+                value.as_data(),
+                "unifiedfvk",
+                "UnifiedFullViewingKey value",
             )?;
             full_viewing_keys.insert(key_id, fvk);
             self.mark_key_parsed(&key);
@@ -488,13 +1057,25 @@ impl<'a> ZcashdParser<'a> {
     }
 
     fn parse_hdseed(&self) -> Result<Option<LegacySeed>> {
-        Ok(if self.dump.has_value_for_keyname("hdseed") {
+        Ok(if self.dump.has_keys_for_keyname("hdseed") {
             let (key, value) = self
                 .dump
                 .record_for_keyname("hdseed")
                 .context("Getting 'hdseed' record")?;
-            let fingerprint = parse!(buf = &key.data, SeedFingerprint, "seed fingerprint")?;
-            let seed_data = parse!(buf = &value, Data, "legacy seed data")?;
+            let fingerprint =
+                self.parse_record::<SeedFingerprint>(&key.data, "hdseed", "seed fingerprint")?;
+            let seed_data = self.parse_record::<Data>(&value, "hdseed", "legacy seed data")?;
+            if let Err(e) = verify_seed_fingerprint(&seed_data, &fingerprint) {
+                match self.mode {
+                    ParseMode::Lenient => {
+                        self.report_recoverable_error("Verifying 'hdseed' fingerprint", &e)
+                    }
+                    ParseMode::StrictCollectAll => {
+                        self.collect_error("Verifying 'hdseed' fingerprint".to_string(), e)
+                    }
+                    ParseMode::StrictFailFast => return Err(e),
+                }
+            }
             self.mark_key_parsed(&key);
             Some(LegacySeed::new(seed_data, Some(fingerprint)))
         } else {
@@ -507,44 +1088,127 @@ impl<'a> ZcashdParser<'a> {
             .dump
             .record_for_keyname("mnemonicphrase")
             .context("Getting 'mnemonicphrase' record")?;
-        let fingerprint = parse!(buf = &key.data, SeedFingerprint, "seed fingerprint")?;
-        let mut bip39_mnemonic = parse!(buf = &value, Bip39Mnemonic, "mnemonic phrase")?;
+        let fingerprint =
+            self.parse_record::<SeedFingerprint>(&key.data, "mnemonicphrase", "seed fingerprint")?;
+        let mut bip39_mnemonic =
+            self.parse_record::<Bip39Mnemonic>(&value, "mnemonicphrase", "mnemonic phrase")?;
         bip39_mnemonic.set_fingerprint(fingerprint);
+        // Deriving the seed takes 2048 rounds of PBKDF2, so the phrase and its
+        // fingerprint are only verified in the strict modes. Lenient parsing
+        // keeps a corrupted phrase for recovery.
+        if self.mode.is_strict() {
+            let checks = [
+                ("checksum", validate_mnemonic_checksum(&bip39_mnemonic)),
+                ("fingerprint", bip39_seed(&bip39_mnemonic).map(drop)),
+            ];
+            for (check, result) in checks {
+                if let Err(e) = result {
+                    match self.mode {
+                        ParseMode::StrictCollectAll => {
+                            self.collect_error(format!("Verifying 'mnemonicphrase' {check}"), e)
+                        }
+                        _ => return Err(e),
+                    }
+                }
+            }
+        }
         self.mark_key_parsed(&key);
         Ok(bip39_mnemonic)
     }
 
-    fn parse_address_names(&self) -> Result<HashMap<Address, String>> {
+    // Wallets created before zcashd 5.0.0 have no mnemonic phrase.
+    fn parse_opt_mnemonic_phrase(&self) -> Result<Option<Bip39Mnemonic>> {
+        if self.dump.has_keys_for_keyname("mnemonicphrase") {
+            self.parse_mnemonic_phrase().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn parse_address_names(&self) -> Result<AddressNames> {
         let records = self
             .dump
             .records_for_keyname("name")
             .context("Getting 'name' records")?;
+        // Sorted so that, of several names for one address, the last is kept
+        // deterministically.
+        let mut records: Vec<_> = records.into_iter().collect();
+        records.sort_by(|(key1, _), (key2, _)| key1.data.cmp(&key2.data));
+        self.collect_address_names(records)
+    }
+
+    // A relabeled address can appear under more than one `name` record. The
+    // last name wins, with a warning, except in the strict modes.
+    fn collect_address_names(
+        &self,
+        records: impl IntoIterator<Item = (DBKey, DBValue)>,
+    ) -> Result<AddressNames> {
         let mut address_names = HashMap::new();
         for (key, value) in records {
-            let address = parse!(buf = &key.data, Address, "address")?;
-            let name = parse!(buf = value.as_data(), String, "name")?;
-            if address_names.contains_key(&address) {
-                return Err(Error::DuplicateRecord {
+            let address = self.parse_record::<Address>(&key.data, &key.keyname, "address")?;
+            let name = self.parse_record::<String>(value.as_data(), &key.keyname, "name")?;
+            if let Some(previous) = address_names.insert(address.clone(), name) {
+                let err = Error::DuplicateRecord {
                     kind: "address",
                     key: format!("{address}"),
+                };
+                match self.mode {
+                    ParseMode::Lenient => self.report_recoverable_error(
+                        &format!("Replacing name {previous:?} of address {address}"),
+                        &err,
+                    ),
+                    ParseMode::StrictCollectAll => {
+                        self.collect_error(format!("Parsing name of address {address}"), err)
+                    }
+                    ParseMode::StrictFailFast => return Err(err),
+                }
+            }
+
+            self.mark_key_parsed(&key);
+        }
+        Ok(AddressNames::new(address_names))
+    }
+
+    fn parse_dest_data(&self) -> Result<HashMap<Address, HashMap<String, String>>> {
+        let mut dest_data: HashMap<Address, HashMap<String, String>> = HashMap::new();
+        if !self.dump.has_keys_for_keyname("destdata") {
+            return Ok(dest_data);
+        }
+        let records = self
+            .dump
+            .records_for_keyname("destdata")
+            .context("Getting 'destdata' records")?;
+        for (key, value) in records {
+            let (address, attribute) = self.parse_record::<(Address, String)>(
+                &key.data,
+                "destdata",
+                "address and attribute",
+            )?;
+            let attribute_value =
+                self.parse_record::<String>(value.as_data(), "destdata", "attribute value")?;
+            let attributes = dest_data.entry(address.clone()).or_default();
+            if attributes.contains_key(&attribute) {
+                return Err(Error::DuplicateRecord {
+                    kind: "destination data",
+                    key: format!("{address}/{attribute}"),
                 });
             }
-            address_names.insert(address, name);
+            attributes.insert(attribute, attribute_value);
 
             self.mark_key_parsed(&key);
         }
-        Ok(address_names)
+        Ok(dest_data)
     }
 
-    fn parse_address_purposes(&self) -> Result<HashMap<Address, String>> {
+    fn parse_address_purposes(&self) -> Result<AddressPurposes> {
         let records = self
             .dump
             .records_for_keyname("purpose")
             .context("Getting 'purpose' records")?;
         let mut address_purposes = HashMap::new();
         for (key, value) in records {
-            let address = parse!(buf = &key.data, Address, "address")?;
-            let purpose = parse!(buf = value.as_data(), String, "purpose")?;
+            let address = self.parse_record::<Address>(&key.data, "purpose", "address")?;
+            let purpose = self.parse_record::<Purpose>(value.as_data(), "purpose", "purpose")?;
             if address_purposes.contains_key(&address) {
                 return Err(Error::DuplicateRecord {
                     kind: "address",
@@ -555,7 +1219,7 @@ impl<'a> ZcashdParser<'a> {
 
             self.mark_key_parsed(&key);
         }
-        Ok(address_purposes)
+        Ok(AddressPurposes::new(address_purposes))
     }
 
     fn parse_sapling_z_addresses(
@@ -570,12 +1234,15 @@ impl<'a> ZcashdParser<'a> {
             .records_for_keyname("sapzaddr")
             .context("Getting 'sapzaddr' records")?;
         for (key, value) in records {
-            let payment_address =
-                parse!(buf = &key.data, SaplingZPaymentAddress, "payment address")?;
-            let viewing_key = parse!(
-                buf = value.as_data(),
-                SaplingIncomingViewingKey,
-                "viewing key"
+            let payment_address = self.parse_record::<SaplingZPaymentAddress>(
+                &key.data,
+                "sapzaddr",
+                "payment address",
+            )?;
+            let viewing_key = self.parse_record::<SaplingIncomingViewingKey>(
+                value.as_data(),
+                "sapzaddr",
+                "viewing key",
             )?;
             if sapling_z_addresses.contains_key(&payment_address) {
                 return Err(Error::DuplicateRecord {
@@ -590,11 +1257,56 @@ impl<'a> ZcashdParser<'a> {
         Ok(sapling_z_addresses)
     }
 
+    fn parse_sapling_extended_fvks(
+        &self,
+    ) -> Result<HashMap<SaplingZPaymentAddress, ::sapling::zip32::ExtendedFullViewingKey>> {
+        let mut sapling_extended_fvks = HashMap::new();
+        if !self.dump.has_keys_for_keyname("sapextfvk") {
+            return Ok(sapling_extended_fvks);
+        }
+        let records = self
+            .dump
+            .records_for_keyname("sapextfvk")
+            .context("Getting 'sapextfvk' records")?;
+        for (key, value) in records {
+            let extfvk = self.parse_record::<::sapling::zip32::ExtendedFullViewingKey>(
+                &key.data,
+                "sapextfvk",
+                "extended full viewing key",
+            )?;
+            // zcashd only loads the key when the value is the character '1'
+            let flag = self.parse_record::<u8>(
+                value.as_data(),
+                "sapextfvk",
+                "extended full viewing key flag",
+            )?;
+            if flag == b'1' {
+                let (_, default_address) = extfvk.default_address();
+                let payment_address = self.parse_record::<SaplingZPaymentAddress>(
+                    &default_address.to_bytes(),
+                    "sapextfvk",
+                    "default address",
+                )?;
+                if sapling_extended_fvks.contains_key(&payment_address) {
+                    return Err(Error::DuplicateRecord {
+                        kind: "sapling extended full viewing key",
+                        key: format!("{payment_address:?}"),
+                    });
+                }
+                sapling_extended_fvks.insert(payment_address, extfvk);
+            }
+
+            self.mark_key_parsed(&key);
+        }
+        Ok(sapling_extended_fvks)
+    }
+
     fn parse_network_info(&self) -> Result<NetworkInfo> {
         let value = self
             .value_for_keyname("networkinfo")
             .context("Getting 'networkinfo' record")?;
-        let network_info = parse!(buf = value.as_data(), NetworkInfo, "network info")?;
+        let network_info =
+            self.parse_record::<NetworkInfo>(value.as_data(), "networkinfo", "network info")?;
         Ok(network_info)
     }
 
@@ -602,11 +1314,14 @@ impl<'a> ZcashdParser<'a> {
         let value = self
             .value_for_keyname("orchard_note_commitment_tree")
             .context("Getting 'orchard_note_commitment_tree' record")?;
-        let orchard_note_commitment_tree = parse!(
-            buf = &&value.as_data()[4..],
-            OrchardNoteCommitmentTree,
-            "orchard note commitment tree"
-        )?;
+        let mut p = Parser::new(value.as_data());
+        p.set_strict(self.mode.is_strict());
+        // zcashd writes the version of the client that serialized the tree
+        // ahead of the tree itself.
+        let _client_version = parse!(&mut p, ClientVersion, "orchard tree client version")?;
+        let orchard_note_commitment_tree =
+            parse!(&mut p, OrchardNoteCommitmentTree, "orchard note commitment tree")?;
+        p.check_finished_ctx("'orchard_note_commitment_tree' record")?;
         Ok(orchard_note_commitment_tree)
     }
 
@@ -617,8 +1332,9 @@ impl<'a> ZcashdParser<'a> {
             .context("Getting 'pool' records")?;
         let mut key_pool = HashMap::new();
         for (key, value) in records {
-            let index = parse!(buf = &key.data, i64, "key pool index")?;
-            let entry = parse!(buf = value.as_data(), KeyPoolEntry, "key pool entry")?;
+            let index = self.parse_record::<i64>(&key.data, "pool", "key pool index")?;
+            let entry =
+                self.parse_record::<KeyPoolEntry>(value.as_data(), "pool", "key pool entry")?;
             key_pool.insert(index, entry);
 
             self.mark_key_parsed(&key);
@@ -626,8 +1342,22 @@ impl<'a> ZcashdParser<'a> {
         Ok(key_pool)
     }
 
-    fn parse_transactions(&self, strict: bool) -> Result<HashMap<TxId, WalletTx>> {
+    fn parse_transactions(&self) -> Result<HashMap<TxId, WalletTx>> {
+        let (transactions, _) = self.parse_transactions_with_failures()?;
+        Ok(transactions)
+    }
+
+    /// Parses the `tx` records, returning the transactions that parsed along
+    /// with the ID and error of each transaction that could not be parsed.
+    ///
+    /// In `StrictFailFast` mode the first failure is returned as an error
+    /// instead; in `StrictCollectAll` mode failures are held back and returned
+    /// together when the whole wallet has been parsed.
+    pub fn parse_transactions_with_failures(
+        &self,
+    ) -> Result<(HashMap<TxId, WalletTx>, Vec<(TxId, Error)>)> {
         let mut transactions = HashMap::new();
+        let mut failures = Vec::new();
         // Some wallet files don't have any transactions
         if self.dump.has_keys_for_keyname("tx") {
             let records = self
@@ -636,10 +1366,12 @@ impl<'a> ZcashdParser<'a> {
                 .context("Getting 'tx' records")?;
             let mut sorted_records: Vec<_> = records.into_iter().collect();
             sorted_records.sort_by(|(key1, _), (key2, _)| key1.data.cmp(&key2.data));
-            for (key, value) in sorted_records {
-                let txid = parse!(buf = &key.data, TxId, "transaction ID")?;
-                let trace = false;
-                match parse!(buf = value.as_data(), WalletTx, "transaction", trace) {
+            // Decoding may run in parallel; duplicates, errors and the
+            // observer are then handled here, in record order.
+            let decoded = decode_tx_records(sorted_records, self.mode.is_strict());
+            for (key, value, txid, transaction) in decoded {
+                let txid = txid?;
+                match transaction {
                     Ok(transaction) => {
                         if transactions.contains_key(&txid) {
                             return Err(Error::DuplicateRecord {
@@ -649,12 +1381,19 @@ impl<'a> ZcashdParser<'a> {
                         }
                         transactions.insert(txid, transaction);
                     }
-                    Err(e) if !strict => {
-                        eprintln!(
-                            "Unable to parse transaction data {}: {}",
-                            value.as_data().encode_hex::<String>(),
-                            e
+                    Err(e) if self.mode == ParseMode::Lenient => {
+                        self.report_recoverable_error(
+                            &format!(
+                                "Unable to parse transaction {} (data: {})",
+                                txid,
+                                value.as_data().encode_hex::<String>()
+                            ),
+                            &e,
                         );
+                        failures.push((txid, e));
+                    }
+                    Err(e) if self.mode == ParseMode::StrictCollectAll => {
+                        self.collect_error(format!("Unable to parse transaction {}", txid), e);
                     }
                     err => {
                         err?;
@@ -664,6 +1403,784 @@ impl<'a> ZcashdParser<'a> {
                 self.mark_key_parsed(&key);
             }
         }
-        Ok(transactions)
+
+        // A note is spent once any wallet transaction reveals its nullifier.
+        let spent_nullifiers: HashSet<Blob32> = transactions
+            .values()
+            .flat_map(WalletTx::sapling_nullifiers)
+            .collect();
+        for transaction in transactions.values_mut() {
+            transaction.mark_spent_outputs(&spent_nullifiers);
+        }
+        Ok((transactions, failures))
+    }
+}
+
+/// Checks `recorded` against the ZIP 32 fingerprint of `seed`, which zcashd
+/// computes as a BLAKE2b-256 hash of the length-prefixed seed bytes.
+fn verify_seed_fingerprint(seed: &Data, recorded: &SeedFingerprint) -> Result<()> {
+    let derived = zip32::fingerprint::SeedFingerprint::from_seed(seed.as_ref())
+        .map(|fp| SeedFingerprint::new(fp.to_bytes()));
+    if derived.as_ref() == Some(recorded) {
+        return Ok(());
+    }
+    Err(Error::SeedFingerprintMismatch {
+        recorded: recorded.to_hex(),
+        derived: derived.map_or_else(
+            || "nothing (invalid seed length)".to_string(),
+            |fp| fp.to_hex(),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+    use zewif::{BlockHeight, Data, Network, Script, TxId};
+
+    use super::ZcashdParser;
+    use crate::{
+        DBKey, DBValue, Error, ParseMode, ParserObserver, ZcashdDump, parse,
+        test_support::{
+            MNEMONIC, compact, der_privkey, dump_from_records, hdseed_dump, keypair_dump,
+            minimal_wallet, minimal_wallet_dump, minimal_wallet_records, mnemonic_record,
+            named_address, parse_records, phrase_record, privkey_value, sapling_key_records,
+            sapling_wallet_tx_value,
+        },
+        zcashd_wallet::{Address, SecondsSinceEpoch},
+    };
+
+    #[test]
+    fn test_parse_watch_only() {
+        // OP_DUP OP_HASH160 <20 bytes> OP_EQUALVERIFY OP_CHECKSIG
+        let mut p2pkh = vec![0x76, 0xa9, 0x14];
+        p2pkh.extend_from_slice(&[0x11; 20]);
+        p2pkh.extend_from_slice(&[0x88, 0xac]);
+        // OP_HASH160 <20 bytes> OP_EQUAL
+        let mut p2sh = vec![0xa9, 0x14];
+        p2sh.extend_from_slice(&[0x22; 20]);
+        p2sh.push(0x87);
+
+        let dump = dump_from_records(&[
+            ("watchs", compact(&p2pkh), vec![b'1']),
+            ("watchs", compact(&p2sh), vec![b'1']),
+        ]);
+        let parser = ZcashdParser::new(&dump, true);
+        let scripts = parser.parse_watch_only().unwrap();
+
+        assert_eq!(scripts.len(), 2);
+        assert!(scripts.contains(&Script::from(Data::from_slice(&p2pkh))));
+        assert!(scripts.contains(&Script::from(Data::from_slice(&p2sh))));
+        assert!(parser.unparsed_keys.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_parse_dest_data() {
+        let address = "t1WmEWuRKGcfi8iG3HxGNg3okswsdB54EXn";
+        let mut key_0 = compact(address.as_bytes());
+        key_0.extend(compact(b"rr0"));
+        let mut key_1 = compact(address.as_bytes());
+        key_1.extend(compact(b"used"));
+
+        let dump = dump_from_records(&[
+            ("destdata", key_0, compact(b"payment request")),
+            ("destdata", key_1, compact(b"p")),
+        ]);
+        let parser = ZcashdParser::new(&dump, true);
+        let dest_data = parser.parse_dest_data().unwrap();
+
+        assert_eq!(dest_data.len(), 1);
+        let attributes = dest_data.get(&Address::from(address.to_string())).unwrap();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes.get("rr0").unwrap(), "payment request");
+        assert_eq!(attributes.get("used").unwrap(), "p");
+        assert!(parser.unparsed_keys.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_parse_legacy_hd_chain() {
+        let mut value = vec![];
+        value.extend_from_slice(&1i32.to_le_bytes());
+        value.extend_from_slice(&[0xab; 32]);
+        value.extend_from_slice(&1_600_000_000i64.to_le_bytes());
+        value.extend_from_slice(&5u32.to_le_bytes());
+
+        let dump = dump_from_records(&[("hdchain", vec![], value)]);
+        let parser = ZcashdParser::new(&dump, true);
+        let hd_chain = parser.parse_opt_legacy_hd_chain().unwrap().unwrap();
+
+        assert_eq!(hd_chain.version(), 1);
+        assert_eq!(hd_chain.seed_fp().as_bytes(), &[0xab; 32]);
+        assert_eq!(
+            hd_chain.create_time(),
+            Some(SecondsSinceEpoch::from(1_600_000_000u64))
+        );
+        assert_eq!(hd_chain.sapling_account_counter(), 5);
+        assert!(parser.unparsed_keys.borrow().is_empty());
+
+        let dump = dump_from_records(&[]);
+        let parser = ZcashdParser::new(&dump, true);
+        assert!(parser.parse_opt_legacy_hd_chain().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_legacy_wallet_without_mnemonic() {
+        let dump = hdseed_dump(&[0x42; 32], false);
+        let parser = ZcashdParser::new(&dump, true);
+
+        assert!(parser.parse_hdseed().unwrap().is_some());
+        assert!(parser.parse_opt_mnemonic_phrase().unwrap().is_none());
+        assert!(parser.parse_opt_mnemonic_hd_chain().unwrap().is_none());
+        assert!(parser.unparsed_keys.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_parse_transactions_reports_failures() {
+        let txid_bytes = [0x5a; 32];
+        let dump = dump_from_records(&[("tx", txid_bytes.to_vec(), vec![0xff; 4])]);
+        let txid = parse!(buf = &txid_bytes, TxId, "txid").unwrap();
+
+        let parser = ZcashdParser::new(&dump, false);
+        let (transactions, failures) = parser.parse_transactions_with_failures().unwrap();
+        assert!(transactions.is_empty());
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, txid);
+
+        let parser = ZcashdParser::new(&dump, true);
+        assert!(parser.parse_transactions_with_failures().is_err());
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver(Rc<RefCell<Vec<String>>>);
+
+    impl ParserObserver for RecordingObserver {
+        fn on_recoverable_error(&self, context: &str, _err: &Error) {
+            self.0.borrow_mut().push(context.to_string());
+        }
+    }
+
+    #[test]
+    fn test_duplicate_address_names() {
+        // `ZcashdDump` keys are unique, so the duplicate records are fed to
+        // the parser directly.
+        let address = named_address();
+        let key = DBKey::new("name", Data::from_slice(&compact(address.as_bytes())));
+        let records = || {
+            [b"old".as_slice(), b"new"]
+                .map(|name| (key.clone(), DBValue::new(Data::from_slice(&compact(name)))))
+        };
+        let dump = dump_from_records(&[]);
+
+        let contexts = Rc::new(RefCell::new(Vec::new()));
+        let observer = RecordingObserver(contexts.clone());
+        let parser = ZcashdParser::new_with_observer(&dump, false, Some(Box::new(observer)));
+        let names = parser.collect_address_names(records()).unwrap();
+        assert_eq!(names[&Address::from(address)], "new");
+        assert_eq!(contexts.borrow().len(), 1);
+
+        let parser = ZcashdParser::new(&dump, ParseMode::StrictFailFast);
+        assert!(matches!(
+            parser.collect_address_names(records()),
+            Err(Error::DuplicateRecord {
+                kind: "address",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_observer_receives_recoverable_errors() {
+        let dump = dump_from_records(&[("tx", vec![0x5a; 32], vec![0xff; 4])]);
+        let contexts = Rc::new(RefCell::new(Vec::new()));
+        let observer = RecordingObserver(contexts.clone());
+
+        let parser = ZcashdParser::new_with_observer(&dump, false, Some(Box::new(observer)));
+        parser.parse_transactions_with_failures().unwrap();
+
+        let contexts = contexts.borrow();
+        assert_eq!(contexts.len(), 1);
+        assert!(contexts[0].starts_with("Unable to parse transaction"));
+    }
+
+    #[test]
+    fn test_parse_hdseed_verifies_fingerprint() {
+        let seed = [0x42; 32];
+        let dump = hdseed_dump(&seed, false);
+        for strict in [false, true] {
+            let parser = ZcashdParser::new(&dump, strict);
+            assert!(parser.parse_hdseed().unwrap().is_some());
+        }
+    }
+
+    #[test]
+    fn test_parse_hdseed_fingerprint_mismatch() {
+        let dump = hdseed_dump(&[0x42; 32], true);
+
+        let parser = ZcashdParser::new(&dump, true);
+        assert!(matches!(
+            parser.parse_hdseed(),
+            Err(Error::SeedFingerprintMismatch { .. })
+        ));
+
+        let contexts = Rc::new(RefCell::new(Vec::new()));
+        let observer = RecordingObserver(contexts.clone());
+        let parser = ZcashdParser::new_with_observer(&dump, false, Some(Box::new(observer)));
+        assert!(parser.parse_hdseed().unwrap().is_some());
+        assert_eq!(contexts.borrow().len(), 1);
+    }
+
+    fn mnemonic_dump(corrupt_fingerprint: bool) -> ZcashdDump {
+        dump_from_records(&[mnemonic_record(corrupt_fingerprint)])
+    }
+
+    #[test]
+    fn test_parse_mnemonic_phrase_verifies_fingerprint() {
+        let dump = mnemonic_dump(false);
+        for strict in [false, true] {
+            let parser = ZcashdParser::new(&dump, strict);
+            let mnemonic = parser.parse_mnemonic_phrase().unwrap();
+            assert_eq!(mnemonic.mnemonic(), MNEMONIC);
+        }
+    }
+
+    #[test]
+    fn test_parse_mnemonic_phrase_fingerprint_mismatch() {
+        let dump = mnemonic_dump(true);
+
+        let parser = ZcashdParser::new(&dump, true);
+        assert!(matches!(
+            parser.parse_mnemonic_phrase(),
+            Err(Error::SeedFingerprintMismatch { .. })
+        ));
+
+        // Lenient parsing does not derive the seed.
+        let parser = ZcashdParser::new(&dump, false);
+        assert!(parser.parse_mnemonic_phrase().is_ok());
+    }
+
+    #[test]
+    fn test_parse_mnemonic_phrase_validates_checksum() {
+        // Twelve words of zero entropy need "about" as the last word to
+        // carry the checksum.
+        let phrase = MNEMONIC.replace("about", "abandon");
+        let dump = dump_from_records(&[phrase_record(&phrase, false)]);
+
+        let parser = ZcashdParser::new(&dump, true);
+        assert!(matches!(
+            parser.parse_mnemonic_phrase(),
+            Err(Error::InvalidMnemonicChecksum)
+        ));
+
+        let parser = ZcashdParser::new(&dump, ParseMode::StrictCollectAll);
+        assert!(parser.parse_mnemonic_phrase().is_ok());
+        assert_eq!(parser.collected_errors.borrow().len(), 1);
+
+        let parser = ZcashdParser::new(&dump, false);
+        assert_eq!(parser.parse_mnemonic_phrase().unwrap().mnemonic(), phrase);
+    }
+
+    #[test]
+    fn test_parse_viewing_keys() {
+        #[allow(deprecated)]
+        let extfvk = ::sapling::zip32::ExtendedSpendingKey::master(&[7u8; 32])
+            .to_extended_full_viewing_key();
+        let mut extfvk_bytes = vec![];
+        extfvk.write(&mut extfvk_bytes).unwrap();
+
+        let dump = dump_from_records(&[("vkey", extfvk_bytes, vec![b'1'])]);
+        let parser = ZcashdParser::new(&dump, true);
+        let viewing_keys = parser.parse_viewing_keys().unwrap();
+
+        assert_eq!(viewing_keys.len(), 1);
+        assert!(viewing_keys[0] == extfvk);
+        assert!(parser.unparsed_keys.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_parse_sapling_extended_fvks() {
+        let mut records = vec![];
+        let mut extfvks = vec![];
+        for seed in 1u8..=3 {
+            #[allow(deprecated)]
+            let extfvk = ::sapling::zip32::ExtendedSpendingKey::master(&[seed; 32])
+                .to_extended_full_viewing_key();
+            let mut extfvk_bytes = vec![];
+            extfvk.write(&mut extfvk_bytes).unwrap();
+            records.push(("sapextfvk", extfvk_bytes, vec![b'1']));
+            extfvks.push(extfvk);
+        }
+
+        let dump = dump_from_records(&records);
+        let parser = ZcashdParser::new(&dump, true);
+        let sapling_extended_fvks = parser.parse_sapling_extended_fvks().unwrap();
+
+        assert_eq!(sapling_extended_fvks.len(), extfvks.len());
+        for extfvk in &extfvks {
+            let (_, default_address) = extfvk.default_address();
+            let stored = sapling_extended_fvks
+                .iter()
+                .find(|(address, _)| {
+                    address.diversifier().as_slice() == default_address.diversifier().0
+                })
+                .map(|(_, stored)| stored)
+                .unwrap();
+            assert!(stored == extfvk);
+        }
+        assert!(parser.unparsed_keys.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_parse_collect_all_reports_every_failed_category() {
+        let mut records = minimal_wallet_records();
+        for (keyname, _, value) in &mut records {
+            if *keyname == "defaultkey" {
+                *value = vec![0xff];
+            }
+        }
+        records.push(("witnesscachesize", vec![], vec![0x01, 0x02]));
+        let dump = dump_from_records(&records);
+
+        let result = ZcashdParser::new(&dump, ParseMode::StrictCollectAll).parse();
+        let Err(Error::Multiple { errors }) = result else {
+            panic!("expected Error::Multiple");
+        };
+        let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+        for category in ["defaultkey", "witnesscachesize"] {
+            let message = format!("Parsing '{category}' records");
+            assert!(
+                messages.contains(&message),
+                "{message} missing from {messages:?}"
+            );
+        }
+
+        let result = ZcashdParser::new(&dump, ParseMode::StrictFailFast).parse();
+        assert!(!matches!(result, Ok(_) | Err(Error::Multiple { .. })));
+    }
+
+    #[test]
+    fn test_parse_transactions_collect_all() {
+        let dump = dump_from_records(&[
+            ("tx", vec![0x11; 32], vec![0xff; 4]),
+            ("tx", vec![0x22; 32], vec![0xff; 4]),
+        ]);
+        let parser = ZcashdParser::new(&dump, ParseMode::StrictCollectAll);
+        let (transactions, failures) = parser.parse_transactions_with_failures().unwrap();
+        assert!(transactions.is_empty());
+        assert!(failures.is_empty());
+        assert_eq!(parser.collected_errors.borrow().len(), 2);
+
+        let parser = ZcashdParser::new(&dump, ParseMode::StrictFailFast);
+        assert!(parser.parse_transactions_with_failures().is_err());
+    }
+
+    #[test]
+    fn test_lenient_mode_accepts_non_canonical_compact_size() {
+        // The name's length as a three-byte CompactSize, where zcashd would
+        // write the single byte 7.
+        let mut name = vec![0xfd, 0x07, 0x00];
+        name.extend_from_slice(b"savings");
+        let dump = dump_from_records(&[("name", compact(named_address().as_bytes()), name)]);
+
+        let parser = ZcashdParser::new(&dump, ParseMode::Lenient);
+        let names = parser.parse_address_names().unwrap();
+        assert_eq!(names.values().collect::<Vec<_>>(), ["savings"]);
+
+        let parser = ZcashdParser::new(&dump, ParseMode::StrictFailFast);
+        assert!(parser.parse_address_names().is_err());
+    }
+
+    #[test]
+    fn test_parse_viewing_keys_skips_unknown_in_non_strict_mode() {
+        let dump = dump_from_records(&[("vkey", vec![0u8; 64], vec![b'1'])]);
+
+        let parser = ZcashdParser::new(&dump, false);
+        assert!(parser.parse_viewing_keys().unwrap().is_empty());
+        assert!(parser.unparsed_keys.borrow().is_empty());
+
+        let parser = ZcashdParser::new(&dump, true);
+        assert!(parser.parse_viewing_keys().is_err());
+    }
+
+    #[test]
+    fn test_keypair_address() {
+        // The secp256k1 generator, whose compressed encoding hashes to
+        // 751e76e8199196d454941c45d1b3a323f1433bd6.
+        let mut secret = [0u8; 32];
+        secret[31] = 1;
+        let pubkey =
+            hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+
+        let dump = keypair_dump(&pubkey, &secret);
+        let keys = ZcashdParser::new(&dump, false).parse_keys().unwrap();
+        let keypair = keys.keypairs().next().unwrap();
+        assert_eq!(
+            keypair.address(Network::Main).to_string(),
+            "t1UYsZVJkLPeMjxEtACvSxfWuNmddpWfxzs"
+        );
+        assert_eq!(
+            keypair.address(Network::Test).to_string(),
+            "tmLPctKo9j49rtCSKpwEBpLBeykiTGomGQs"
+        );
+    }
+
+    #[test]
+    fn test_keypair_to_wif() {
+        let mut secret = [0u8; 32];
+        secret[31] = 1;
+        let compressed =
+            hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+        let uncompressed = hex::decode(
+            "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+             483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+        )
+        .unwrap();
+
+        for (pubkey, expected) in [
+            (
+                compressed,
+                "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn",
+            ),
+            (
+                uncompressed,
+                "5HpHagT65TZzG1PH3CSu63k8DbpvD8s5ip4nEB3kEsreAnchuDf",
+            ),
+        ] {
+            let dump = keypair_dump(&pubkey, &secret);
+            let keys = ZcashdParser::new(&dump, false).parse_keys().unwrap();
+            let keypair = keys.keypairs().next().unwrap();
+            assert_eq!(keypair.to_wif(Network::Main).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_sapling_key_to_export_string() {
+        let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]);
+        let dump = dump_from_records(&sapling_key_records(&extsk));
+        let keys = ZcashdParser::new(&dump, false)
+            .parse_sapling_keys()
+            .unwrap();
+        let key = keys.keypairs().next().unwrap();
+
+        let exported = key.to_export_string(Network::Main);
+        assert!(exported.starts_with("secret-extended-key-main1"));
+        let decoded = zcash_keys::encoding::decode_extended_spending_key(
+            "secret-extended-key-main",
+            &exported,
+        )
+        .unwrap();
+        assert_eq!(decoded.to_bytes(), extsk.to_bytes());
+        assert!(
+            key.to_export_string(Network::Test)
+                .starts_with("secret-extended-key-test1")
+        );
+    }
+
+    #[test]
+    fn test_parse_keys_verifies_keypairs_in_strict_mode() {
+        let secret = [0x01; 32];
+        let secp = secp256k1::Secp256k1::signing_only();
+        let secret_key = secp256k1::SecretKey::from_slice(&secret).unwrap();
+        let pubkey = secp256k1::PublicKey::from_secret_key(&secp, &secret_key).serialize();
+
+        let dump = keypair_dump(&pubkey, &secret);
+        for mode in [ParseMode::Lenient, ParseMode::StrictFailFast] {
+            let keys = ZcashdParser::new(&dump, mode).parse_keys().unwrap();
+            keys.keypairs().next().unwrap().verify().unwrap();
+        }
+
+        let mut wrong_pubkey = pubkey;
+        wrong_pubkey[32] ^= 0x01;
+        let dump = keypair_dump(&wrong_pubkey, &secret);
+        assert!(ZcashdParser::new(&dump, ParseMode::Lenient).parse_keys().is_ok());
+        assert!(matches!(
+            ZcashdParser::new(&dump, ParseMode::StrictFailFast).parse_keys(),
+            Err(Error::InvalidKeypair)
+        ));
+
+        let parser = ZcashdParser::new(&dump, ParseMode::StrictCollectAll);
+        assert!(parser.parse_keys().is_ok());
+        assert_eq!(parser.collected_errors.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_trailing_bytes_name_the_record() {
+        let mut value = compact(&[0x02; 33]);
+        value.push(0);
+        let dump = dump_from_records(&[("defaultkey", vec![], value)]);
+        let err = ZcashdParser::new(&dump, false)
+            .parse_default_key()
+            .unwrap_err();
+
+        let messages: Vec<String> =
+            std::iter::successors(Some(&err as &dyn std::error::Error), |err| err.source())
+                .map(ToString::to_string)
+                .collect();
+        assert!(
+            messages.contains(&"Trailing bytes in 'defaultkey' record".to_string()),
+            "{messages:?}"
+        );
+        assert!(messages.iter().any(|message| message.contains("buffer not fully consumed")));
+    }
+
+    #[test]
+    fn test_key_without_metadata_is_named() {
+        let is_missing = |result: Result<(), Error>, metakind: &str, expected: &str| {
+            matches!(
+                result,
+                Err(Error::MissingRecord { kind, key }) if kind == metakind && key == expected
+            )
+        };
+        let mut keymeta = 1i32.to_le_bytes().to_vec();
+        keymeta.extend_from_slice(&0u64.to_le_bytes());
+
+        // Each key comes with one metadata record, but for a different key.
+        let pubkey = [0x02; 33];
+        let other_pubkey = [0x03; 33];
+        let dump = dump_from_records(&[
+            (
+                "key",
+                compact(&pubkey),
+                privkey_value(&pubkey, &[0x5c; 214]),
+            ),
+            ("keymeta", compact(&other_pubkey), keymeta.clone()),
+        ]);
+        let result = ZcashdParser::new(&dump, false).parse_keys().map(drop);
+        assert!(is_missing(result, "keymeta", &hex::encode(pubkey)));
+
+        let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]);
+        let dump = dump_from_records(&[
+            ("sapzkey", vec![0x77; 32], extsk.to_bytes().to_vec()),
+            ("sapzkeymeta", vec![0x78; 32], keymeta.clone()),
+        ]);
+        let result = ZcashdParser::new(&dump, false)
+            .parse_sapling_keys()
+            .map(drop);
+        assert!(is_missing(result, "sapzkeymeta", &hex::encode([0x77; 32])));
+
+        let dump = dump_from_records(&[
+            ("zkey", vec![0x88; 64], vec![0x0f; 32]),
+            ("zkeymeta", vec![0x89; 64], keymeta),
+        ]);
+        let result = ZcashdParser::new(&dump, false)
+            .parse_sprout_keys()
+            .map(drop);
+        assert!(is_missing(result, "zkeymeta", &hex::encode([0x88; 64])));
+    }
+
+    #[test]
+    fn test_parse_keys_with_future_keymeta_version() {
+        let mut secret = [0u8; 32];
+        secret[31] = 1;
+        let pubkey =
+            hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+        // A version 11 record: the version 10 fields, then two unknown bytes.
+        let mut keymeta = 11i32.to_le_bytes().to_vec();
+        keymeta.extend_from_slice(&1_600_000_000u64.to_le_bytes());
+        keymeta.extend(compact(b"m/0'/0'/5'"));
+        keymeta.extend_from_slice(&[0x33; 32]);
+        keymeta.extend_from_slice(&[0xde, 0xad]);
+        let dump = dump_from_records(&[
+            ("key", compact(&pubkey), privkey_value(&pubkey, &der_privkey(&secret))),
+            ("keymeta", compact(&pubkey), keymeta),
+        ]);
+
+        let keys = ZcashdParser::new(&dump, false).parse_keys().unwrap();
+        let metadata = keys.keypairs().next().unwrap().metadata();
+        assert!(metadata.is_future_version());
+        assert_eq!(metadata.hd_keypath(), Some(&"m/0'/0'/5'".to_string()));
+        assert_eq!(metadata.extra().as_slice(), [0xde, 0xad]);
+
+        assert!(matches!(
+            ZcashdParser::new(&dump, true).parse_keys(),
+            Err(Error::UnsupportedVersion {
+                kind: "keymeta",
+                version: 11
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_best_effort() {
+        let mut records = minimal_wallet_records();
+        for (keyname, _, value) in records.iter_mut() {
+            match *keyname {
+                "defaultkey" => *value = compact(&[0x02; 5]),
+                "networkinfo" => *value = vec![0xff],
+                "pool" => value.truncate(3),
+                _ => {}
+            }
+        }
+        let dump = dump_from_records(&records);
+        assert!(ZcashdParser::parse_dump(&dump, false).is_err());
+
+        let (wallet, failures) = ZcashdParser::parse_best_effort(&dump);
+        let categories: Vec<_> = failures.iter().map(|(category, _)| *category).collect();
+        assert_eq!(categories, ["defaultkey", "pool", "networkinfo"]);
+        assert!(wallet.default_key().as_slice().is_empty());
+        assert!(wallet.key_pool().is_empty());
+        assert_eq!(wallet.network(), Network::Main);
+
+        // The intact categories are still there.
+        assert_eq!(wallet.keys().keypairs().count(), 1);
+        assert_eq!(wallet.address_names().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_dump_with_progress() {
+        let dump = minimal_wallet_dump();
+        let mut reports = Vec::new();
+        ZcashdParser::parse_dump_with_progress(&dump, false, |progress| reports.push(progress))
+            .unwrap();
+
+        let categories: HashSet<_> = reports.iter().map(|progress| progress.category).collect();
+        assert_eq!(categories.len(), reports.len());
+        assert_eq!(reports[0].category, "acc");
+        for (index, progress) in reports.iter().enumerate() {
+            assert_eq!(progress.categories_parsed, index + 1);
+            assert_eq!(progress.records_total, dump.records().len());
+        }
+        for pair in reports.windows(2) {
+            assert!(pair[0].records_parsed <= pair[1].records_parsed);
+        }
+        let last = reports.last().unwrap();
+        assert_eq!(last.categories_parsed, last.categories_total);
+        assert!(last.records_parsed > 0);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_tx_decoding_matches_sequential() {
+        use super::{DecodedTx, decode_tx_record, decode_tx_records};
+        use crate::test_support::{v1_transaction, wallet_tx_value};
+
+        let mut records: Vec<_> = (0..64u8)
+            .map(|n| ("tx", vec![n; 32], wallet_tx_value(&v1_transaction(), Some(n.into()))))
+            .collect();
+        records.push(("tx", vec![0xfe; 32], vec![0xff; 4]));
+        records.push(("tx", vec![0xff; 31], wallet_tx_value(&v1_transaction(), None)));
+        let dump = dump_from_records(&records);
+        let mut tx_records: Vec<_> = dump.records_for_keyname("tx").unwrap().into_iter().collect();
+        tx_records.sort_by(|(key1, _), (key2, _)| key1.data.cmp(&key2.data));
+
+        let outcome = |decoded: Vec<DecodedTx>| -> Vec<_> {
+            decoded
+                .into_iter()
+                .map(|(key, _, txid, transaction)| (key, txid.ok(), transaction.ok()))
+                .collect()
+        };
+        let sequential = outcome(
+            tx_records
+                .iter()
+                .cloned()
+                .map(|record| decode_tx_record(record, true))
+                .collect(),
+        );
+        let parallel = outcome(decode_tx_records(tx_records, true));
+        assert_eq!(parallel.len(), 66);
+        assert_eq!(parallel.iter().filter(|(_, txid, _)| txid.is_none()).count(), 1);
+        assert_eq!(parallel.iter().filter(|(_, _, tx)| tx.is_none()).count(), 1);
+        assert_eq!(parallel, sequential);
+    }
+
+    #[cfg(feature = "view-only")]
+    #[test]
+    fn test_view_only_skips_secrets() {
+        let mut records = minimal_wallet_records();
+        records.push(mnemonic_record(false));
+        let (wallet, unparsed) =
+            ZcashdParser::parse_dump(&dump_from_records(&records), false).unwrap();
+
+        assert_eq!(wallet.keys().keypairs().count(), 0);
+        assert_eq!(wallet.sapling_keys().keypairs().count(), 0);
+        assert!(wallet.sprout_keys().is_none());
+        assert!(wallet.wallet_keys().is_none());
+        assert!(wallet.legacy_hd_seed().is_none());
+        assert!(wallet.bip39_mnemonic().is_none());
+        assert!(!wallet.sapling_z_addresses().is_empty());
+        let secret_keynames = ["key", "keymeta", "zkey", "zkeymeta", "mnemonicphrase"];
+        assert!(
+            unparsed
+                .iter()
+                .all(|key| !secret_keynames.contains(&key.keyname.as_str()))
+        );
+    }
+
+    #[test]
+    fn test_best_block_height() {
+        let wallet = minimal_wallet();
+        assert_eq!(wallet.bestblock().height(), None);
+
+        // The tip is the block the wallet's Sapling notes are witnessed to.
+        let mut records = minimal_wallet_records();
+        records.push(("tx", vec![0x20; 32], sapling_wallet_tx_value(&[0, 0, 0], 100)));
+        let wallet = parse_records(&records);
+        assert_eq!(wallet.bestblock().height(), Some(BlockHeight::from(100)));
+
+        // The Orchard tree's last checkpoint takes precedence.
+        for (keyname, _, value) in records.iter_mut() {
+            if *keyname == "orchard_note_commitment_tree" {
+                let checkpoint = 2_000_100u32.to_le_bytes();
+                value.splice(5..6, std::iter::once(1).chain(checkpoint));
+            }
+        }
+        let wallet = parse_records(&records);
+        assert_eq!(
+            wallet.bestblock().height(),
+            Some(BlockHeight::from(2_000_100))
+        );
+        assert_eq!(
+            wallet.effective_best_block().unwrap().height(),
+            Some(BlockHeight::from(2_000_100))
+        );
+    }
+
+    #[test]
+    fn test_parse_legacy_accounting() {
+        let mut account = 60000u32.to_le_bytes().to_vec();
+        account.extend(compact(&[0x03; 33]));
+
+        let entry_key = |number: u64| {
+            let mut key = compact(b"savings");
+            key.extend_from_slice(&number.to_le_bytes());
+            key
+        };
+        let entry_value = |credit_debit: i64, comment: &[u8]| {
+            let mut value = 60000u32.to_le_bytes().to_vec();
+            value.extend_from_slice(&credit_debit.to_le_bytes());
+            value.extend_from_slice(&1_400_000_000u64.to_le_bytes());
+            value.extend(compact(b"spending"));
+            value.extend(compact(comment));
+            value
+        };
+        // A comment followed by a map value holding the order position.
+        let mut comment = b"rent\0\x01".to_vec();
+        comment.extend(compact(b"n"));
+        comment.extend(compact(b"7"));
+
+        let mut records = minimal_wallet_records();
+        records.push(("acc", compact(b"savings"), account));
+        records.push(("acentry", entry_key(1), entry_value(250_000, b"top up")));
+        records.push(("acentry", entry_key(2), entry_value(-100_000, &comment)));
+        let (wallet, unparsed) =
+            ZcashdParser::parse_dump(&dump_from_records(&records), false).unwrap();
+        assert!(unparsed.iter().all(|key| !key.keyname.starts_with("ac")));
+
+        let account = &wallet.legacy_accounts()["savings"];
+        assert_eq!(account.pubkey().as_slice(), &[0x03; 33]);
+
+        let entries = &wallet.accounting_entries()["savings"];
+        assert_eq!(entries.keys().copied().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(entries[&1].comment(), "top up");
+        assert_eq!(entries[&1].order_pos(), None);
+        assert_eq!(entries[&2].credit_debit(), -100_000);
+        assert_eq!(entries[&2].other_account(), "spending");
+        assert_eq!(entries[&2].comment(), "rent");
+        assert_eq!(entries[&2].order_pos(), Some(7));
+        assert_eq!(wallet.accounting_balance("savings"), 150_000);
+        assert_eq!(wallet.accounting_balance("spending"), 0);
     }
 }
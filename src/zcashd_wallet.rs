@@ -1,114 +1,231 @@
 use zewif::{LegacySeed, mod_use};
 
+mod_use!(accounting_entry);
 mod_use!(address);
+mod_use!(address_book);
+mod_use!(address_entry);
+mod_use!(any_spending_key);
+mod_use!(balances);
 mod_use!(block_locator);
 mod_use!(client_version);
 mod_use!(compact_size);
+mod_use!(derivation_path);
 mod_use!(key_metadata);
+mod_use!(legacy_account);
+mod_use!(legacy_hd_chain);
 mod_use!(incremental_merkle_tree);
 mod_use!(incremental_witness);
+mod_use!(is_mine);
+mod_use!(merge);
 mod_use!(mnemonic_hd_chain);
 mod_use!(network_info);
 mod_use!(parseable_types);
+mod_use!(purpose);
 mod_use!(receiver_type);
 mod_use!(recipient_address);
 mod_use!(recipient_mapping);
+mod_use!(redacted);
 mod_use!(seconds_since_epoch);
+mod_use!(sorted_accessors);
 mod_use!(unified_accounts);
+mod_use!(unified_account_view);
 mod_use!(unified_account_metadata);
 mod_use!(unified_address_metadata);
 mod_use!(u160_type);
 mod_use!(u252_type);
 mod_use!(u256_type);
+mod_use!(wallet_builder);
+mod_use!(wallet_diff);
+mod_use!(wallet_era);
+mod_use!(wallet_parts);
 mod_use!(wallet_tx);
+mod_use!(wallet_tx_outputs);
+mod_use!(watch_only_export);
 
 pub mod orchard;
 pub mod sapling;
 pub mod sprout;
 pub mod transparent;
 
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::OnceLock,
+};
 
 use orchard::OrchardNoteCommitmentTree;
-use sapling::{SaplingKeys, SaplingZPaymentAddress};
+use sapling::{SaplingKeys, SaplingNoteCommitmentTree, SaplingZPaymentAddress};
 use sprout::SproutKeys;
-use transparent::{KeyPoolEntry, Keys, PubKey, WalletKeys};
-use zewif::{Bip39Mnemonic, Network, TxId, sapling::SaplingIncomingViewingKey};
+use transparent::{KeyPoolEntry, Keys, PubKey, SaplingOutPoint, WalletKeys};
+use zewif::{
+    Bip39Mnemonic, BlockHash, BlockHeight, Network, Script, TxId,
+    sapling::SaplingIncomingViewingKey,
+};
 
-#[derive(Debug)]
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ZcashdWallet {
-    address_names: HashMap<Address, String>,
-    address_purposes: HashMap<Address, String>,
+    accounting_entries: HashMap<String, BTreeMap<u64, AccountingEntry>>,
+    address_names: AddressNames,
+    address_purposes: AddressPurposes,
     bestblock_nomerkle: Option<BlockLocator>,
     bestblock: BlockLocator,
     client_version: ClientVersion,
     default_key: PubKey,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::keyed_map"))]
+    dest_data: HashMap<Address, HashMap<String, String>>,
     key_pool: HashMap<i64, KeyPoolEntry>,
     keys: Keys,
     min_version: ClientVersion,
+    legacy_accounts: HashMap<String, LegacyAccount>,
+    legacy_hd_chain: Option<LegacyHDChain>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::opt_legacy_seed"))]
     legacy_hd_seed: Option<LegacySeed>,
-    mnemonic_hd_chain: MnemonicHDChain,
-    bip39_mnemonic: Bip39Mnemonic,
+    mnemonic_hd_chain: Option<MnemonicHDChain>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::opt_bip39_mnemonic"))]
+    bip39_mnemonic: Option<Bip39Mnemonic>,
     network_info: NetworkInfo,
     orchard_note_commitment_tree: OrchardNoteCommitmentTree,
     orderposnext: Option<i64>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::hex_values"))]
+    sapling_extended_fvks:
+        HashMap<SaplingZPaymentAddress, ::sapling::zip32::ExtendedFullViewingKey>,
     sapling_keys: SaplingKeys,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::hex_values"))]
     sapling_z_addresses:
         HashMap<SaplingZPaymentAddress, SaplingIncomingViewingKey>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::keyed_map"))]
     send_recipients: HashMap<TxId, Vec<RecipientMapping>>,
     sprout_keys: Option<SproutKeys>,
     wallet_keys: Option<WalletKeys>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::keyed_map"))]
     transactions: HashMap<TxId, WalletTx>,
     unified_accounts: UnifiedAccounts,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::json_bytes_seq"))]
+    viewing_keys: Vec<::sapling::zip32::ExtendedFullViewingKey>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::json_bytes_set"))]
+    watch_only_scripts: HashSet<Script>,
     witnesscachesize: i64,
+    /// Built on first use by `transparent_address_index`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    transparent_address_index: OnceLock<HashMap<Address, PubKey>>,
+}
+
+/// Redacts the seed material, which every HD key of the wallet is derived
+/// from, and leaves out the transparent address index, which is only a cache.
+impl std::fmt::Debug for ZcashdWallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZcashdWallet")
+            .field("accounting_entries", &self.accounting_entries)
+            .field("address_names", &self.address_names)
+            .field("address_purposes", &self.address_purposes)
+            .field("bestblock_nomerkle", &self.bestblock_nomerkle)
+            .field("bestblock", &self.bestblock)
+            .field("client_version", &self.client_version)
+            .field("default_key", &self.default_key)
+            .field("dest_data", &self.dest_data)
+            .field("key_pool", &self.key_pool)
+            .field("keys", &self.keys)
+            .field("min_version", &self.min_version)
+            .field("legacy_accounts", &self.legacy_accounts)
+            .field("legacy_hd_chain", &self.legacy_hd_chain)
+            .field(
+                "legacy_hd_seed",
+                &self.legacy_hd_seed.as_ref().map(|seed| Redacted(seed.data().len())),
+            )
+            .field("mnemonic_hd_chain", &self.mnemonic_hd_chain)
+            .field(
+                "bip39_mnemonic",
+                &self
+                    .bip39_mnemonic
+                    .as_ref()
+                    .map(|mnemonic| Redacted(mnemonic.mnemonic().len())),
+            )
+            .field("network_info", &self.network_info)
+            .field("orchard_note_commitment_tree", &self.orchard_note_commitment_tree)
+            .field("orderposnext", &self.orderposnext)
+            .field("sapling_extended_fvks", &self.sapling_extended_fvks)
+            .field("sapling_keys", &self.sapling_keys)
+            .field("sapling_z_addresses", &self.sapling_z_addresses)
+            .field("send_recipients", &self.send_recipients)
+            .field("sprout_keys", &self.sprout_keys)
+            .field("wallet_keys", &self.wallet_keys)
+            .field("transactions", &self.transactions)
+            .field("unified_accounts", &self.unified_accounts)
+            .field("viewing_keys", &self.viewing_keys)
+            .field("watch_only_scripts", &self.watch_only_scripts)
+            .field("witnesscachesize", &self.witnesscachesize)
+            .finish_non_exhaustive()
+    }
 }
 
 impl ZcashdWallet {
-    #[allow(clippy::too_many_arguments)]
+    /// Assembles a wallet from its parts, grouped so that fields of the same
+    /// type, such as the address names and purposes, cannot be swapped.
     pub fn new(
-        address_names: HashMap<Address, String>,
-        address_purposes: HashMap<Address, String>,
-        bestblock_nomerkle: Option<BlockLocator>,
-        bestblock: BlockLocator,
-        client_version: ClientVersion,
-        default_key: PubKey,
-        key_pool: HashMap<i64, KeyPoolEntry>,
-        keys: Keys,
-        min_version: ClientVersion,
-        legacy_hd_seed: Option<LegacySeed>,
-        mnemonic_hd_chain: MnemonicHDChain,
-        bip39_mnemonic: Bip39Mnemonic,
-        network_info: NetworkInfo,
-        orchard_note_commitment_tree: OrchardNoteCommitmentTree,
-        orderposnext: Option<i64>,
-        sapling_keys: SaplingKeys,
-        sapling_z_addresses: HashMap<
-            SaplingZPaymentAddress,
-            SaplingIncomingViewingKey,
-        >,
-        send_recipients: HashMap<TxId, Vec<RecipientMapping>>,
-        sprout_keys: Option<SproutKeys>,
-        wallet_keys: Option<WalletKeys>,
-        transactions: HashMap<TxId, WalletTx>,
-        unified_accounts: UnifiedAccounts,
-        witnesscachesize: i64,
+        key_material: KeyMaterial,
+        metadata: Metadata,
+        chain_state: ChainState,
+        transactions: Transactions,
     ) -> Self {
+        let KeyMaterial {
+            bip39_mnemonic,
+            default_key,
+            key_pool,
+            keys,
+            legacy_hd_chain,
+            legacy_hd_seed,
+            mnemonic_hd_chain,
+            sapling_extended_fvks,
+            sapling_keys,
+            sapling_z_addresses,
+            sprout_keys,
+            unified_accounts,
+            viewing_keys,
+            wallet_keys,
+            watch_only_scripts,
+        } = key_material;
+        let Metadata {
+            address_names,
+            address_purposes,
+            client_version,
+            dest_data,
+            legacy_accounts,
+            min_version,
+            network_info,
+        } = metadata;
+        let ChainState {
+            bestblock,
+            bestblock_nomerkle,
+            orchard_note_commitment_tree,
+            witnesscachesize,
+        } = chain_state;
+        let Transactions {
+            accounting_entries,
+            orderposnext,
+            send_recipients,
+            transactions,
+        } = transactions;
         ZcashdWallet {
+            accounting_entries,
             address_names,
             address_purposes,
             bestblock_nomerkle,
             bestblock,
             client_version,
             default_key,
+            dest_data,
             key_pool,
             keys,
             min_version,
+            legacy_accounts,
+            legacy_hd_chain,
             legacy_hd_seed,
             mnemonic_hd_chain,
             bip39_mnemonic,
             network_info,
             orchard_note_commitment_tree,
             orderposnext,
+            sapling_extended_fvks,
             sapling_keys,
             sapling_z_addresses,
             send_recipients,
@@ -116,17 +233,31 @@ impl ZcashdWallet {
             wallet_keys,
             transactions,
             unified_accounts,
+            viewing_keys,
+            watch_only_scripts,
             witnesscachesize,
+            transparent_address_index: OnceLock::new(),
         }
     }
-    pub fn address_names(&self) -> &HashMap<Address, String> {
+
+    /// The legacy accounting entries of each account, by entry number.
+    pub fn accounting_entries(&self) -> &HashMap<String, BTreeMap<u64, AccountingEntry>> {
+        &self.accounting_entries
+    }
+
+    pub fn address_names(&self) -> &AddressNames {
         &self.address_names
     }
 
-    pub fn address_purposes(&self) -> &HashMap<Address, String> {
+    pub fn address_purposes(&self) -> &AddressPurposes {
         &self.address_purposes
     }
 
+    /// The purpose recorded for `address` in the wallet's address book.
+    pub fn address_purpose(&self, address: &Address) -> Option<&Purpose> {
+        self.address_purposes.get(address)
+    }
+
     pub fn bestblock_nomerkle(&self) -> Option<&BlockLocator> {
         self.bestblock_nomerkle.as_ref()
     }
@@ -137,21 +268,45 @@ impl ZcashdWallet {
 
     pub fn default_key(&self) -> &PubKey { &self.default_key }
 
+    pub fn dest_data(&self) -> &HashMap<Address, HashMap<String, String>> {
+        &self.dest_data
+    }
+
     pub fn key_pool(&self) -> &HashMap<i64, KeyPoolEntry> { &self.key_pool }
 
     pub fn keys(&self) -> &Keys { &self.keys }
 
     pub fn min_version(&self) -> &ClientVersion { &self.min_version }
 
+    /// The accounts of the accounting system removed in zcashd 4.5.0, by
+    /// name.
+    pub fn legacy_accounts(&self) -> &HashMap<String, LegacyAccount> {
+        &self.legacy_accounts
+    }
+
+    /// The sum of `account`'s accounting entries in zatoshis: the value moved
+    /// into or out of it between accounts, excluding its transactions.
+    pub fn accounting_balance(&self, account: &str) -> i64 {
+        self.accounting_entries.get(account).map_or(0, |entries| {
+            entries.values().map(AccountingEntry::credit_debit).sum()
+        })
+    }
+
+    pub fn legacy_hd_chain(&self) -> Option<&LegacyHDChain> {
+        self.legacy_hd_chain.as_ref()
+    }
+
     pub fn legacy_hd_seed(&self) -> Option<&LegacySeed> {
         self.legacy_hd_seed.as_ref()
     }
 
-    pub fn mnemonic_hd_chain(&self) -> &MnemonicHDChain {
-        &self.mnemonic_hd_chain
+    pub fn mnemonic_hd_chain(&self) -> Option<&MnemonicHDChain> {
+        self.mnemonic_hd_chain.as_ref()
     }
 
-    pub fn bip39_mnemonic(&self) -> &Bip39Mnemonic { &self.bip39_mnemonic }
+    pub fn bip39_mnemonic(&self) -> Option<&Bip39Mnemonic> {
+        self.bip39_mnemonic.as_ref()
+    }
 
     pub fn network_info(&self) -> &NetworkInfo { &self.network_info }
 
@@ -161,6 +316,12 @@ impl ZcashdWallet {
 
     pub fn orderposnext(&self) -> Option<i64> { self.orderposnext }
 
+    pub fn sapling_extended_fvks(
+        &self,
+    ) -> &HashMap<SaplingZPaymentAddress, ::sapling::zip32::ExtendedFullViewingKey> {
+        &self.sapling_extended_fvks
+    }
+
     pub fn sapling_keys(&self) -> &SaplingKeys { &self.sapling_keys }
 
     pub fn sapling_z_addresses(
@@ -189,9 +350,999 @@ impl ZcashdWallet {
         &self.unified_accounts
     }
 
+    pub fn viewing_keys(&self) -> &[::sapling::zip32::ExtendedFullViewingKey] {
+        &self.viewing_keys
+    }
+
+    pub fn watch_only_scripts(&self) -> &HashSet<Script> {
+        &self.watch_only_scripts
+    }
+
     pub fn witnesscachesize(&self) -> i64 { self.witnesscachesize }
+
+    /// The number of witnesses zcashd has cached for each of its notes, one
+    /// per block scanned, validated to be non-negative.
+    ///
+    /// zcashd keeps at most 101 (its maximum reorg length plus one), dropping
+    /// the oldest as new blocks arrive, so a note's witness can be rolled back
+    /// by at most this many blocks before it has to be rebuilt from the note
+    /// commitment tree. Returns an
+    /// [`InvalidAmount`](crate::Error::InvalidAmount) error if the record is
+    /// negative.
+    pub fn witness_cache_size(&self) -> crate::Result<usize> {
+        usize::try_from(self.witnesscachesize).map_err(|_| crate::Error::InvalidAmount {
+            kind: "witness cache size",
+            value: self.witnesscachesize,
+        })
+    }
 }
 
 impl ZcashdWallet {
     pub fn network(&self) -> Network { self.network_info.network() }
+
+    /// The on-disk generation of this wallet, derived from its client version
+    /// and the presence of the `mnemonichdchain` and `bestblock_nomerkle`
+    /// records.
+    pub fn era(&self) -> WalletEra {
+        WalletEra::detect(
+            &self.client_version,
+            self.mnemonic_hd_chain.is_some(),
+            self.bestblock_nomerkle.is_some(),
+        )
+    }
+
+    /// The wallet's BIP 39 recovery phrase, its words separated by single
+    /// spaces, or `None` if the wallet has no mnemonic seed, as in wallets
+    /// created before zcashd 5.0.0.
+    ///
+    /// The phrase is the wallet's master secret: anyone holding it can spend
+    /// all of the wallet's funds, so it must not be logged or stored in the
+    /// clear.
+    pub fn mnemonic_words(&self) -> Option<String> {
+        let mnemonic = self.bip39_mnemonic.as_ref()?;
+        let words: Vec<_> = mnemonic.mnemonic().split_whitespace().collect();
+        (!words.is_empty()).then(|| words.join(" "))
+    }
+
+    /// The hex of the legacy HD seed of wallets that predate mnemonic seeds,
+    /// or `None` if the wallet has none. Such a seed has no recovery phrase.
+    ///
+    /// Like [`mnemonic_words`](ZcashdWallet::mnemonic_words), this exposes
+    /// the secret every HD key of the wallet is derived from.
+    pub fn legacy_seed_hex(&self) -> Option<String> {
+        self.legacy_hd_seed
+            .as_ref()
+            .map(|seed| hex::encode(seed.data()))
+    }
+
+    /// The locator of the last block the wallet synced to: `bestblock_nomerkle`
+    /// for wallets of the [`NoMerkle`](WalletEra::NoMerkle) era, whose
+    /// `bestblock` is empty, and `bestblock` for earlier ones. Either falls
+    /// back to the other if empty, and `None` is returned if both are.
+    pub fn effective_best_block(&self) -> Option<&BlockLocator> {
+        let nomerkle = self.bestblock_nomerkle.as_ref();
+        let candidates = match self.era() {
+            WalletEra::NoMerkle => [nomerkle, Some(&self.bestblock)],
+            WalletEra::Legacy | WalletEra::Mnemonic => [Some(&self.bestblock), nomerkle],
+        };
+        candidates
+            .into_iter()
+            .flatten()
+            .find(|locator| !locator.is_empty())
+    }
+
+    /// The P2PKH addresses of the wallet's transparent keys, from both the
+    /// `key` and `wkey` records, sorted and without duplicates.
+    pub fn transparent_addresses(&self) -> Vec<Address> {
+        let network = self.network();
+        let addresses: BTreeSet<String> = self
+            .keys
+            .keypairs()
+            .map(|keypair| keypair.address(network).to_string())
+            .chain(
+                self.wallet_keys
+                    .iter()
+                    .flat_map(WalletKeys::keypairs)
+                    .map(|key| key.address(network).to_string()),
+            )
+            .collect();
+        addresses.into_iter().map(Address::from).collect()
+    }
+
+    /// The public key behind each P2PKH address of the wallet's transparent
+    /// keys, from both the `key` and `wkey` records.
+    ///
+    /// Deriving an address hashes its public key, so the index is built on
+    /// first use and kept for later calls.
+    pub fn transparent_address_index(&self) -> &HashMap<Address, PubKey> {
+        self.transparent_address_index.get_or_init(|| {
+            let network = self.network();
+            self.keys
+                .keypairs()
+                .map(|keypair| (keypair.address(network), keypair.pubkey().clone()))
+                .chain(
+                    self.wallet_keys
+                        .iter()
+                        .flat_map(WalletKeys::keypairs)
+                        .map(|key| (key.address(network), key.pubkey().clone())),
+                )
+                .collect()
+        })
+    }
+
+    /// Whether `address` is the P2PKH address of one of the wallet's
+    /// transparent keys.
+    pub fn is_transparent_address_mine(&self, address: &Address) -> bool {
+        self.transparent_address_index().contains_key(address)
+    }
+
+    /// Returns every address the wallet holds keys for, plus any other
+    /// address in its address book, each joined with its HD derivation path
+    /// and its `name`/`purpose` entries.
+    ///
+    /// Entries are ordered by protocol, then by address.
+    pub fn all_addresses(&self) -> Vec<AddressEntry> {
+        let network = self.network();
+        let mut entries: HashMap<String, AddressEntry> = HashMap::new();
+        let mut add = |address: String, protocol, hd_keypath: Option<&String>| {
+            entries.entry(address.clone()).or_insert_with(|| {
+                AddressEntry::new(address, protocol, hd_keypath.cloned())
+            });
+        };
+
+        for keypair in self.keys.keypairs() {
+            add(
+                keypair.address(network).to_string(),
+                AddressProtocol::Transparent,
+                keypair.metadata().hd_keypath(),
+            );
+        }
+
+        // `wkey` records carry no key metadata, so no derivation path.
+        for key in self.wallet_keys.iter().flat_map(WalletKeys::keypairs) {
+            add(
+                key.address(network).to_string(),
+                AddressProtocol::Transparent,
+                None,
+            );
+        }
+
+        for (address, spending_key) in self.sprout_keys.iter().flat_map(SproutKeys::iter) {
+            add(
+                address.to_string(network),
+                AddressProtocol::Sprout,
+                spending_key.metadata().hd_keypath(),
+            );
+        }
+
+        for (address, ivk) in &self.sapling_z_addresses {
+            let key = self.sapling_keys.get(ivk);
+            add(
+                address.to_string(network),
+                AddressProtocol::Sapling,
+                key.and_then(|key| key.metadata().hd_keypath()),
+            );
+        }
+
+        for metadata in &self.unified_accounts.address_metadata {
+            // Addresses whose UFVK is missing or whose receivers are invalid
+            // cannot be encoded, so they are left out.
+            let Ok(Some(address)) =
+                self.unified_accounts.encode_address(metadata, &self.network_info)
+            else {
+                continue;
+            };
+            let hd_keypath = self
+                .unified_accounts
+                .account_metadata
+                .get(&metadata.key_id)
+                .map(UnifiedAccountMetadata::hd_keypath);
+            add(address, AddressProtocol::Unified, hd_keypath.as_ref());
+        }
+
+        for address in self.address_names.keys().chain(self.address_purposes.keys()) {
+            if let Some(protocol) = AddressProtocol::from_encoded(&address.to_string()) {
+                add(address.to_string(), protocol, None);
+            }
+        }
+
+        let mut entries: Vec<AddressEntry> = entries
+            .into_values()
+            .map(|mut entry| {
+                let address = Address::from(entry.address().to_string());
+                entry.set_name(self.address_names.get(&address).cloned());
+                entry.set_purpose(self.address_purpose(&address).map(Purpose::to_string));
+                entry
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            (a.protocol(), a.address()).cmp(&(b.protocol(), b.address()))
+        });
+        entries
+    }
+
+    /// The creation time recorded in the metadata of the key behind a
+    /// transparent, Sapling or Sprout `address`.
+    ///
+    /// Transparent keys are looked up in both the `key` and `wkey` records.
+    /// Returns `None` if the wallet holds no key for the address, if the
+    /// address is unified, or if the key's metadata has no creation time.
+    pub fn key_creation_time(&self, address: &Address) -> Option<SecondsSinceEpoch> {
+        let network = self.network();
+        let encoded = address.to_string();
+        let metadata = match AddressProtocol::from_encoded(&encoded)? {
+            AddressProtocol::Transparent => {
+                return self.transparent_key_creation_time(address);
+            }
+            AddressProtocol::Sapling => self
+                .sapling_z_addresses
+                .iter()
+                .find(|(z_address, _)| z_address.to_string(network) == encoded)
+                .and_then(|(_, ivk)| self.sapling_keys.get(ivk))
+                .map(|key| key.metadata()),
+            AddressProtocol::Sprout => self
+                .sprout_keys
+                .iter()
+                .flat_map(SproutKeys::iter)
+                .find(|(z_address, _)| z_address.to_string(network) == encoded)
+                .map(|(_, key)| key.metadata()),
+            AddressProtocol::Unified => None,
+        }?;
+        metadata.create_time()
+    }
+
+    /// The creation time of the `key` or `wkey` record behind a transparent
+    /// `address`. A `wkey` created at time zero has no creation time.
+    fn transparent_key_creation_time(&self, address: &Address) -> Option<SecondsSinceEpoch> {
+        let pubkey = self.transparent_address_index().get(address)?;
+        if let Some(keypair) = self.keys.keypair_for_pubkey(pubkey) {
+            return keypair.metadata().create_time();
+        }
+        let key = self.wallet_keys.as_ref()?.keypair_for_pubkey(pubkey)?;
+        let time_created = key.time_created();
+        (!time_created.is_zero()).then_some(time_created)
+    }
+
+    /// A best-effort height to start scanning from when importing the
+    /// wallet, or `None` if it cannot be estimated.
+    ///
+    /// Block locators such as `bestblock` hold hashes rather than heights, so
+    /// the birthday is derived from timestamps instead: the earliest nonzero
+    /// creation time among the key metadata (transparent, Sprout and
+    /// Sapling), the HD chains and the key pool. That time is moved back by a
+    /// week and converted to a height assuming blocks slightly slower than
+    /// mainnet's target spacing, so the estimate errs towards scanning too
+    /// early rather than missing transactions.
+    ///
+    /// Only mainnet is supported; test networks have had block gaps too long
+    /// to estimate heights from time.
+    pub fn birthday_height(&self) -> Option<BlockHeight> {
+        if !matches!(self.network(), Network::Main) {
+            return None;
+        }
+        let key_times = self
+            .keys
+            .keypairs()
+            .map(|keypair| keypair.metadata().create_time())
+            .chain(self.sapling_keys.keypairs().map(|key| key.metadata().create_time()))
+            .chain(
+                self.sprout_keys
+                    .iter()
+                    .flat_map(SproutKeys::keypairs)
+                    .map(|key| key.metadata().create_time()),
+            )
+            .chain(self.legacy_hd_chain.as_ref().map(LegacyHDChain::create_time))
+            .flatten();
+        let other_times = self
+            .mnemonic_hd_chain
+            .as_ref()
+            .map(MnemonicHDChain::create_time)
+            .into_iter()
+            .chain(self.key_pool.values().map(KeyPoolEntry::timestamp));
+        let earliest = key_times
+            .chain(other_times)
+            .filter(|time| !time.is_zero())
+            .min()?;
+        Some(estimated_mainnet_height(u64::from(earliest)))
+    }
+
+    /// The recipients zcashd recorded for a transaction the wallet sent, or
+    /// an empty slice if there are none.
+    pub fn recipients_for_tx(&self, txid: &TxId) -> &[RecipientMapping] {
+        self.send_recipients.get(txid).map_or(&[], Vec::as_slice)
+    }
+
+    /// The unified address the user paid in transaction `txid`, where
+    /// `recipient` is the receiver of that address the output went to.
+    pub fn unified_address_for_recipient(
+        &self,
+        txid: &TxId,
+        recipient: &RecipientAddress,
+    ) -> Option<&str> {
+        self.recipients_for_tx(txid)
+            .iter()
+            .find(|mapping| mapping.recipient_address == *recipient)
+            .map(|mapping| mapping.unified_address.as_str())
+    }
+
+    /// The wallet's confirmed, unspent value in each pool, or an
+    /// [`InvalidAmount`](crate::Error::InvalidAmount) error if a pool's total
+    /// overflows `i64`.
+    pub fn balances(&self) -> crate::Result<Balances> {
+        Balances::compute(self)
+    }
+
+    /// The keys, addresses and transactions that differ between this wallet
+    /// and `other`, with `other` taken as the later of the two.
+    pub fn diff(&self, other: &ZcashdWallet) -> WalletDiff {
+        WalletDiff::compute(self, other)
+    }
+
+    /// The Sapling notes received by the wallet whose nullifiers are revealed
+    /// by one of its transactions.
+    pub fn spent_notes(&self) -> HashSet<&SaplingOutPoint> {
+        self.transactions
+            .values()
+            .flat_map(|tx| {
+                tx.sapling_note_data()
+                    .into_iter()
+                    .flatten()
+                    .filter(|(outpoint, _)| tx.is_output_spent(outpoint.vout()) == Some(true))
+                    .map(|(outpoint, _)| outpoint)
+            })
+            .collect()
+    }
+
+    /// Returns the Sapling note commitment tree as of the block the wallet's
+    /// Sapling witnesses were last updated to.
+    ///
+    /// zcashd has no record holding a wallet-wide Sapling tree. Each Sapling
+    /// note instead carries witnesses, newest first, from which the tree at
+    /// the block they were updated to can be rebuilt. The newest witness of
+    /// the note with the greatest `witness_height` is used, ties going to the
+    /// note with the greatest position in the tree; the result is `None` if
+    /// the wallet has no witnessed Sapling notes.
+    pub fn sapling_note_commitment_tree(&self) -> crate::Result<Option<SaplingNoteCommitmentTree>> {
+        self.transactions
+            .values()
+            .filter_map(WalletTx::sapling_note_data)
+            .flat_map(HashMap::values)
+            .filter_map(|note_data| {
+                let witness = note_data.witnesses().first()?;
+                Some(((note_data.witness_height(), witness.tree().size()), witness))
+            })
+            .max_by_key(|(key, _)| *key)
+            .map(|(_, witness)| SaplingNoteCommitmentTree::at_witness_tip(witness))
+            .transpose()
+    }
+
+    /// Returns the wallet's transactions in the order they entered the
+    /// wallet, by their [`order_pos`](WalletTx::order_pos).
+    ///
+    /// Positions at or beyond `orderposnext`, the next position zcashd would
+    /// have assigned, are treated like missing ones: those transactions come
+    /// last. Ties are broken by transaction ID.
+    pub fn transactions_in_order(&self) -> Vec<(&TxId, &WalletTx)> {
+        let bound = self.orderposnext.unwrap_or(i64::MAX);
+        let position = |tx: &WalletTx| tx.order_pos().filter(|pos| (0..bound).contains(pos));
+        let mut transactions: Vec<_> = self.transactions.iter().collect();
+        transactions.sort_by_key(|(txid, tx)| (position(tx).is_none(), position(tx), **txid));
+        transactions
+    }
+
+    /// The height of the block each transaction was mined in, or `None` for
+    /// transactions not yet mined.
+    ///
+    /// zcashd records only the hash of that block, so heights come from the
+    /// wallet's note tracking: the Orchard note commitment tree records the
+    /// height of each transaction with Orchard notes, and a Sapling note's
+    /// witness cache holds one witness for each block from the one containing
+    /// it until the cache fills. Mined transactions with neither also map to
+    /// `None`.
+    pub fn tx_heights(&self) -> HashMap<TxId, Option<BlockHeight>> {
+        self.transactions
+            .iter()
+            .map(|(txid, tx)| (*txid, self.tx_height(txid, tx)))
+            .collect()
+    }
+
+    fn tx_height(&self, txid: &TxId, tx: &WalletTx) -> Option<BlockHeight> {
+        // zcashd keeps `MAX_REORG_LENGTH + 1` witnesses per note.
+        const WITNESS_CACHE_SIZE: usize = 101;
+
+        if tx.hash_block() == BlockHash::from_bytes([0u8; 32]) {
+            return None;
+        }
+        if let Some(height) = self.orchard_note_commitment_tree.tx_height(txid) {
+            return Some(BlockHeight::from(u32::from(height)));
+        }
+        tx.sapling_note_data()
+            .into_iter()
+            .flatten()
+            .find_map(|(_, note_data)| {
+                let cached = note_data.witnesses().len();
+                if cached == 0 || cached >= WITNESS_CACHE_SIZE {
+                    return None;
+                }
+                u32::try_from(note_data.witness_height())
+                    .ok()?
+                    .checked_sub(cached as u32 - 1)
+                    .map(BlockHeight::from)
+            })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ZcashdWallet {
+    /// Serializes the wallet as pretty-printed JSON with secret material
+    /// (private keys, spending keys and seeds) redacted.
+    pub fn to_json_pretty(&self) -> crate::Result<String> {
+        self.to_json(false)
+    }
+
+    /// Serializes the wallet as pretty-printed JSON, including secret
+    /// material. Handle the output with the same care as the wallet itself.
+    pub fn to_json_with_secrets(&self) -> crate::Result<String> {
+        self.to_json(true)
+    }
+
+    /// Reconstructs a wallet from JSON produced by
+    /// [`to_json_with_secrets`](Self::to_json_with_secrets).
+    ///
+    /// Redacted output from [`to_json_pretty`](Self::to_json_pretty) is
+    /// rejected, since the secrets it omits cannot be recovered.
+    pub fn from_json(json: &str) -> crate::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    fn to_json(&self, include_secrets: bool) -> crate::Result<String> {
+        // Going through `Value` sorts object keys, which keeps the output
+        // stable across runs despite the `HashMap`s in the wallet.
+        let value = crate::serde_support::with_scope(include_secrets, self.network(), || {
+            serde_json::to_value(self)
+        })?;
+        Ok(serde_json::to_string_pretty(&value)?)
+    }
+}
+
+/// Converts a time to a mainnet block height, rounding down.
+///
+/// Blocks are assumed to arrive every 152 seconds before Blossom and every 76
+/// seconds after it, slightly slower than the 150 and 75 second targets, and
+/// the time is first moved back by a week, so the estimate falls below the
+/// true height.
+fn estimated_mainnet_height(time: u64) -> BlockHeight {
+    const GENESIS_TIME: u64 = 1_477_641_360;
+    const BLOSSOM_HEIGHT: u64 = 653_600;
+    const BLOSSOM_TIME: u64 = 1_576_100_000;
+    const MARGIN: u64 = 7 * 24 * 60 * 60;
+
+    let time = time.saturating_sub(MARGIN);
+    let height = if time < BLOSSOM_TIME {
+        time.saturating_sub(GENESIS_TIME) / 152
+    } else {
+        BLOSSOM_HEIGHT + (time - BLOSSOM_TIME) / 76
+    };
+    BlockHeight::from(u32::try_from(height).unwrap_or(u32::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use zewif::{Bip39Mnemonic, BlockHeight, Data, LegacySeed, TxId};
+
+    use crate::{
+        Error, ZcashdParser, ZcashdWallet, parse,
+        test_support::{
+            compact, dump_from_records, minimal_wallet, minimal_wallet_records, named_address,
+            parse_records, received_note_wallet_tx_value, sapling_key_records,
+            sapling_output_transaction, sapling_spend_transaction, sapling_wallet_tx_value,
+            sapling_witness_wallet_tx_value, txid, v1_transaction, wallet_tx_value, wkey_record,
+        },
+        zcashd_wallet::{
+            Address, AddressEntry, AddressProtocol, KeyMetadata, Purpose, RecipientAddress,
+            SecondsSinceEpoch, WalletEra,
+            sapling::SaplingKey,
+            sprout::SproutSpendingKey,
+            transparent::{KeyPair, Keys},
+            u252,
+        },
+    };
+
+    #[test]
+    fn test_debug_redacts_seed_material() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                      abandon abandon about";
+        let wallet = ZcashdWallet::builder()
+            .bip39_mnemonic(Some(Bip39Mnemonic::new(phrase.to_string(), None)))
+            .legacy_hd_seed(Some(LegacySeed::new(Data::from_slice(&[0xab; 32]), None)))
+            .build();
+        let debug = format!("{wallet:?}");
+        assert!(!debug.contains("abandon"));
+        assert!(!debug.contains("about"));
+        assert!(!debug.contains("abab"));
+        assert!(debug.contains("<redacted 93 bytes>"));
+        assert!(debug.contains("<redacted 32 bytes>"));
+    }
+
+    #[test]
+    fn test_seed_secrets() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                      abandon abandon about";
+        let wallet = ZcashdWallet::builder()
+            .bip39_mnemonic(Some(Bip39Mnemonic::new(format!(" {phrase}\n"), None)))
+            .build();
+        assert_eq!(wallet.mnemonic_words().as_deref(), Some(phrase));
+        assert_eq!(wallet.legacy_seed_hex(), None);
+
+        let wallet = ZcashdWallet::builder()
+            .legacy_hd_seed(Some(LegacySeed::new(Data::from_slice(&[0xab; 32]), None)))
+            .build();
+        assert_eq!(wallet.mnemonic_words(), None);
+        assert_eq!(wallet.legacy_seed_hex(), Some("ab".repeat(32)));
+
+        let wallet = ZcashdWallet::builder()
+            .bip39_mnemonic(Some(Bip39Mnemonic::new(String::new(), None)))
+            .build();
+        assert_eq!(wallet.mnemonic_words(), None);
+    }
+
+    #[test]
+    fn test_transparent_address_index_is_built_once() {
+        let keypair =
+            KeyPair::from_secret(&[0x01; 32], true, KeyMetadata::new(None, None, None)).unwrap();
+        let pubkey = keypair.pubkey().clone();
+        let wallet = ZcashdWallet::builder()
+            .keys(Keys::new([(pubkey.clone(), keypair)].into()))
+            .build();
+        let address = wallet.transparent_addresses()[0].clone();
+        assert!(wallet.transparent_address_index.get().is_none());
+
+        let index = wallet.transparent_address_index();
+        assert_eq!(index.get(&address), Some(&pubkey));
+        assert!(wallet.is_transparent_address_mine(&address));
+        assert!(std::ptr::eq(index, wallet.transparent_address_index()));
+        assert!(!wallet.is_transparent_address_mine(&Address::from("t1other".to_string())));
+    }
+
+    #[test]
+    fn test_secret_key_equality() {
+        let metadata = || KeyMetadata::new(None, None, None);
+        let privkey = |secret| {
+            KeyPair::from_secret(&[secret; 32], true, metadata())
+                .unwrap()
+                .privkey()
+                .clone()
+        };
+        assert_eq!(privkey(0x01), privkey(0x01));
+        assert_ne!(privkey(0x01), privkey(0x02));
+        let privkeys: HashSet<_> = [privkey(0x01), privkey(0x01), privkey(0x02)].into();
+        assert_eq!(privkeys.len(), 2);
+
+        let sprout =
+            |key| SproutSpendingKey::new(u252::from_slice(&[key; 32]).unwrap(), metadata());
+        assert_eq!(sprout(0x01), sprout(0x01));
+        assert_ne!(sprout(0x01), sprout(0x02));
+
+        let sapling = |seed| {
+            let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[seed; 32]);
+            SaplingKey::from_spending_key(extsk, metadata())
+        };
+        assert_eq!(sapling(0x07), sapling(0x07));
+        assert_ne!(sapling(0x07), sapling(0x08));
+    }
+
+    #[test]
+    fn test_secret_key_debug_is_redacted() {
+        let metadata = || KeyMetadata::new(None, None, None);
+        let keypair = KeyPair::from_secret(&[0x5a; 32], false, metadata()).unwrap();
+        let privkey = keypair.privkey();
+        let debug = format!("{keypair:?}");
+        assert!(debug.contains("PrivKey(<redacted 279 bytes>)"));
+        assert!(!debug.contains(&"5a".repeat(32)));
+        assert!(privkey.reveal().contains(&format!("{:?}", privkey.data())));
+
+        let sprout = SproutSpendingKey::new(u252::from_slice(&[0x0a; 32]).unwrap(), metadata());
+        let key = format!("{:?}", sprout.key());
+        assert!(!format!("{sprout:?}").contains(&key));
+        assert!(sprout.reveal().contains(&key));
+
+        let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]);
+        let sapling = SaplingKey::from_spending_key(extsk.clone(), metadata());
+        let debug = format!("{sapling:?}");
+        assert!(debug.contains("extsk: <redacted 169 bytes>"));
+        assert!(!debug.contains(&format!("{extsk:?}")));
+        assert!(sapling.reveal().contains(&format!("{extsk:?}")));
+    }
+
+    #[test]
+    fn test_transparent_addresses() {
+        let wallet = minimal_wallet();
+        let default_address = wallet.default_key().key_id().to_string(wallet.network());
+        assert_eq!(wallet.transparent_addresses(), [Address::from(default_address)]);
+    }
+
+    #[test]
+    fn test_all_addresses() {
+        let mut records = minimal_wallet_records();
+        let mut wkey_pubkey = vec![0x03];
+        wkey_pubkey.extend_from_slice(&[0x12; 32]);
+        records.push(wkey_record(&wkey_pubkey, &[0x02; 32], 1_400_000_000));
+        let wallet = parse_records(&records);
+        let addresses = wallet.all_addresses();
+
+        let protocols: Vec<_> = addresses.iter().map(AddressEntry::protocol).collect();
+        assert_eq!(
+            protocols,
+            [
+                AddressProtocol::Transparent,
+                AddressProtocol::Transparent,
+                AddressProtocol::Transparent,
+                AddressProtocol::Sprout,
+                AddressProtocol::Sapling,
+            ]
+        );
+
+        let named = addresses
+            .iter()
+            .find(|entry| entry.address() == named_address())
+            .unwrap();
+        assert_eq!(named.name(), Some("savings"));
+        assert_eq!(named.purpose(), Some("receive"));
+        assert_eq!(named.hd_keypath(), None);
+
+        let key_address = wallet.default_key().key_id().to_string(wallet.network());
+        let derived = addresses
+            .iter()
+            .find(|entry| entry.address() == key_address)
+            .unwrap();
+        assert_eq!(derived.hd_keypath(), Some("m/0'/0'/0'"));
+        assert_eq!(derived.name(), None);
+
+        let wallet_key = wallet.wallet_keys().unwrap().keypairs().next().unwrap();
+        let wkey_address = wallet_key.address(wallet.network()).to_string();
+        assert!(
+            addresses
+                .iter()
+                .any(|entry| entry.address() == wkey_address)
+        );
+        assert_eq!(wallet.transparent_addresses().len(), 2);
+    }
+
+    #[test]
+    fn test_address_purpose() {
+        let mut records = minimal_wallet_records();
+        let address = named_address();
+        for (keyname, _, value) in records.iter_mut() {
+            if *keyname == "purpose" {
+                *value = compact(b"cold storage");
+            }
+        }
+        let wallet = parse_records(&records);
+
+        let purpose = wallet.address_purpose(&Address::from(address.clone()));
+        assert_eq!(purpose, Some(&Purpose::Other("cold storage".to_string())));
+        let entry = wallet
+            .all_addresses()
+            .into_iter()
+            .find(|entry| entry.address() == address)
+            .unwrap();
+        assert_eq!(entry.purpose(), Some("cold storage"));
+
+        let wallet = minimal_wallet();
+        let purpose = wallet.address_purpose(&Address::from(address));
+        assert_eq!(purpose, Some(&Purpose::Receive));
+    }
+
+    #[test]
+    fn test_unknown_address_purposes() {
+        let address = named_address();
+        for raw in ["", "unknown"] {
+            let mut records = minimal_wallet_records();
+            for (keyname, _, value) in records.iter_mut() {
+                if *keyname == "purpose" {
+                    *value = compact(raw.as_bytes());
+                }
+            }
+            let wallet = parse_records(&records);
+
+            let purpose = wallet.address_purpose(&Address::from(address.clone())).unwrap();
+            assert_eq!(purpose, &Purpose::Unknown(raw.to_string()));
+            assert!(purpose.is_unknown());
+            assert_eq!(String::from(purpose.clone()), raw);
+        }
+    }
+
+    #[test]
+    fn test_recipients_for_tx() {
+        let txid = [0x66; 32];
+        let recipient = |key_id: u8| {
+            let mut address = vec![0];
+            address.extend_from_slice(&[key_id; 20]);
+            address
+        };
+        let mapping_key = |key_id: u8| [txid.as_slice(), &recipient(key_id)].concat();
+        let mut records = minimal_wallet_records();
+        records.push(("recipientmapping", mapping_key(0x01), compact(b"u1first")));
+        records.push(("recipientmapping", mapping_key(0x02), compact(b"u1second")));
+        let wallet = parse_records(&records);
+
+        let txid = TxId::from_bytes(txid);
+        assert_eq!(wallet.recipients_for_tx(&txid).len(), 2);
+        let other_txid = TxId::from_bytes([0x67; 32]);
+        assert!(wallet.recipients_for_tx(&other_txid).is_empty());
+
+        let address =
+            |key_id: u8| parse!(buf = &recipient(key_id), RecipientAddress, "recipient").unwrap();
+        assert_eq!(
+            wallet.unified_address_for_recipient(&txid, &address(0x02)),
+            Some("u1second")
+        );
+        assert_eq!(
+            wallet.unified_address_for_recipient(&txid, &address(0x03)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_wallet_era() {
+        let wallet = minimal_wallet();
+        assert_eq!(wallet.era(), WalletEra::Mnemonic);
+    }
+
+    #[test]
+    fn test_birthday_height() {
+        let wallet = minimal_wallet();
+        // The key and key pool entry were both created at 1_600_000_000
+        // (2020-09-13); the Sprout key has no creation time. A week earlier at
+        // 76 seconds per block after Blossom gives 653_600 + 306_515.
+        assert_eq!(wallet.birthday_height(), Some(BlockHeight::from(960_115)));
+    }
+
+    #[test]
+    fn test_key_creation_time() {
+        let mut records = minimal_wallet_records();
+        // Give the Sprout key a creation time, add a Sapling key for the
+        // fixture's Sapling address and add a `wkey` key.
+        let mut zkeymeta = 1i32.to_le_bytes().to_vec();
+        zkeymeta.extend_from_slice(&1_500_000_000u64.to_le_bytes());
+        records.retain(|(keyname, _, _)| *keyname != "zkeymeta");
+        records.push(("zkeymeta", vec![0x88; 64], zkeymeta));
+        let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]);
+        records.extend(sapling_key_records(&extsk));
+        let mut wkey_pubkey = vec![0x03];
+        wkey_pubkey.extend_from_slice(&[0x12; 32]);
+        records.push(wkey_record(&wkey_pubkey, &[0x02; 32], 1_400_000_000));
+        let wallet = parse_records(&records);
+        let network = wallet.network();
+
+        let creation_time = |address: String| wallet.key_creation_time(&Address::from(address));
+        let transparent = wallet.default_key().key_id().to_string(network);
+        assert_eq!(
+            creation_time(transparent),
+            Some(SecondsSinceEpoch::from(1_600_000_000u64))
+        );
+        let sapling = wallet.sapling_z_addresses().keys().next().unwrap().to_string(network);
+        assert_eq!(creation_time(sapling), Some(SecondsSinceEpoch::from(1_550_000_000u64)));
+        let (sprout, _) = wallet.sprout_keys().unwrap().iter().next().unwrap();
+        assert_eq!(
+            creation_time(sprout.to_string(network)),
+            Some(SecondsSinceEpoch::from(1_500_000_000u64))
+        );
+        let wallet_key = wallet.wallet_keys().unwrap().keypairs().next().unwrap();
+        assert_eq!(
+            creation_time(wallet_key.address(network).to_string()),
+            Some(SecondsSinceEpoch::from(1_400_000_000u64))
+        );
+        // An address the wallet has no key for.
+        assert_eq!(creation_time(named_address()), None);
+    }
+
+    #[test]
+    fn test_transactions_in_order() {
+        let mut records = minimal_wallet_records();
+        records.push(("orderposnext", vec![], 3i64.to_le_bytes().to_vec()));
+        // Inserted in the order 0x30, 0x10, 0x20; 0x40 has a position past
+        // `orderposnext` and 0x05 has none.
+        for (txid_byte, order_pos) in [(0x10, 1), (0x20, 2), (0x30, 0), (0x40, 7)] {
+            records.push((
+                "tx",
+                vec![txid_byte; 32],
+                wallet_tx_value(&v1_transaction(), Some(order_pos)),
+            ));
+        }
+        records.push(("tx", vec![0x05; 32], wallet_tx_value(&v1_transaction(), None)));
+        let wallet = parse_records(&records);
+
+        let txids: Vec<_> = wallet
+            .transactions_in_order()
+            .into_iter()
+            .map(|(txid, _)| *txid)
+            .collect();
+        let expected: Vec<_> = [0x30, 0x10, 0x20, 0x05, 0x40]
+            .into_iter()
+            .map(|byte| txid(byte))
+            .collect();
+        assert_eq!(txids, expected);
+    }
+
+    #[test]
+    fn test_tx_heights() {
+        let mut mempool_tx = wallet_tx_value(&v1_transaction(), None);
+        let hash_block = v1_transaction().len();
+        mempool_tx[hash_block..hash_block + 32].fill(0);
+
+        // 0x55 has its height in the fixture's Orchard tree, 0x20 was mined at
+        // block 100 with its Sapling note witnessed only there, nothing
+        // records the height of 0x10 and 0x30 is in the mempool.
+        let mut records = minimal_wallet_records();
+        records.extend([
+            (
+                "tx",
+                vec![0x55; 32],
+                wallet_tx_value(&v1_transaction(), None),
+            ),
+            (
+                "tx",
+                vec![0x20; 32],
+                sapling_wallet_tx_value(&[0, 0, 0], 100),
+            ),
+            (
+                "tx",
+                vec![0x10; 32],
+                wallet_tx_value(&v1_transaction(), None),
+            ),
+            ("tx", vec![0x30; 32], mempool_tx),
+        ]);
+        let wallet = parse_records(&records);
+
+        let heights = wallet.tx_heights();
+        assert_eq!(heights.len(), 4);
+        assert_eq!(heights[&txid(0x55)], Some(BlockHeight::from(2_000_000)));
+        assert_eq!(heights[&txid(0x20)], Some(BlockHeight::from(100)));
+        assert_eq!(heights[&txid(0x10)], None);
+        assert_eq!(heights[&txid(0x30)], None);
+    }
+
+    #[test]
+    fn test_sapling_note_commitment_tree() {
+        use incrementalmerkletree::{Hashable, Level, frontier::CommitmentTree};
+
+        let leaves: Vec<_> = (1..=5u8)
+            .map(|i| {
+                let mut bytes = [0u8; 32];
+                bytes[0] = i;
+                ::sapling::Node::from_bytes(bytes).unwrap()
+            })
+            .collect();
+        let leaf = |i: usize| [&[1][..], &leaves[i].to_bytes()].concat();
+        let pair = |i: usize| {
+            ::sapling::Node::combine(Level::from(0), &leaves[i], &leaves[i + 1]).to_bytes()
+        };
+        // The cursor both witnesses have started since: a partial subtree of
+        // height 2 holding the fifth leaf.
+        let cursor = [&[1][..], &leaf(4), &[0, 0]].concat();
+        // The second note's witness: a tree of two leaves, then the subtree
+        // of the next two as filled.
+        let second_note = [&leaf(0)[..], &leaf(1), &[0, 1], &pair(2), &cursor].concat();
+        // The fourth note's witness: a tree of four leaves, nothing filled.
+        let fourth_note = [&leaf(2)[..], &leaf(3), &[1, 1], &pair(0), &[0], &cursor].concat();
+
+        let mut records = minimal_wallet_records();
+        records.extend([
+            (
+                "tx",
+                vec![0x10; 32],
+                sapling_witness_wallet_tx_value(&second_note, 200),
+            ),
+            (
+                "tx",
+                vec![0x20; 32],
+                sapling_witness_wallet_tx_value(&fourth_note, 200),
+            ),
+            (
+                "tx",
+                vec![0x30; 32],
+                sapling_wallet_tx_value(&[0, 0, 0], 100),
+            ),
+        ]);
+        let wallet = parse_records(&records);
+        let tree = wallet.sapling_note_commitment_tree().unwrap().unwrap();
+
+        // Both witnesses at the greatest height lead to the tree of all five
+        // leaves, the one their roots are taken from.
+        let mut expected = CommitmentTree::<_, 32>::empty();
+        for leaf in &leaves {
+            expected.append(*leaf).unwrap();
+        }
+        assert_eq!(tree.size(), 5);
+        assert_eq!(tree.root(), ::sapling::Anchor::from(expected.root()));
+
+        let wallet = minimal_wallet();
+        assert!(wallet.sapling_note_commitment_tree().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_effective_best_block() {
+        let mut nomerkle = 6_000_050u32.to_le_bytes().to_vec();
+        nomerkle.push(1);
+        nomerkle.extend_from_slice(&[0x45; 32]);
+        let mut records = minimal_wallet_records();
+        for (keyname, _, value) in records.iter_mut() {
+            if matches!(*keyname, "version" | "minversion") {
+                *value = 6_000_050u32.to_le_bytes().to_vec();
+            } else if *keyname == "bestblock" {
+                value.clear();
+            }
+        }
+        records.push(("bestblock_nomerkle", vec![], nomerkle));
+        let dump = dump_from_records(&records);
+        let (wallet, failures) = ZcashdParser::parse_best_effort(&dump);
+
+        let categories: Vec<_> = failures.iter().map(|(category, _)| *category).collect();
+        assert!(!categories.contains(&"bestblock"));
+        assert_eq!(wallet.era(), WalletEra::NoMerkle);
+        assert!(wallet.bestblock().is_empty());
+        let tip = wallet.effective_best_block().unwrap().tip().unwrap();
+        assert_eq!(tip.into_bytes(), [0x45; 32]);
+
+        let wallet = minimal_wallet();
+        let tip = wallet.effective_best_block().unwrap().tip().unwrap();
+        assert_eq!(tip.into_bytes(), [0x44; 32]);
+
+        let wallet = ZcashdWallet::builder().build();
+        assert!(wallet.effective_best_block().is_none());
+    }
+
+    #[test]
+    fn test_spent_notes() {
+        let (_, own_address) =
+            ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]).default_address();
+        let mut records = minimal_wallet_records();
+        // Two notes received at output 0, with nullifiers 0xaa and 0xbb.
+        for (txid_byte, nullifier) in [(0x10, [0xaa; 32]), (0x11, [0xbb; 32])] {
+            let tx = sapling_output_transaction(&own_address, 10_000, [0; 512]);
+            let value = received_note_wallet_tx_value(&tx, txid_byte, nullifier);
+            records.push(("tx", vec![txid_byte; 32], value));
+        }
+        // A later transaction spending the first note.
+        let mut value = wallet_tx_value(&sapling_spend_transaction([0xaa; 32]), None);
+        value.push(0);
+        records.push(("tx", vec![0x20; 32], value));
+        let wallet = parse_records(&records);
+        let transactions = wallet.transactions();
+
+        assert_eq!(transactions[&txid(0x10)].is_output_spent(0), Some(true));
+        assert_eq!(transactions[&txid(0x11)].is_output_spent(0), Some(false));
+        // Outputs without note data have no spentness.
+        assert_eq!(transactions[&txid(0x10)].is_output_spent(1), None);
+        assert_eq!(transactions[&txid(0x20)].is_output_spent(0), None);
+
+        let spent: Vec<_> = wallet
+            .spent_notes()
+            .into_iter()
+            .map(|outpoint| (outpoint.txid(), outpoint.vout()))
+            .collect();
+        assert_eq!(spent, vec![(txid(0x10), 0)]);
+    }
+
+    #[test]
+    fn test_witness_cache_size() {
+        let wallet = minimal_wallet();
+        assert_eq!(wallet.witness_cache_size().unwrap(), 0);
+
+        let mut records = minimal_wallet_records();
+        for (keyname, _, value) in &mut records {
+            if *keyname == "witnesscachesize" {
+                *value = (-1i64).to_le_bytes().to_vec();
+            }
+        }
+        let wallet = parse_records(&records);
+        assert_eq!(wallet.witnesscachesize(), -1);
+        assert!(matches!(
+            wallet.witness_cache_size(),
+            Err(Error::InvalidAmount { value: -1, .. })
+        ));
+    }
 }
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use zewif::Data;
+
+use crate::{
+    Result, parse,
+    parser::prelude::*,
+    zcashd_wallet::{ClientVersion, SecondsSinceEpoch},
+};
+
+/// A credit or debit between legacy accounts, from an `acentry` record. The
+/// record's key holds the account's name and the entry's number.
+///
+/// See [`LegacyAccount`](crate::zcashd_wallet::LegacyAccount).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountingEntry {
+    version: ClientVersion,
+    credit_debit: i64,
+    time: SecondsSinceEpoch,
+    other_account: String,
+    comment: String,
+    map_value: HashMap<String, String>,
+}
+
+impl AccountingEntry {
+    pub fn version(&self) -> ClientVersion {
+        self.version
+    }
+
+    /// The amount credited to the account in zatoshis, negative for a debit.
+    pub fn credit_debit(&self) -> i64 {
+        self.credit_debit
+    }
+
+    pub fn time(&self) -> SecondsSinceEpoch {
+        self.time
+    }
+
+    /// The account the amount was moved from or to.
+    pub fn other_account(&self) -> &str {
+        &self.other_account
+    }
+
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    pub fn map_value(&self) -> &HashMap<String, String> {
+        &self.map_value
+    }
+
+    pub fn order_pos(&self) -> Option<i64> {
+        self.map_value.get("n")?.parse().ok()
+    }
+}
+
+impl Parse for AccountingEntry {
+    fn parse(p: &mut Parser) -> Result<Self> {
+        let version = parse!(p, "version")?;
+        let credit_debit = parse!(p, "credit_debit")?;
+        let time = parse!(p, "time")?;
+        let other_account = parse!(p, "other_account")?;
+        // zcashd appends the entry's map value, and reserved bytes after it,
+        // to the comment following a NUL.
+        let comment_data = parse!(p, Data, "comment")?;
+        let bytes = comment_data.as_slice();
+        let (comment, map_value) = match bytes.iter().position(|&byte| byte == 0) {
+            Some(nul) => {
+                let extra_bytes = &bytes[nul + 1..];
+                let mut extra = Parser::new(&extra_bytes);
+                (&bytes[..nul], parse!(&mut extra, "map_value")?)
+            }
+            None => (bytes, HashMap::new()),
+        };
+        Ok(Self {
+            version,
+            credit_debit,
+            time,
+            other_account,
+            comment: String::from_utf8_lossy(comment).into_owned(),
+            map_value,
+        })
+    }
+}
@@ -0,0 +1,68 @@
+use std::{collections::HashMap, ops::Deref};
+
+use crate::{
+    Result,
+    zcashd_wallet::{Address, Purpose, check_map, merge_map},
+};
+
+/// The label of each address in the wallet's address book, from its `name`
+/// records.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AddressNames(
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::keyed_map"))]
+    HashMap<Address, String>,
+);
+
+impl AddressNames {
+    pub fn new(map: HashMap<Address, String>) -> Self {
+        Self(map)
+    }
+
+    pub(crate) fn check_merge(&self, other: &Self) -> Result<()> {
+        check_map("address name", &self.0, &other.0)
+    }
+
+    pub(crate) fn merge(&mut self, other: Self) {
+        merge_map(&mut self.0, other.0)
+    }
+}
+
+impl Deref for AddressNames {
+    type Target = HashMap<Address, String>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// The purpose of each address in the wallet's address book, from its
+/// `purpose` records.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AddressPurposes(
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::keyed_map"))]
+    HashMap<Address, Purpose>,
+);
+
+impl AddressPurposes {
+    pub fn new(map: HashMap<Address, Purpose>) -> Self {
+        Self(map)
+    }
+
+    pub(crate) fn check_merge(&self, other: &Self) -> Result<()> {
+        check_map("address purpose", &self.0, &other.0)
+    }
+
+    pub(crate) fn merge(&mut self, other: Self) {
+        merge_map(&mut self.0, other.0)
+    }
+}
+
+impl Deref for AddressPurposes {
+    type Target = HashMap<Address, Purpose>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,133 @@
+use std::convert::Infallible;
+
+use zcash_address::{ConversionError, TryFromAddress, ZcashAddress};
+use zcash_protocol::consensus::NetworkType;
+
+/// The protocol an address in an [`AddressEntry`] belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AddressProtocol {
+    /// Transparent P2PKH or P2SH address
+    Transparent,
+    /// Sprout shielded address
+    Sprout,
+    /// Sapling shielded address
+    Sapling,
+    /// Unified address
+    Unified,
+}
+
+impl AddressProtocol {
+    /// Determines the protocol of an encoded address, or `None` if it cannot
+    /// be decoded.
+    pub fn from_encoded(address: &str) -> Option<Self> {
+        ZcashAddress::try_from_encoded(address)
+            .ok()?
+            .convert::<AddressProtocol>()
+            .ok()
+    }
+}
+
+impl TryFromAddress for AddressProtocol {
+    type Error = Infallible;
+
+    fn try_from_sprout(
+        _: NetworkType,
+        _: [u8; 64],
+    ) -> std::result::Result<Self, ConversionError<Self::Error>> {
+        Ok(AddressProtocol::Sprout)
+    }
+
+    fn try_from_sapling(
+        _: NetworkType,
+        _: [u8; 43],
+    ) -> std::result::Result<Self, ConversionError<Self::Error>> {
+        Ok(AddressProtocol::Sapling)
+    }
+
+    fn try_from_unified(
+        _: NetworkType,
+        _: zcash_address::unified::Address,
+    ) -> std::result::Result<Self, ConversionError<Self::Error>> {
+        Ok(AddressProtocol::Unified)
+    }
+
+    fn try_from_transparent_p2pkh(
+        _: NetworkType,
+        _: [u8; 20],
+    ) -> std::result::Result<Self, ConversionError<Self::Error>> {
+        Ok(AddressProtocol::Transparent)
+    }
+
+    fn try_from_transparent_p2sh(
+        _: NetworkType,
+        _: [u8; 20],
+    ) -> std::result::Result<Self, ConversionError<Self::Error>> {
+        Ok(AddressProtocol::Transparent)
+    }
+
+    fn try_from_tex(
+        _: NetworkType,
+        _: [u8; 20],
+    ) -> std::result::Result<Self, ConversionError<Self::Error>> {
+        Ok(AddressProtocol::Transparent)
+    }
+}
+
+/// An address controlled by or known to the wallet, joined with its
+/// derivation path and address book entry.
+///
+/// Produced by [`ZcashdWallet::all_addresses`](crate::ZcashdWallet::all_addresses).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AddressEntry {
+    address: String,
+    protocol: AddressProtocol,
+    hd_keypath: Option<String>,
+    name: Option<String>,
+    purpose: Option<String>,
+}
+
+impl AddressEntry {
+    pub fn new(address: String, protocol: AddressProtocol, hd_keypath: Option<String>) -> Self {
+        Self {
+            address,
+            protocol,
+            hd_keypath,
+            name: None,
+            purpose: None,
+        }
+    }
+
+    /// The encoded address string.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn protocol(&self) -> AddressProtocol {
+        self.protocol
+    }
+
+    /// The HD derivation path of the key behind this address, if known.
+    pub fn hd_keypath(&self) -> Option<&str> {
+        self.hd_keypath.as_deref()
+    }
+
+    /// The user-assigned label from the wallet's `name` records.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The address purpose (e.g. `receive`, `send`) from the `purpose` records.
+    pub fn purpose(&self) -> Option<&str> {
+        self.purpose.as_deref()
+    }
+
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    pub fn set_purpose(&mut self, purpose: Option<String>) {
+        self.purpose = purpose;
+    }
+}
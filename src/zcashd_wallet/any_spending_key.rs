@@ -0,0 +1,121 @@
+use crate::{
+    ZcashdWallet,
+    migrate::derive_orchard_spending_key,
+    zcashd_wallet::{
+        Redacted,
+        sapling::SaplingKey,
+        sprout::SproutSpendingKey,
+        transparent::{KeyPair, WalletKey},
+    },
+};
+
+/// A spending key from any of the wallet's pools, as yielded by
+/// [`ZcashdWallet::all_spending_keys`].
+#[derive(Clone)]
+pub enum AnySpendingKey<'a> {
+    /// A transparent key from a `key` record.
+    Transparent(&'a KeyPair),
+    /// A transparent key from a `wkey` record.
+    TransparentWallet(&'a WalletKey),
+    Sapling(&'a SaplingKey),
+    Sprout(&'a SproutSpendingKey),
+    /// The Orchard key of a unified account, derived from the mnemonic seed
+    /// since zcashd does not store it.
+    Orchard {
+        account_id: u32,
+        key: ::orchard::keys::SpendingKey,
+    },
+}
+
+/// Redacts the Orchard spending key, as the other pools' keys redact their
+/// own.
+impl std::fmt::Debug for AnySpendingKey<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transparent(key) => f.debug_tuple("Transparent").field(key).finish(),
+            Self::TransparentWallet(key) => f.debug_tuple("TransparentWallet").field(key).finish(),
+            Self::Sapling(key) => f.debug_tuple("Sapling").field(key).finish(),
+            Self::Sprout(key) => f.debug_tuple("Sprout").field(key).finish(),
+            Self::Orchard { account_id, .. } => f
+                .debug_struct("Orchard")
+                .field("account_id", account_id)
+                .field("key", &Redacted(32))
+                .finish(),
+        }
+    }
+}
+
+impl ZcashdWallet {
+    /// Every spending key in the wallet: transparent, then Sapling, Sprout
+    /// and Orchard.
+    ///
+    /// Orchard keys are derived for each unified account, which runs the BIP
+    /// 39 key stretching once per account. An account whose key cannot be
+    /// derived, for example because the wallet has no mnemonic, is skipped;
+    /// [`derive_orchard_spending_key`] reports why.
+    pub fn all_spending_keys(&self) -> impl Iterator<Item = AnySpendingKey<'_>> {
+        let transparent = self.keys().keypairs().map(AnySpendingKey::Transparent);
+        let transparent_wallet = self
+            .wallet_keys()
+            .into_iter()
+            .flat_map(|keys| keys.keypairs().map(AnySpendingKey::TransparentWallet));
+        let sapling = self.sapling_keys().keypairs().map(AnySpendingKey::Sapling);
+        let sprout = self
+            .sprout_keys()
+            .into_iter()
+            .flat_map(|keys| keys.keypairs().map(AnySpendingKey::Sprout));
+        let orchard = self
+            .unified_accounts()
+            .accounts()
+            .into_iter()
+            .filter_map(|account| {
+                let account_id = account.account_id();
+                derive_orchard_spending_key(self, account_id)
+                    .ok()
+                    .map(|key| AnySpendingKey::Orchard { account_id, key })
+            });
+        transparent
+            .chain(transparent_wallet)
+            .chain(sapling)
+            .chain(sprout)
+            .chain(orchard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnySpendingKey;
+    use crate::test_support::{
+        minimal_wallet_records, mnemonic_record, parse_records, sapling_key_records,
+    };
+
+    #[test]
+    fn test_all_spending_keys() {
+        let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]);
+        let mut records = minimal_wallet_records();
+        records.extend(sapling_key_records(&extsk));
+        // A mnemonic seed with one unified account.
+        records.push(mnemonic_record(false));
+        let mut key = vec![0x01; 32];
+        key.extend_from_slice(&133u32.to_le_bytes());
+        key.extend_from_slice(&0u32.to_le_bytes());
+        key.extend_from_slice(&[0xaa; 32]);
+        records.push(("unifiedaccount", key, 0u32.to_le_bytes().to_vec()));
+        let wallet = parse_records(&records);
+
+        let keys: Vec<_> = wallet.all_spending_keys().collect();
+        let individual = wallet.keys().keypairs().count()
+            + wallet.sapling_keys().keypairs().count()
+            + wallet.sprout_keys().unwrap().keypairs().count()
+            + wallet.unified_accounts().accounts().len();
+        assert_eq!(keys.len(), individual);
+        assert_eq!(keys.len(), 4);
+        assert!(matches!(keys[0], AnySpendingKey::Transparent(_)));
+        assert!(matches!(keys[1], AnySpendingKey::Sapling(_)));
+        assert!(matches!(keys[2], AnySpendingKey::Sprout(_)));
+        assert!(matches!(
+            keys[3],
+            AnySpendingKey::Orchard { account_id: 0, .. }
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+
+use zewif::{BlockHash, TxId};
+use zip32::Scope;
+
+use crate::{
+    Error, Result, ZcashdWallet,
+    migrate::sapling_ivks,
+    zcashd_wallet::{Address, WalletTx},
+};
+
+/// The wallet's confirmed, unspent value in each pool, in zatoshis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Balances {
+    transparent: i64,
+    sapling: i64,
+    orchard: i64,
+}
+
+impl Balances {
+    /// The value of unspent outputs paying the wallet's transparent keys.
+    pub fn transparent(&self) -> i64 {
+        self.transparent
+    }
+
+    /// The value of unspent Sapling notes the wallet's keys decrypt.
+    pub fn sapling(&self) -> i64 {
+        self.sapling
+    }
+
+    /// The value of unspent Orchard notes the wallet's keys decrypt.
+    pub fn orchard(&self) -> i64 {
+        self.orchard
+    }
+
+    /// Always `None`: Sprout notes cannot be decrypted without the Sprout
+    /// note encryption, which this crate does not implement.
+    pub fn sprout(&self) -> Option<i64> {
+        None
+    }
+
+    pub(crate) fn compute(wallet: &ZcashdWallet) -> Result<Self> {
+        let network = wallet.network();
+        let own_addresses: HashSet<Address> = wallet.transparent_addresses().into_iter().collect();
+        let spent_outpoints: HashSet<(TxId, u32)> = wallet
+            .transactions()
+            .values()
+            .filter_map(|tx| tx.transaction().transparent_bundle())
+            .flat_map(|bundle| &bundle.vin)
+            .map(|input| (TxId::from_bytes(*input.prevout.hash()), input.prevout.n()))
+            .collect();
+        let sapling_ivks = sapling_ivks(wallet);
+        // zcashd does not record Orchard nullifiers, so they are derived from
+        // the notes the unified full viewing keys decrypt.
+        let orchard_nullifiers: HashSet<[u8; 32]> = wallet
+            .transactions()
+            .values()
+            .flat_map(WalletTx::orchard_actions)
+            .map(|action| action.nullifier())
+            .collect();
+        let orchard_fvks: Vec<_> = wallet
+            .unified_accounts()
+            .full_viewing_keys
+            .values()
+            .filter_map(|ufvk| ufvk.orchard())
+            .collect();
+
+        let mut transparent = vec![];
+        let mut sapling = vec![];
+        let mut orchard = vec![];
+        let confirmed = wallet
+            .transactions()
+            .iter()
+            .filter(|(_, tx)| tx.hash_block() != BlockHash::from_bytes([0u8; 32]));
+        for (txid, tx) in confirmed {
+            for output in tx.transparent_outputs() {
+                let is_own = output
+                    .recipient_address(network)
+                    .is_some_and(|address| own_addresses.contains(&Address::from(address)));
+                if is_own && !spent_outpoints.contains(&(*txid, output.index())) {
+                    transparent.push(output.value());
+                }
+            }
+
+            for output in tx.sapling_outputs() {
+                if output.note_data().is_none() || tx.is_output_spent(output.index()) == Some(true)
+                {
+                    continue;
+                }
+                if let Some(note) = sapling_ivks.iter().find_map(|ivk| output.decrypt(ivk)) {
+                    sapling.push(note.value());
+                }
+            }
+
+            for action in tx.orchard_actions() {
+                if action.receiving_key().is_none() {
+                    continue;
+                }
+                let viewed = orchard_fvks.iter().find_map(|fvk| {
+                    [Scope::External, Scope::Internal]
+                        .into_iter()
+                        .find_map(|scope| action.decrypt_note_with(&fvk.to_ivk(scope)))
+                        .map(|note| (note, fvk))
+                });
+                match viewed {
+                    Some((note, fvk)) => {
+                        if !orchard_nullifiers.contains(&note.nullifier(fvk).to_bytes()) {
+                            orchard.push(note.value().inner());
+                        }
+                    }
+                    // Without a full viewing key the note's spentness is
+                    // unknown, so it is counted as unspent.
+                    None => {
+                        if let Some(note) = action.decrypt() {
+                            orchard.push(note.value());
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            transparent: sum_values("transparent balance", transparent)?,
+            sapling: sum_values("sapling balance", sapling)?,
+            orchard: sum_values("orchard balance", orchard)?,
+        })
+    }
+}
+
+// Sums zatoshi values, failing rather than wrapping past `i64::MAX`.
+fn sum_values(kind: &'static str, values: impl IntoIterator<Item = u64>) -> Result<i64> {
+    values.into_iter().try_fold(0i64, |total, value| {
+        i64::try_from(value)
+            .ok()
+            .and_then(|value| total.checked_add(value))
+            .ok_or(Error::InvalidAmount { kind, value: total })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        compact, minimal_wallet_records, parse_records, received_note_wallet_tx_value,
+        sapling_key_records, sapling_output_transaction, sapling_spend_transaction,
+        wallet_tx_value,
+    };
+
+    #[test]
+    fn test_balances() {
+        use ripemd::{Digest, Ripemd160};
+        use sha2::Sha256;
+
+        // The P2PKH script of the fixture's key, and one of another wallet.
+        let mut pubkey = vec![0x02];
+        pubkey.extend_from_slice(&[0x11; 32]);
+        let p2pkh = |hash: &[u8]| {
+            let mut script = vec![0x76, 0xa9, 0x14];
+            script.extend_from_slice(hash);
+            script.extend_from_slice(&[0x88, 0xac]);
+            script
+        };
+        let own_script = p2pkh(&Ripemd160::digest(Sha256::digest(&pubkey)));
+        let other_script = p2pkh(&[0x99; 20]);
+        // A v1 transaction spending `inputs` to `outputs`.
+        let transparent_tx = |inputs: &[([u8; 32], u32)], outputs: &[(i64, &[u8])]| {
+            let mut tx = 1u32.to_le_bytes().to_vec();
+            tx.push(inputs.len() as u8);
+            for (hash, n) in inputs {
+                tx.extend_from_slice(hash);
+                tx.extend_from_slice(&n.to_le_bytes());
+                tx.push(0);
+                tx.extend_from_slice(&u32::MAX.to_le_bytes());
+            }
+            tx.push(outputs.len() as u8);
+            for (value, script) in outputs {
+                tx.extend_from_slice(&value.to_le_bytes());
+                tx.extend(compact(script));
+            }
+            tx.extend_from_slice(&0u32.to_le_bytes());
+            tx
+        };
+
+        let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]);
+        let (_, own_address) = extsk.default_address();
+        let mut records = minimal_wallet_records();
+        records.extend(sapling_key_records(&extsk));
+
+        // Two outputs to the wallet and one elsewhere, then a transaction
+        // spending the second.
+        let outputs: [(i64, &[u8]); 3] =
+            [(30_000, &own_script), (20_000, &own_script), (5_000, &other_script)];
+        records.push(("tx", vec![0x10; 32], wallet_tx_value(&transparent_tx(&[], &outputs), None)));
+        let spend = transparent_tx(&[([0x10; 32], 1)], &[(19_000, &other_script)]);
+        records.push(("tx", vec![0x11; 32], wallet_tx_value(&spend, None)));
+        // A payment still in the mempool, which has no block hash.
+        let unconfirmed_tx = transparent_tx(&[], &[(1_000, &own_script)]);
+        let mut unconfirmed = wallet_tx_value(&unconfirmed_tx, None);
+        unconfirmed[unconfirmed_tx.len()..][..32].fill(0);
+        records.push(("tx", vec![0x12; 32], unconfirmed));
+
+        // Two Sapling notes, the first of them spent.
+        for (txid_byte, value, nullifier) in [(0x20, 10_000, [0xaa; 32]), (0x21, 7_000, [0xbb; 32])]
+        {
+            let tx = sapling_output_transaction(&own_address, value, [0; 512]);
+            let value = received_note_wallet_tx_value(&tx, txid_byte, nullifier);
+            records.push(("tx", vec![txid_byte; 32], value));
+        }
+        let mut value = wallet_tx_value(&sapling_spend_transaction([0xaa; 32]), None);
+        value.push(0);
+        records.push(("tx", vec![0x22; 32], value));
+
+        let wallet = parse_records(&records);
+        let balances = wallet.balances().unwrap();
+        assert_eq!(balances.transparent(), 30_000);
+        assert_eq!(balances.sapling(), 7_000);
+        assert_eq!(balances.orchard(), 0);
+        assert_eq!(balances.sprout(), None);
+    }
+}
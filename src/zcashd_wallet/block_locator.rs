@@ -1,4 +1,5 @@
 use crate::Result;
+use zewif::BlockHeight;
 
 use crate::{
     parse,
@@ -6,20 +7,57 @@ use crate::{
     zcashd_wallet::{ClientVersion, u256},
 };
 
-/// Vector of block hashes
-#[derive(Debug, Clone, PartialEq)]
+/// A `CBlockLocator`: the client version that wrote it, followed by block
+/// hashes running back from the wallet's best block, dense near the tip and
+/// exponentially sparser towards genesis.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockLocator {
     version: ClientVersion,
-    blocks: Vec<u256>,
+    hashes: Vec<u256>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    height: Option<u32>,
 }
 
 impl BlockLocator {
+    /// A locator with no block hashes, as zcashd 6.0.0 and later write to
+    /// `bestblock`.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
     pub fn version(&self) -> ClientVersion {
         self.version
     }
 
-    pub fn blocks(&self) -> &[u256] {
-        &self.blocks
+    /// All block hashes in the locator, tip first.
+    pub fn hashes(&self) -> &[u256] {
+        &self.hashes
+    }
+
+    /// The hash of the last block the wallet synced to, or `None` for the
+    /// empty locator zcashd 6.0.0 and later write to `bestblock`.
+    pub fn tip(&self) -> Option<&u256> {
+        self.hashes.first()
+    }
+
+    /// The height of the [`tip`](Self::tip), if known.
+    ///
+    /// A locator holds only hashes, so the height comes from the wallet's
+    /// note commitment data, which zcashd writes in the same batch as the
+    /// best block. Wallets without Orchard checkpoints or Sapling or Sprout
+    /// witnesses leave it `None`, as does the empty locator.
+    pub fn height(&self) -> Option<BlockHeight> {
+        self.height.map(BlockHeight::from)
+    }
+
+    pub(crate) fn with_height(mut self, height: Option<u32>) -> Self {
+        self.height = height.filter(|_| !self.is_empty());
+        self
     }
 }
 
@@ -27,7 +65,31 @@ impl Parse for BlockLocator {
     fn parse(p: &mut Parser) -> Result<Self> {
         Ok(Self {
             version: parse!(p, "version")?,
-            blocks: parse!(p, "blocks")?,
+            hashes: parse!(p, "hashes")?,
+            height: None,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::BlockLocator;
+    use crate::{parse, parser::prelude::*, zcashd_wallet::u256};
+
+    #[test]
+    fn test_parse_multi_hash_locator() {
+        let mut data = 6_020_050u32.to_le_bytes().to_vec();
+        data.push(3);
+        for byte in [0x03, 0x02, 0x01] {
+            data.extend_from_slice(&[byte; 32]);
+        }
+
+        let mut p = Parser::new(&data);
+        let locator = parse!(&mut p, BlockLocator, "block locator").unwrap();
+        p.check_finished().unwrap();
+
+        assert_eq!(locator.version().major(), 6);
+        assert_eq!(locator.hashes().len(), 3);
+        assert_eq!(locator.tip(), Some(&u256::try_from(&[0x03; 32]).unwrap()));
+    }
+}
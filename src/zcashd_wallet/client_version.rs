@@ -2,7 +2,8 @@ use crate::Result;
 
 use crate::{parse, parser::prelude::*};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientVersion {
     version: u32,
     major: u32,
@@ -20,7 +20,8 @@ use crate::{Error, parse, parser::prelude::*, Result};
 /// # Errors
 /// Returns an error if:
 /// - There are insufficient bytes in the parser input
-/// - The encoding doesn't follow the compact size rules (e.g., using 0xfd for a value < 253)
+/// - The parser is strict and the encoding isn't canonical (e.g., using 0xfd for a
+///   value < 253)
 ///
 /// # Examples
 /// ```no_run
@@ -42,39 +43,18 @@ use crate::{Error, parse, parser::prelude::*, Result};
 /// # fn main() {}
 /// ```
 pub fn parse_compact_size(p: &mut Parser) -> Result<usize> {
-    match parse!(p, u8, "compact size")? {
-        0xfd => {
-            let n = parse!(p, u16, "compact size")?;
-            if n < 253 {
-                return Err(Error::InvalidCompactSize {
-                    prefix: 0xfd,
-                    value: n as u64,
-                });
-            }
-            Ok(n as usize)
-        }
-        0xfe => {
-            let n = parse!(p, u32, "compact size")?;
-            if n < 0x10000 {
-                return Err(Error::InvalidCompactSize {
-                    prefix: 0xfe,
-                    value: n as u64,
-                });
-            }
-            Ok(n as usize)
-        }
-        0xff => {
-            let n = parse!(p, u64, "compact size")?;
-            if n < 0x100000000 {
-                return Err(Error::InvalidCompactSize {
-                    prefix: 0xff,
-                    value: n,
-                });
-            }
-            Ok(n as usize)
-        }
-        size => Ok(size as usize),
+    let (prefix, value, min) = match parse!(p, u8, "compact size")? {
+        0xfd => (0xfd, parse!(p, u16, "compact size")? as u64, 253),
+        0xfe => (0xfe, parse!(p, u32, "compact size")? as u64, 0x10000),
+        0xff => (0xff, parse!(p, u64, "compact size")?, 0x100000000),
+        size => return Ok(size as usize),
+    };
+    // A value that would fit a shorter form is non-canonical. zcashd never
+    // writes one, so outside strict parsing it is only a curiosity.
+    if p.strict && value < min {
+        return Err(Error::InvalidCompactSize { prefix, value });
     }
+    Ok(value as usize)
 }
 
 /// A Bitcoin/Zcash-style variable-length integer used for size encoding in binary formats.
@@ -146,3 +126,50 @@ impl std::ops::Deref for CompactSize {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `prefix` followed by `value` in `width` little-endian bytes.
+    fn encode(prefix: u8, value: u64, width: usize) -> Vec<u8> {
+        let mut bytes = vec![prefix];
+        bytes.extend_from_slice(&value.to_le_bytes()[..width]);
+        bytes
+    }
+
+    #[test]
+    fn test_canonical_compact_sizes() {
+        for (bytes, value) in [
+            (vec![0xfc], 0xfc),
+            (encode(0xfd, 0xfd, 2), 0xfd),
+            (encode(0xfe, 0x10000, 4), 0x10000),
+            (encode(0xff, 0x100000000, 8), 0x100000000),
+        ] {
+            let mut p = Parser::new(&bytes);
+            assert_eq!(parse_compact_size(&mut p).unwrap(), value);
+            p.check_finished().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_non_canonical_compact_sizes() {
+        for (prefix, value, width) in [(0xfd, 0xfc, 2), (0xfe, 0xffff, 4), (0xff, 0xffff_ffff, 8)] {
+            let bytes = encode(prefix, value, width);
+            match parse_compact_size(&mut Parser::new(&bytes)) {
+                Err(Error::InvalidCompactSize {
+                    prefix: p,
+                    value: v,
+                }) => {
+                    assert_eq!((p, v), (prefix, value))
+                }
+                other => panic!("expected InvalidCompactSize, got {other:?}"),
+            }
+
+            // Outside strict parsing the value is read as encoded.
+            let mut p = Parser::new(&bytes);
+            p.set_strict(false);
+            assert_eq!(parse_compact_size(&mut p).unwrap(), value as usize);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::{fmt, str::FromStr};
+
+use crate::{Error, Result};
+
+const HARDENED_BIT: u32 = 1 << 31;
+
+/// One step of a BIP 32 derivation path: a child index, derived either
+/// normally or hardened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChildNumber {
+    Normal(u32),
+    Hardened(u32),
+}
+
+impl ChildNumber {
+    /// The child index, without the hardened bit.
+    pub fn index(&self) -> u32 {
+        match self {
+            Self::Normal(index) | Self::Hardened(index) => *index,
+        }
+    }
+
+    pub fn is_hardened(&self) -> bool {
+        matches!(self, Self::Hardened(_))
+    }
+}
+
+impl fmt::Display for ChildNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Normal(index) => write!(f, "{index}"),
+            Self::Hardened(index) => write!(f, "{index}'"),
+        }
+    }
+}
+
+/// An HD derivation path as zcashd records it in key metadata, such as
+/// `m/44'/133'/0'/0/5`, where `'` marks a hardened step.
+///
+/// # Examples
+/// ```
+/// # use zewif_zcashd::zcashd_wallet::{ChildNumber, DerivationPath};
+/// let path: DerivationPath = "m/44'/133'/0'/0/5".parse().unwrap();
+/// assert_eq!(path.components()[1], ChildNumber::Hardened(133));
+/// assert_eq!(path.components()[4], ChildNumber::Normal(5));
+/// assert_eq!(path.to_string(), "m/44'/133'/0'/0/5");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Vec<ChildNumber>);
+
+impl DerivationPath {
+    pub fn new(components: Vec<ChildNumber>) -> Self {
+        Self(components)
+    }
+
+    /// The steps from the master key, in derivation order.
+    pub fn components(&self) -> &[ChildNumber] {
+        &self.0
+    }
+}
+
+/// Parses a path of the form `m/<index>[']/...`. Indexes must be below 2^31,
+/// as the top bit is the hardened flag.
+impl FromStr for DerivationPath {
+    type Err = Error;
+
+    fn from_str(path: &str) -> Result<Self> {
+        let invalid = || Error::InvalidDerivationPath {
+            path: path.to_string(),
+        };
+        let mut steps = path.split('/');
+        if steps.next() != Some("m") {
+            return Err(invalid());
+        }
+        steps
+            .map(|step| {
+                let (index, hardened) = match step.strip_suffix('\'') {
+                    Some(index) => (index, true),
+                    None => (step, false),
+                };
+                // `u32::from_str` accepts a leading `+`, which no keypath has.
+                if !index.bytes().all(|byte| byte.is_ascii_digit()) {
+                    return Err(invalid());
+                }
+                let index = index
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|index| index & HARDENED_BIT == 0)
+                    .ok_or_else(invalid)?;
+                Ok(if hardened {
+                    ChildNumber::Hardened(index)
+                } else {
+                    ChildNumber::Normal(index)
+                })
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for component in &self.0 {
+            write!(f, "/{component}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChildNumber, DerivationPath};
+    use crate::Error;
+
+    #[test]
+    fn test_parse_derivation_path() {
+        let hardened: DerivationPath = "m/32'/133'/2147483647'/0'".parse().unwrap();
+        assert!(hardened.components().iter().all(ChildNumber::is_hardened));
+        assert_eq!(hardened.components()[2].index(), 0x7fff_ffff);
+
+        let mixed: DerivationPath = "m/44'/1'/0'/1/7".parse().unwrap();
+        assert_eq!(
+            mixed.components(),
+            [
+                ChildNumber::Hardened(44),
+                ChildNumber::Hardened(1),
+                ChildNumber::Hardened(0),
+                ChildNumber::Normal(1),
+                ChildNumber::Normal(7),
+            ]
+        );
+        assert_eq!(mixed.to_string(), "m/44'/1'/0'/1/7");
+
+        let master: DerivationPath = "m".parse().unwrap();
+        assert!(master.components().is_empty());
+    }
+
+    #[test]
+    fn test_parse_derivation_path_rejects_malformed_input() {
+        for path in [
+            "",
+            "44'/133'",
+            "M/44'",
+            "m/",
+            "m//0",
+            "m/44''",
+            "m/+1",
+            "m/-1",
+            "m/0x10",
+            "m/2147483648",
+            "m/2147483648'",
+            "m/44h",
+        ] {
+            assert!(
+                matches!(
+                    path.parse::<DerivationPath>(),
+                    Err(Error::InvalidDerivationPath { .. })
+                ),
+                "{path}"
+            );
+        }
+    }
+}
@@ -43,6 +43,7 @@ use crate::{parse, parser::prelude::*, zcashd_wallet::u256};
 /// // The tree can be used to generate witnesses for spending notes
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IncrementalMerkleTree {
     /// The left child at the current insertion point (None if empty)
     left: Option<u256>,
@@ -162,6 +163,46 @@ impl IncrementalMerkleTree {
     pub fn push_parent(&mut self, parent: Option<u256>) {
         self.parents.push(parent);
     }
+
+    /// Returns the number of leaves appended to the tree.
+    ///
+    /// Each parent at index `i` stands for a complete subtree of `2^(i + 1)`
+    /// leaves to the left of the current insertion point.
+    pub fn size(&self) -> u64 {
+        let leaves = self.left.is_some() as u64 + self.right.is_some() as u64;
+        self.parents
+            .iter()
+            .enumerate()
+            .filter(|(_, parent)| parent.is_some())
+            .fold(leaves, |size, (i, _)| size + (1 << (i + 1)))
+    }
+
+    /// Returns the depth of the `skip`-th empty position after the last
+    /// leaf, as zcashd's `IncrementalMerkleTree::next_depth` does.
+    ///
+    /// A witness made against this tree fills those positions in order, so
+    /// this gives the depth of each hash in its `filled` list and of its
+    /// cursor.
+    pub fn next_depth(&self, mut skip: usize) -> usize {
+        let slots = [self.left.is_none(), self.right.is_none()]
+            .into_iter()
+            .map(|empty| (empty, 0))
+            .chain(
+                self.parents
+                    .iter()
+                    .enumerate()
+                    .map(|(i, parent)| (parent.is_none(), i + 1)),
+            );
+        for (empty, depth) in slots {
+            if empty {
+                if skip == 0 {
+                    return depth;
+                }
+                skip -= 1;
+            }
+        }
+        self.parents.len() + 1 + skip
+    }
 }
 
 /// Default implementation creates an empty incremental Merkle tree
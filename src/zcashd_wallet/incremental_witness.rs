@@ -53,11 +53,17 @@ use crate::{parse, parser::prelude::*};
 /// // In a real implementation, this witness would be updated as new notes are added to the tree
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound = "Hash: crate::serde_support::JsonBytes")
+)]
 pub struct IncrementalWitness<const DEPTH: usize, Hash> {
     /// The Merkle tree as it was when the witness was created
     tree: IncrementalMerkleTree,
 
     /// Hashes filled in since the witness was created
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::json_bytes_seq"))]
     filled: Vec<Hash>,
 
     /// Optional cursor for tracking the witness position
@@ -0,0 +1,158 @@
+use zewif::{Script, sapling::SaplingIncomingViewingKey};
+
+use crate::{
+    ZcashdWallet,
+    zcashd_wallet::{Address, sprout::SproutKeys, wallet_tx_outputs::script_address},
+};
+
+const OP_CHECKSIG: u8 = 0xac;
+
+/// Ownership checks in the manner of zcashd's `IsMine`: whether the wallet
+/// holds a spending key, a viewing key or a watch-only script for an address,
+/// script or incoming viewing key.
+impl ZcashdWallet {
+    /// Whether `address` belongs to the wallet: the P2PKH address of one of
+    /// its transparent keys or watch-only scripts, or one of its Sprout,
+    /// Sapling or unified addresses.
+    ///
+    /// Transparent addresses are looked up in the
+    /// [`transparent_address_index`](ZcashdWallet::transparent_address_index);
+    /// shielded addresses are compared in their encoding.
+    pub fn is_mine_address(&self, address: &Address) -> bool {
+        if self.is_transparent_address_mine(address) {
+            return true;
+        }
+        let network = self.network();
+        let encoded = address.to_string();
+        self.watch_only_scripts
+            .iter()
+            .filter_map(|script| script_address(script.as_ref(), network))
+            .chain(
+                self.sapling_z_addresses
+                    .keys()
+                    .map(|address| address.to_string(network)),
+            )
+            .chain(
+                self.sprout_keys
+                    .iter()
+                    .flat_map(SproutKeys::iter)
+                    .map(|(address, _)| address.to_string(network)),
+            )
+            .chain(
+                self.unified_accounts
+                    .address_metadata
+                    .iter()
+                    .filter_map(|metadata| {
+                        self.unified_accounts
+                            .encode_address(metadata, &self.network_info)
+                            .ok()
+                            .flatten()
+                    }),
+            )
+            .any(|mine| mine == encoded)
+    }
+
+    /// Whether `script` pays the wallet: it is a watch-only script, or a
+    /// P2PKH or P2PK script for one of the wallet's transparent keys.
+    ///
+    /// P2SH scripts are only recognized as watch-only scripts, since the
+    /// wallet's redeem scripts (its `cscript` records) are not parsed.
+    pub fn is_mine_script(&self, script: &Script) -> bool {
+        if self.watch_only_scripts.contains(script) {
+            return true;
+        }
+        let bytes: &[u8] = script.as_ref();
+        match bytes {
+            [0x76, ..] => script_address(bytes, self.network())
+                .is_some_and(|address| self.is_transparent_address_mine(&Address::from(address))),
+            // `<pubkey> OP_CHECKSIG`, with a compressed or uncompressed key.
+            [len @ (33 | 65), pubkey @ .., OP_CHECKSIG] if pubkey.len() == usize::from(*len) => {
+                self.transparent_address_index()
+                    .values()
+                    .any(|key| key.as_slice() == pubkey)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether `ivk` is the incoming viewing key of one of the wallet's
+    /// Sapling spending keys, of a Sapling viewing key it holds, or of one of
+    /// its Sapling addresses.
+    pub fn is_mine_ivk(&self, ivk: &SaplingIncomingViewingKey) -> bool {
+        self.sapling_keys.get(ivk).is_some()
+            || self.sapling_z_addresses.values().any(|mine| mine == ivk)
+            || self
+                .viewing_keys
+                .iter()
+                .chain(self.sapling_extended_fvks.values())
+                .any(|extfvk| {
+                    let mine = extfvk.to_diversifiable_full_viewing_key().fvk().vk.ivk();
+                    SaplingIncomingViewingKey::new(mine.to_repr()) == *ivk
+                })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zewif::{Data, Network, Script, sapling::SaplingIncomingViewingKey};
+
+    use crate::{
+        ZcashdWallet,
+        zcashd_wallet::{
+            Address, KeyMetadata,
+            sapling::{SaplingKey, SaplingKeys},
+            transparent::{KeyPair, Keys},
+            u160,
+            wallet_tx_outputs::script_address,
+        },
+    };
+
+    fn p2pkh(hash: &[u8]) -> Script {
+        let script = [&[0x76, 0xa9, 0x14], hash, &[0x88, 0xac]].concat();
+        Script::from(Data::from_slice(&script))
+    }
+
+    fn sapling_key(seed: u8) -> SaplingKey {
+        let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[seed; 32]);
+        SaplingKey::from_spending_key(extsk, KeyMetadata::new(None, None, None))
+    }
+
+    #[test]
+    fn test_is_mine() {
+        let keypair =
+            KeyPair::from_secret(&[0x01; 32], true, KeyMetadata::new(None, None, None)).unwrap();
+        let pubkey = keypair.pubkey().clone();
+        let own_hash = u160::from(pubkey.key_id());
+        let watched = p2pkh(&[0x22; 20]);
+        let foreign = p2pkh(&[0x33; 20]);
+        let spending_key = sapling_key(0x07);
+        let viewed_key = sapling_key(0x08);
+        #[allow(deprecated)]
+        let viewing_key = viewed_key.extsk().to_extended_full_viewing_key();
+        let sapling_keys = [(*spending_key.ivk(), spending_key.clone())].into();
+        let wallet = ZcashdWallet::builder()
+            .keys(Keys::new([(pubkey.clone(), keypair)].into()))
+            .watch_only_scripts([watched.clone()].into())
+            .sapling_keys(SaplingKeys::new(sapling_keys))
+            .viewing_keys(vec![viewing_key])
+            .build();
+        let address = |script: &Script| {
+            Address::from(script_address(script.as_ref(), Network::Main).unwrap())
+        };
+
+        assert!(wallet.is_mine_script(&p2pkh(own_hash.as_ref())));
+        let p2pk = [&[0x21], pubkey.as_slice(), &[0xac]].concat();
+        assert!(wallet.is_mine_script(&Script::from(Data::from_slice(&p2pk))));
+        assert!(wallet.is_mine_script(&watched));
+        assert!(!wallet.is_mine_script(&foreign));
+
+        assert!(wallet.is_mine_address(&address(&p2pkh(own_hash.as_ref()))));
+        assert!(wallet.is_mine_address(&address(&watched)));
+        assert!(!wallet.is_mine_address(&address(&foreign)));
+
+        assert!(wallet.is_mine_ivk(spending_key.ivk()));
+        assert!(wallet.is_mine_ivk(viewed_key.ivk()));
+        assert!(!wallet.is_mine_ivk(sapling_key(0x09).ivk()));
+        assert!(!wallet.is_mine_ivk(&SaplingIncomingViewingKey::new([0; 32])));
+    }
+}
@@ -1,31 +1,76 @@
 use crate::Result;
 
-use zewif::{Blob32, NoQuotesDebugOption};
+use zewif::{Blob32, Data, NoQuotesDebugOption};
 
-use crate::{parse, parser::prelude::*, zcashd_wallet::SecondsSinceEpoch};
+use crate::{
+    parse,
+    parser::prelude::*,
+    zcashd_wallet::{DerivationPath, SecondsSinceEpoch},
+};
 
 const VERSION_WITH_HDDATA: i32 = 10;
 
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeyMetadata {
     version: i32,
     create_time: Option<SecondsSinceEpoch>,
     hd_keypath: Option<String>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::opt_hex"))]
     seed_fp: Option<Blob32>,
+    /// The bytes a metadata version newer than this crate knows appends after
+    /// the fields it shares with the current version.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "Data::new", with = "crate::serde_support::hex")
+    )]
+    extra: Data,
 }
 
 impl std::fmt::Debug for KeyMetadata {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("KeyMetadata")
+        let mut debug = f.debug_struct("KeyMetadata");
+        debug
             .field("version", &self.version)
             .field("create_time", &NoQuotesDebugOption(&self.create_time))
             .field("hd_keypath", &NoQuotesDebugOption(&self.hd_keypath))
-            .field("seed_fp", &NoQuotesDebugOption(&self.seed_fp))
-            .finish()
+            .field("seed_fp", &NoQuotesDebugOption(&self.seed_fp));
+        if !self.extra.is_empty() {
+            debug.field("extra", &self.extra);
+        }
+        debug.finish()
     }
 }
 
 impl KeyMetadata {
+    /// The newest `CKeyMetadata` version zcashd writes.
+    pub const CURRENT_VERSION: i32 = VERSION_WITH_HDDATA;
+
+    pub fn new(
+        create_time: Option<SecondsSinceEpoch>,
+        hd_keypath: Option<String>,
+        seed_fp: Option<Blob32>,
+    ) -> Self {
+        let version = if hd_keypath.is_some() || seed_fp.is_some() {
+            VERSION_WITH_HDDATA
+        } else {
+            1
+        };
+        Self {
+            version,
+            create_time,
+            hd_keypath,
+            seed_fp,
+            extra: Data::new(),
+        }
+    }
+
+    /// The metadata zcashd records for a key derived at `hd_keypath` from the
+    /// seed with fingerprint `seed_fp`, with the creation time unknown.
+    pub fn with_hd_keypath(hd_keypath: String, seed_fp: Blob32) -> Self {
+        Self::new(None, Some(hd_keypath), Some(seed_fp))
+    }
+
     pub fn version(&self) -> i32 {
         self.version
     }
@@ -38,9 +83,26 @@ impl KeyMetadata {
         self.hd_keypath.as_ref()
     }
 
+    /// The HD keypath parsed into its steps, or `None` if the key was not
+    /// derived from a seed or its keypath is malformed.
+    pub fn derivation_path(&self) -> Option<DerivationPath> {
+        self.hd_keypath.as_deref()?.parse().ok()
+    }
+
     pub fn seed_fp(&self) -> Option<&Blob32> {
         self.seed_fp.as_ref()
     }
+
+    /// Whether the record was written by a zcashd newer than this crate
+    /// knows, in which case its unrecognized fields are kept in
+    /// [`extra`](Self::extra).
+    pub fn is_future_version(&self) -> bool {
+        self.version > Self::CURRENT_VERSION
+    }
+
+    pub fn extra(&self) -> &Data {
+        &self.extra
+    }
 }
 
 impl Parse for KeyMetadata {
@@ -62,11 +124,19 @@ impl Parse for KeyMetadata {
             hd_keypath = None;
             seed_fp = None;
         }
+        // Newer versions are assumed to extend the current layout, as version
+        // 10 extended version 1; what follows is kept rather than rejected.
+        let extra = if version > Self::CURRENT_VERSION {
+            p.rest()
+        } else {
+            Data::new()
+        };
         Ok(Self {
             version,
             create_time,
             hd_keypath: hd_keypath.filter(|p| !p.trim().is_empty()),
             seed_fp: seed_fp.filter(|fp| fp.as_bytes() != &[0u8; 32]),
+            extra,
         })
     }
 }
@@ -0,0 +1,33 @@
+use zewif::Data;
+
+use crate::{Result, parse, parser::prelude::*, zcashd_wallet::ClientVersion};
+
+/// An account of the accounting system zcashd inherited from Bitcoin Core and
+/// removed in 4.5.0, from an `acc` record. The account's name is the record's
+/// key.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LegacyAccount {
+    version: ClientVersion,
+    pubkey: Data,
+}
+
+impl LegacyAccount {
+    pub fn version(&self) -> ClientVersion {
+        self.version
+    }
+
+    /// The account's receiving key, empty if none was ever assigned.
+    pub fn pubkey(&self) -> &Data {
+        &self.pubkey
+    }
+}
+
+impl Parse for LegacyAccount {
+    fn parse(p: &mut Parser) -> Result<Self> {
+        Ok(Self {
+            version: parse!(p, "version")?,
+            pubkey: parse!(p, "pubkey")?,
+        })
+    }
+}
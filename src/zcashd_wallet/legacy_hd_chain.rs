@@ -0,0 +1,54 @@
+use crate::Result;
+
+use zewif::Blob32;
+
+use crate::{parse, parser::prelude::*, zcashd_wallet::SecondsSinceEpoch};
+
+/// The pre-mnemonic HD chain state stored under the `hdchain` keyname.
+///
+/// Per zcashd's `walletdb.h` (`CHDChain`), this records the fingerprint of the
+/// legacy HD seed and the derivation counter used when allocating new Sapling
+/// keys from it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LegacyHDChain {
+    version: i32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::hex"))]
+    seed_fp: Blob32,
+    create_time: Option<SecondsSinceEpoch>,
+    sapling_account_counter: u32,
+}
+
+impl LegacyHDChain {
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    pub fn seed_fp(&self) -> &Blob32 {
+        &self.seed_fp
+    }
+
+    pub fn create_time(&self) -> Option<SecondsSinceEpoch> {
+        self.create_time
+    }
+
+    pub fn sapling_account_counter(&self) -> u32 {
+        self.sapling_account_counter
+    }
+}
+
+impl Parse for LegacyHDChain {
+    fn parse(p: &mut Parser) -> Result<Self> {
+        let version = parse!(p, "version")?;
+        let seed_fp = parse!(p, "seed_fp")?;
+        let create_time: SecondsSinceEpoch = parse!(p, "create_time")?;
+        let sapling_account_counter = parse!(p, "sapling_account_counter")?;
+        Ok(Self {
+            version,
+            seed_fp,
+            // 0 means unknown (per `walletdb.h`)
+            create_time: (!create_time.is_zero()).then_some(create_time),
+            sapling_account_counter,
+        })
+    }
+}
@@ -0,0 +1,405 @@
+use std::{collections::HashMap, fmt::Debug, hash::Hash};
+
+use crate::{Error, Result, ZcashdWallet};
+
+impl ZcashdWallet {
+    /// Merges `other` into this wallet, so that several zcashd wallets can be
+    /// imported as one.
+    ///
+    /// Keys, transactions and address metadata are unioned. An entry present
+    /// in both wallets is kept once if the two agree, and is an
+    /// [`Error::DuplicateRecord`] if they differ. `other`'s key pool entries
+    /// are renumbered to follow this wallet's, since each wallet numbers its
+    /// pool from one. This wallet's best block, versions and default key are
+    /// kept.
+    ///
+    /// A wallet has at most one legacy HD seed and one mnemonic seed, so only
+    /// wallets whose seeds agree can be merged: if both wallets have a seed
+    /// (or HD chain) of the same kind and the two differ, the merge is an
+    /// [`Error::UnmergeableWallets`]. Wallets created by different zcashd
+    /// installations generally have different seeds, and must be migrated
+    /// separately. Unified accounts and Orchard note commitment trees are
+    /// per-wallet state with no meaningful union, so merging two wallets that
+    /// both have them is also an [`Error::UnmergeableWallets`], as is merging
+    /// wallets on different networks.
+    ///
+    /// Every conflict is checked for before anything is merged, so on error
+    /// this wallet is left unchanged.
+    pub fn merge(&mut self, other: ZcashdWallet) -> Result<()> {
+        self.check_merge(&other)?;
+
+        merge_singleton(&mut self.legacy_hd_seed, other.legacy_hd_seed);
+        merge_singleton(&mut self.bip39_mnemonic, other.bip39_mnemonic);
+        merge_singleton(&mut self.legacy_hd_chain, other.legacy_hd_chain);
+        merge_singleton(&mut self.mnemonic_hd_chain, other.mnemonic_hd_chain);
+
+        self.address_names.merge(other.address_names);
+        self.address_purposes.merge(other.address_purposes);
+        for (address, attributes) in other.dest_data {
+            merge_map(self.dest_data.entry(address).or_default(), attributes);
+        }
+
+        merge_map(&mut self.legacy_accounts, other.legacy_accounts);
+        for (account, entries) in other.accounting_entries {
+            let into = self.accounting_entries.entry(account).or_default();
+            for (number, entry) in entries {
+                into.entry(number).or_insert(entry);
+            }
+        }
+
+        // The transparent keys change, so the address index is rebuilt.
+        self.transparent_address_index.take();
+        self.keys.merge(other.keys);
+        merge_optional(&mut self.wallet_keys, other.wallet_keys, |a, b| a.merge(b));
+        self.sapling_keys.merge(other.sapling_keys);
+        merge_optional(&mut self.sprout_keys, other.sprout_keys, |a, b| a.merge(b));
+        merge_map(&mut self.sapling_extended_fvks, other.sapling_extended_fvks);
+        merge_map(&mut self.sapling_z_addresses, other.sapling_z_addresses);
+        for viewing_key in other.viewing_keys {
+            if !self.viewing_keys.contains(&viewing_key) {
+                self.viewing_keys.push(viewing_key);
+            }
+        }
+        self.watch_only_scripts.extend(other.watch_only_scripts);
+
+        let mut next_index = self.key_pool.keys().max().map_or(1, |index| index + 1);
+        let mut pool: Vec<_> = other.key_pool.into_iter().collect();
+        pool.sort_by_key(|(index, _)| *index);
+        for (_, entry) in pool {
+            if !self.key_pool.values().any(|existing| *existing == entry) {
+                self.key_pool.insert(next_index, entry);
+                next_index += 1;
+            }
+        }
+
+        merge_map(&mut self.transactions, other.transactions);
+        merge_map(&mut self.send_recipients, other.send_recipients);
+        self.orderposnext = self.orderposnext.max(other.orderposnext);
+
+        if self.unified_accounts.is_empty() {
+            self.unified_accounts = other.unified_accounts;
+        }
+        if self.orchard_note_commitment_tree.is_empty() {
+            self.orchard_note_commitment_tree = other.orchard_note_commitment_tree;
+        }
+        Ok(())
+    }
+
+    /// Returns the error [`merge`](Self::merge) would fail with, without
+    /// changing either wallet.
+    fn check_merge(&self, other: &ZcashdWallet) -> Result<()> {
+        if self.network() != other.network() {
+            return Err(Error::UnmergeableWallets {
+                reason: "the wallets are on different networks",
+            });
+        }
+        if !self.unified_accounts.is_empty() && !other.unified_accounts.is_empty() {
+            return Err(Error::UnmergeableWallets {
+                reason: "both wallets have unified accounts",
+            });
+        }
+        if !self.orchard_note_commitment_tree.is_empty()
+            && !other.orchard_note_commitment_tree.is_empty()
+        {
+            return Err(Error::UnmergeableWallets {
+                reason: "both wallets have an Orchard note commitment tree",
+            });
+        }
+        check_singleton(
+            "the wallets have different legacy HD seeds",
+            &self.legacy_hd_seed,
+            &other.legacy_hd_seed,
+            |a, b| a.data() == b.data(),
+        )?;
+        check_singleton(
+            "the wallets have different mnemonic seeds",
+            &self.bip39_mnemonic,
+            &other.bip39_mnemonic,
+            |a, b| a.mnemonic() == b.mnemonic(),
+        )?;
+        check_singleton(
+            "the wallets have different legacy HD chains",
+            &self.legacy_hd_chain,
+            &other.legacy_hd_chain,
+            PartialEq::eq,
+        )?;
+        check_singleton(
+            "the wallets have different mnemonic HD chains",
+            &self.mnemonic_hd_chain,
+            &other.mnemonic_hd_chain,
+            PartialEq::eq,
+        )?;
+
+        self.address_names.check_merge(&other.address_names)?;
+        self.address_purposes.check_merge(&other.address_purposes)?;
+        for (address, attributes) in &other.dest_data {
+            if let Some(into) = self.dest_data.get(address) {
+                check_entries("destination data", into, attributes, |attribute| {
+                    format!("{address}/{attribute}")
+                })?;
+            }
+        }
+
+        check_map(
+            "legacy account",
+            &self.legacy_accounts,
+            &other.legacy_accounts,
+        )?;
+        for (account, entries) in &other.accounting_entries {
+            let Some(into) = self.accounting_entries.get(account) else {
+                continue;
+            };
+            for (number, entry) in entries {
+                if into.get(number).is_some_and(|existing| existing != entry) {
+                    return Err(Error::DuplicateRecord {
+                        kind: "accounting entry",
+                        key: format!("{account}/{number}"),
+                    });
+                }
+            }
+        }
+
+        self.keys.check_merge(&other.keys)?;
+        if let (Some(a), Some(b)) = (&self.wallet_keys, &other.wallet_keys) {
+            a.check_merge(b)?;
+        }
+        self.sapling_keys.check_merge(&other.sapling_keys)?;
+        if let (Some(a), Some(b)) = (&self.sprout_keys, &other.sprout_keys) {
+            a.check_merge(b)?;
+        }
+        check_map(
+            "sapling extended full viewing key",
+            &self.sapling_extended_fvks,
+            &other.sapling_extended_fvks,
+        )?;
+        check_map(
+            "sapling payment address",
+            &self.sapling_z_addresses,
+            &other.sapling_z_addresses,
+        )?;
+
+        check_map("transaction", &self.transactions, &other.transactions)?;
+        check_map(
+            "send recipients",
+            &self.send_recipients,
+            &other.send_recipients,
+        )
+    }
+}
+
+/// Fails with [`Error::DuplicateRecord`] on a key the two maps give
+/// different values.
+pub(crate) fn check_map<K, V>(
+    kind: &'static str,
+    into: &HashMap<K, V>,
+    from: &HashMap<K, V>,
+) -> Result<()>
+where
+    K: Eq + Hash + Debug,
+    V: PartialEq,
+{
+    check_entries(kind, into, from, |key| format!("{key:?}"))
+}
+
+/// Moves the entries of `from` into `into`, keeping `into`'s value for keys
+/// both maps have; [`check_map`] says whether those values agree.
+pub(crate) fn merge_map<K: Eq + Hash, V>(into: &mut HashMap<K, V>, from: HashMap<K, V>) {
+    for (key, value) in from {
+        into.entry(key).or_insert(value);
+    }
+}
+
+fn check_entries<K, V: PartialEq>(
+    kind: &'static str,
+    into: &HashMap<K, V>,
+    from: &HashMap<K, V>,
+    describe: impl FnOnce(&K) -> String,
+) -> Result<()>
+where
+    K: Eq + Hash,
+{
+    let conflict = from
+        .iter()
+        .find(|(key, value)| into.get(*key).is_some_and(|existing| existing != *value));
+    match conflict {
+        Some((key, _)) => Err(Error::DuplicateRecord {
+            kind,
+            key: describe(key),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn merge_optional<T>(into: &mut Option<T>, from: Option<T>, merge: impl FnOnce(&mut T, T)) {
+    let Some(from) = from else {
+        return;
+    };
+    match into {
+        Some(into) => merge(into, from),
+        None => *into = Some(from),
+    }
+}
+
+/// Takes `from` if `into` is empty; [`check_singleton`] says whether the two
+/// agree otherwise.
+fn merge_singleton<T>(into: &mut Option<T>, from: Option<T>) {
+    if into.is_none() {
+        *into = from;
+    }
+}
+
+fn check_singleton<T>(
+    reason: &'static str,
+    into: &Option<T>,
+    from: &Option<T>,
+    same: impl Fn(&T, &T) -> bool,
+) -> Result<()> {
+    match (into, from) {
+        (Some(into), Some(from)) if !same(into, from) => Err(Error::UnmergeableWallets { reason }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Error,
+        test_support::{
+            MNEMONIC, compact, minimal_wallet_records, mnemonic_record, named_address,
+            parse_records, phrase_record, privkey_value, txid, v1_transaction, wallet_tx_value,
+        },
+    };
+
+    #[test]
+    fn test_merge_wallets() {
+        // A second wallet sharing the minimal wallet's address book and chain
+        // state, with a key, key pool entry and transaction of its own.
+        let mut pubkey = vec![0x03];
+        pubkey.extend_from_slice(&[0x12; 32]);
+        let mut keymeta = 1i32.to_le_bytes().to_vec();
+        keymeta.extend_from_slice(&0u64.to_le_bytes());
+        let mut pool_entry = 5_000_150u32.to_le_bytes().to_vec();
+        pool_entry.extend_from_slice(&1_600_000_000u64.to_le_bytes());
+        pool_entry.extend(compact(&pubkey));
+        let mut records: Vec<_> = minimal_wallet_records()
+            .into_iter()
+            .filter(|(keyname, _, _)| {
+                !matches!(
+                    *keyname,
+                    "key"
+                        | "keymeta"
+                        | "orchard_note_commitment_tree"
+                        | "pool"
+                        | "sapzaddr"
+                        | "zkey"
+                        | "zkeymeta"
+                )
+            })
+            .collect();
+        records.extend([
+            (
+                "key",
+                compact(&pubkey),
+                privkey_value(&pubkey, &[0x5d; 214]),
+            ),
+            ("keymeta", compact(&pubkey), keymeta),
+            ("pool", 1i64.to_le_bytes().to_vec(), pool_entry),
+            (
+                "tx",
+                vec![0x10; 32],
+                wallet_tx_value(&v1_transaction(), Some(0)),
+            ),
+        ]);
+
+        let mut wallet = parse_records(&minimal_wallet_records());
+        wallet.merge(parse_records(&records)).unwrap();
+        assert_eq!(wallet.keys().keypairs().count(), 2);
+        let mut pool_indices: Vec<_> = wallet.key_pool().keys().copied().collect();
+        pool_indices.sort();
+        assert_eq!(pool_indices, [1, 2]);
+        assert_eq!(wallet.address_names().len(), 1);
+        assert_eq!(wallet.sapling_z_addresses().len(), 1);
+        assert!(wallet.transactions().contains_key(&txid(0x10)));
+        assert!(
+            wallet
+                .orchard_note_commitment_tree()
+                .note_position(&txid(0x55), 0)
+                .is_some()
+        );
+
+        // The same address named differently in each wallet.
+        let mut conflicting = records.clone();
+        for (keyname, _, value) in &mut conflicting {
+            if *keyname == "name" {
+                *value = compact(b"checking");
+            }
+        }
+        let mut wallet = parse_records(&minimal_wallet_records());
+        match wallet.merge(parse_records(&conflicting)) {
+            Err(Error::DuplicateRecord { kind, key }) => {
+                assert_eq!(kind, "address name");
+                assert!(key.contains(&named_address()));
+            }
+            other => panic!("expected a duplicate address name, got {other:?}"),
+        }
+        // Nothing of the other wallet was merged.
+        assert_eq!(wallet.keys().keypairs().count(), 1);
+        assert_eq!(wallet.key_pool().len(), 1);
+        assert!(!wallet.transactions().contains_key(&txid(0x10)));
+
+        // Both wallets track notes in an Orchard note commitment tree.
+        let mut wallet = parse_records(&minimal_wallet_records());
+        assert!(matches!(
+            wallet.merge(parse_records(&minimal_wallet_records())),
+            Err(Error::UnmergeableWallets { .. })
+        ));
+    }
+
+    #[test]
+    fn test_merge_wallets_with_different_seeds() {
+        // Disjoint wallets, each with its own mnemonic seed.
+        let mut records = minimal_wallet_records();
+        records.push(mnemonic_record(false));
+        let mut other_records: Vec<_> = minimal_wallet_records()
+            .into_iter()
+            .filter(|(keyname, _, _)| {
+                !matches!(
+                    *keyname,
+                    "key"
+                        | "keymeta"
+                        | "orchard_note_commitment_tree"
+                        | "pool"
+                        | "sapzaddr"
+                        | "zkey"
+                        | "zkeymeta"
+                )
+            })
+            .collect();
+        other_records.extend([
+            phrase_record("zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong", false),
+            (
+                "tx",
+                vec![0x10; 32],
+                wallet_tx_value(&v1_transaction(), Some(0)),
+            ),
+        ]);
+
+        let mut wallet = parse_records(&records);
+        match wallet.merge(parse_records(&other_records)) {
+            Err(Error::UnmergeableWallets { reason }) => {
+                assert_eq!(reason, "the wallets have different mnemonic seeds");
+            }
+            other => panic!("expected unmergeable wallets, got {other:?}"),
+        }
+        assert_eq!(wallet.bip39_mnemonic().unwrap().mnemonic(), MNEMONIC);
+        assert!(!wallet.transactions().contains_key(&txid(0x10)));
+
+        // The same seed in both wallets is kept once.
+        other_records.pop();
+        other_records.pop();
+        other_records.push(mnemonic_record(false));
+        let mut wallet = parse_records(&records);
+        wallet.merge(parse_records(&other_records)).unwrap();
+        assert_eq!(wallet.bip39_mnemonic().unwrap().mnemonic(), MNEMONIC);
+    }
+}
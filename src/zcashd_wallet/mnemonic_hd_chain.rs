@@ -5,8 +5,10 @@ use zewif::Blob32;
 use crate::{parse, parser::prelude::*, zcashd_wallet::SecondsSinceEpoch};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MnemonicHDChain {
     version: i32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::hex"))]
     seed_fp: Blob32,
     create_time: SecondsSinceEpoch,
     account_counter: u32,
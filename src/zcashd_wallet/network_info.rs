@@ -1,30 +1,55 @@
-use crate::Result;
+use crate::{Error, Result};
 use zewif::Network;
 
-use crate::{parse, parser::prelude::*};
+use crate::{migrate::primitives::address_encoding_network, parse, parser::prelude::*};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NetworkInfo {
     zcash: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::network_name"))]
     network: Network,
 }
 
 impl NetworkInfo {
+    pub fn new(network: Network) -> Self {
+        Self {
+            network,
+            ..Self::default()
+        }
+    }
+
     pub fn zcash(&self) -> &str {
         &self.zcash
     }
 
+    /// The network the wallet belongs to, as recorded by zcashd.
     pub fn network(&self) -> Network {
         self.network
     }
 
     pub fn to_address_encoding_network(&self) -> zcash_protocol::consensus::Network {
-        use zcash_protocol::consensus::Network::*;
-        match self.network {
-            Network::Main => MainNetwork,
-            Network::Test => TestNetwork,
-            // Regtest addresses are encoded as for the test network.
-            Network::Regtest => TestNetwork,
+        address_encoding_network(self.network)
+    }
+}
+
+/// Maps the identifier zcashd writes (`CChainParams::NetworkIDString`) to the
+/// network it names.
+fn network_from_identifier(identifier: String) -> Result<Network> {
+    match identifier.as_str() {
+        "main" => Ok(Network::Main),
+        "test" => Ok(Network::Test),
+        "regtest" => Ok(Network::Regtest),
+        _ => Err(Error::UnknownNetwork { identifier }),
+    }
+}
+
+/// Mainnet, which zcashd uses unless told otherwise.
+impl Default for NetworkInfo {
+    fn default() -> Self {
+        Self {
+            zcash: "Zcash".to_string(),
+            network: Network::Main,
         }
     }
 }
@@ -32,7 +57,48 @@ impl NetworkInfo {
 impl Parse for NetworkInfo {
     fn parse(p: &mut Parser) -> Result<Self> {
         let (zcash, identifier): (String, String) = parse!(p, "(zcash, identifier)")?;
-        let network = Network::try_from(identifier)?;
+        let network = network_from_identifier(identifier)?;
         Ok(Self { zcash, network })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use zewif::Network;
+
+    use super::NetworkInfo;
+    use crate::{
+        Error, parse,
+        parser::prelude::{Parse, Parser},
+    };
+
+    fn network_info(identifier: &str) -> Vec<u8> {
+        let mut payload = vec![5];
+        payload.extend_from_slice(b"Zcash");
+        payload.push(identifier.len() as u8);
+        payload.extend_from_slice(identifier.as_bytes());
+        payload
+    }
+
+    #[test]
+    fn test_network_identifiers() {
+        for (identifier, network) in [
+            ("main", Network::Main),
+            ("test", Network::Test),
+            ("regtest", Network::Regtest),
+        ] {
+            let info = parse!(buf = &network_info(identifier), NetworkInfo, "info").unwrap();
+            assert_eq!(info.zcash(), "Zcash");
+            assert_eq!(info.network(), network);
+        }
+    }
+
+    #[test]
+    fn test_unknown_network_identifier() {
+        let payload = network_info("signet");
+        assert!(matches!(
+            NetworkInfo::parse(&mut Parser::new(&payload)),
+            Err(Error::UnknownNetwork { identifier }) if identifier == "signet"
+        ));
+    }
+}
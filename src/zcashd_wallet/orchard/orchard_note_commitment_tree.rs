@@ -1,20 +1,24 @@
-use crate::Result;
+use crate::{parse, Error, Result};
 use bridgetree::{BridgeTree, Position};
 use byteorder::{LittleEndian, ReadBytesExt};
+use incrementalmerkletree::MerklePath;
 use std::{
     collections::BTreeMap,
     io::{self, Read},
 };
 
-use ::orchard::tree::MerkleHashOrchard;
+use ::orchard::tree::{Anchor, MerkleHashOrchard};
 use zcash_encoding::{Optional, Vector};
 use zcash_primitives::{consensus::BlockHeight, merkle_tree::read_position, transaction::TxId};
+use zewif::Data;
 
 use super::bridgetree_parsing::read_tree;
 use crate::parser::prelude::*;
 
 // Constants for tree validation
 const ORCHARD_TREE_DEPTH: u8 = 32;
+// The number of checkpoints zcashd keeps in its Orchard tree.
+const MAX_CHECKPOINTS: usize = 100;
 
 /// A data structure holding chain positions for a single transaction.
 #[allow(dead_code)]
@@ -34,6 +38,20 @@ pub struct OrchardNoteCommitmentTree {
     last_checkpoint: Option<BlockHeight>,
     commitment_tree: BridgeTree<MerkleHashOrchard, BlockHeight, ORCHARD_TREE_DEPTH>,
     note_positions: Vec<(TxId, NotePositions)>,
+    /// The serialized tree exactly as it appeared in the wallet.
+    data: Data,
+}
+
+/// An empty tree with no tracked notes.
+impl Default for OrchardNoteCommitmentTree {
+    fn default() -> Self {
+        Self {
+            last_checkpoint: None,
+            commitment_tree: BridgeTree::new(MAX_CHECKPOINTS),
+            note_positions: Vec::new(),
+            data: Data::new(),
+        }
+    }
 }
 
 impl OrchardNoteCommitmentTree {
@@ -66,6 +84,7 @@ impl OrchardNoteCommitmentTree {
                     last_checkpoint,
                     commitment_tree,
                     note_positions,
+                    data: Data::new(),
                 })
             }
             unrecognized => Err(io::Error::other(format!(
@@ -75,18 +94,202 @@ impl OrchardNoteCommitmentTree {
         }
     }
 
-    /// Convert to Zewif IncremetalWitness format
-    #[allow(dead_code)]
-    fn extract_witness(
+    /// The root of the tree as of its latest state, which is the anchor
+    /// that witnesses to the wallet's notes are made against.
+    pub fn root(&self) -> Anchor {
+        self.commitment_tree
+            .root(0)
+            .map_or_else(Anchor::empty_tree, Anchor::from)
+    }
+
+    /// The number of note commitments appended to the tree.
+    pub fn size(&self) -> u64 {
+        self.commitment_tree
+            .current_position()
+            .map_or(0, |position| u64::from(position) + 1)
+    }
+
+    /// Whether the tree has no note commitments and tracks no notes, as in a
+    /// wallet that never used Orchard.
+    pub fn is_empty(&self) -> bool {
+        self.size() == 0 && self.note_positions.is_empty()
+    }
+
+    /// The height of the last block the tree was checkpointed at, which
+    /// zcashd does for each block it connects once Orchard is active.
+    pub fn last_checkpoint(&self) -> Option<BlockHeight> {
+        self.last_checkpoint
+    }
+
+    /// Looks up the tree position of the note created by Orchard action
+    /// `action_index` of transaction `txid`.
+    pub fn note_position(&self, txid: &TxId, action_index: u32) -> Option<Position> {
+        let (_, positions) = self.note_positions.iter().find(|(id, _)| id == txid)?;
+        positions.note_positions.get(&action_index).copied()
+    }
+
+    /// The height of the block that mined transaction `txid`, recorded for
+    /// each transaction with Orchard notes the wallet tracks.
+    pub fn tx_height(&self, txid: &TxId) -> Option<BlockHeight> {
+        let (_, positions) = self.note_positions.iter().find(|(id, _)| id == txid)?;
+        Some(positions.tx_height)
+    }
+
+    /// The authentication path from the note commitment at `position` to the
+    /// tree's [`root`](Self::root). Only positions the wallet marked, which
+    /// are those of its own notes, can be witnessed.
+    pub fn witness(
         &self,
-        _position: Position,
-    ) -> zewif::IncrementalWitness<32, MerkleHashOrchard> {
-        todo!()
+        position: Position,
+    ) -> Result<MerklePath<MerkleHashOrchard, ORCHARD_TREE_DEPTH>> {
+        let unavailable = || Error::WitnessUnavailable {
+            position: u64::from(position),
+        };
+        let path = self
+            .commitment_tree
+            .witness(position, 0)
+            .map_err(|_| unavailable())?;
+        MerklePath::from_parts(path, position).map_err(|_| unavailable())
     }
 }
 
 impl Parse for OrchardNoteCommitmentTree {
     fn parse(p: &mut Parser) -> Result<Self> {
-        Ok(OrchardNoteCommitmentTree::read(p)?)
+        let start = p.offset;
+        let mut tree = OrchardNoteCommitmentTree::read(&mut *p)?;
+        tree.data = Data::from_slice(&p.buffer[start..p.offset]);
+        Ok(tree)
+    }
+}
+
+/// The last checkpoint and the positions of the wallet's notes, keyed by txid,
+/// are written for readability; `data` holds the hex of the serialized tree and
+/// is what deserialization reads back.
+#[cfg(feature = "serde")]
+impl serde::Serialize for OrchardNoteCommitmentTree {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        #[derive(serde::Serialize)]
+        struct NotePositionsJson {
+            tx_height: u32,
+            note_positions: BTreeMap<u32, u64>,
+        }
+
+        let note_positions: BTreeMap<String, NotePositionsJson> = self
+            .note_positions
+            .iter()
+            .map(|(txid, positions)| {
+                let json = NotePositionsJson {
+                    tx_height: u32::from(positions.tx_height),
+                    note_positions: positions
+                        .note_positions
+                        .iter()
+                        .map(|(index, position)| (*index, u64::from(*position)))
+                        .collect(),
+                };
+                (txid.to_string(), json)
+            })
+            .collect();
+
+        let mut state = serializer.serialize_struct("OrchardNoteCommitmentTree", 3)?;
+        state.serialize_field("last_checkpoint", &self.last_checkpoint.map(u32::from))?;
+        state.serialize_field("note_positions", &note_positions)?;
+        state.serialize_field("data", &hex::encode(self.data.as_slice()))?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for OrchardNoteCommitmentTree {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        #[derive(serde::Deserialize)]
+        struct TreeJson {
+            data: String,
+        }
+
+        let json = <TreeJson as serde::Deserialize>::deserialize(deserializer)?;
+        let bytes = hex::decode(json.data).map_err(D::Error::custom)?;
+        parse!(
+            buf = &bytes,
+            OrchardNoteCommitmentTree,
+            "orchard note commitment tree"
+        )
+        .map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bridgetree::BridgeTree;
+    use zewif::Data;
+
+    use super::*;
+    use crate::test_support::minimal_wallet;
+
+    fn leaf(i: u8) -> MerkleHashOrchard {
+        let mut bytes = [0u8; 32];
+        bytes[0] = i;
+        MerkleHashOrchard::from_bytes(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_witness_matches_root() {
+        let txid = TxId::from_bytes([0x55; 32]);
+        let mut commitment_tree = BridgeTree::new(10);
+        for i in 0..5u8 {
+            commitment_tree.append(leaf(i));
+            if i == 2 {
+                commitment_tree.mark();
+            }
+        }
+        commitment_tree.checkpoint(BlockHeight::from(1));
+        let tree = OrchardNoteCommitmentTree {
+            last_checkpoint: Some(BlockHeight::from(1)),
+            commitment_tree,
+            note_positions: vec![(
+                txid,
+                NotePositions {
+                    tx_height: BlockHeight::from(1),
+                    note_positions: BTreeMap::from([(0, Position::from(2))]),
+                },
+            )],
+            data: Data::new(),
+        };
+
+        let position = tree.note_position(&txid, 0).unwrap();
+        let path = tree.witness(position).unwrap();
+        assert_eq!(Anchor::from(path.root(leaf(2))), tree.root());
+        assert_eq!(tree.size(), 5);
+
+        assert!(tree.note_position(&txid, 1).is_none());
+        assert!(matches!(
+            tree.witness(Position::from(3)),
+            Err(Error::WitnessUnavailable { position: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_orchard_tree_root() {
+        use incrementalmerkletree::{Hashable, Level};
+        use ::orchard::tree::{Anchor, MerkleHashOrchard};
+
+        let wallet = minimal_wallet();
+        let tree = wallet.orchard_note_commitment_tree();
+
+        // The fixture's bridge tree has no leaves, so its anchor is the root
+        // of the empty depth-32 Orchard tree.
+        let empty_root = MerkleHashOrchard::empty_root(Level::from(32));
+        assert_eq!(tree.size(), 0);
+        assert_eq!(tree.root(), Anchor::from(empty_root));
     }
 }
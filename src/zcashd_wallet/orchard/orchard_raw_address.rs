@@ -7,8 +7,11 @@ use crate::{parse, parser::prelude::*};
 use crate::migrate::primitives::address_network_from_zewif;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OrchardRawAddress {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::hex"))]
     diversifier: Blob<11>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::hex"))]
     pk_d: Blob32,
 }
 
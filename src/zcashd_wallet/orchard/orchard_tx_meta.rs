@@ -5,8 +5,10 @@ use std::collections::HashMap;
 use crate::{parse, parser::prelude::*, zcashd_wallet::ClientVersion};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OrchardTxMeta {
     version: ClientVersion,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::hex_values"))]
     receiving_keys: HashMap<u32, IncomingViewingKey>,
     actions_spending_my_nodes: Vec<u32>,
 }
@@ -213,8 +213,28 @@ pub fn parse_fixed_length_array_with_param<T: ParseWithParam<U>, U: Clone, const
     Ok(array)
 }
 
+// Parses the `CompactSize` element count of a collection, rejecting counts
+// above the parser's `max_collection_length` so a corrupt count cannot cause
+// a huge allocation.
+fn parse_collection_length(p: &mut Parser, context: &str) -> Result<usize> {
+    let length = *parse!(p, CompactSize, context)?;
+    if length > p.max_collection_length {
+        let prefix = match length {
+            0..=0xfc => length as u8,
+            0xfd..=0xffff => 0xfd,
+            0x1_0000..=0xffff_ffff => 0xfe,
+            _ => 0xff,
+        };
+        return Err(Error::InvalidCompactSize {
+            prefix,
+            value: length as u64,
+        });
+    }
+    Ok(length)
+}
+
 pub fn parse_vec<T: Parse>(p: &mut Parser) -> Result<Vec<T>> {
-    let length = *parse!(p, CompactSize, "array length")?;
+    let length = parse_collection_length(p, "array length")?;
     parse_fixed_length_vec(p, length)
 }
 
@@ -222,7 +242,7 @@ pub fn parse_vec_with_param<T: ParseWithParam<U>, U: Clone>(
     p: &mut Parser,
     param: U,
 ) -> Result<Vec<T>> {
-    let length = *parse!(p, CompactSize, "array length")?;
+    let length = parse_collection_length(p, "array length")?;
     parse_fixed_length_vec_with_param(p, length, param)
 }
 
@@ -251,7 +271,7 @@ impl<T: ParseWithParam<U>, U: Clone> ParseWithParam<U> for Vec<T> {
 }
 
 pub fn parse_map<K: Parse, V: Parse>(p: &mut Parser) -> Result<Vec<(K, V)>> {
-    let length = *parse!(p, CompactSize, "map length")?;
+    let length = parse_collection_length(p, "map length")?;
     let mut items = Vec::with_capacity(length);
     for _ in 0..length {
         items.push(
@@ -282,7 +302,7 @@ pub fn parse_hashset<T>(p: &mut Parser) -> Result<HashSet<T>>
 where
     T: Parse + Eq + std::hash::Hash,
 {
-    let length = *parse!(p, CompactSize, "set length")?;
+    let length = parse_collection_length(p, "set length")?;
     let mut items = HashSet::with_capacity(length);
     for _ in 0..length {
         items.insert(parse!(p, "set item")?);
@@ -508,3 +528,109 @@ impl Parse for ::orchard::keys::IncomingViewingKey {
             .ok_or_else(|| Error::InvalidOrchardIncomingViewingKey)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `err` followed by each error it wraps.
+    fn error_chain(err: &Error) -> Vec<&Error> {
+        let mut chain = vec![];
+        let mut current: Option<&(dyn std::error::Error + 'static)> = Some(err);
+        while let Some(err) = current {
+            chain.extend(err.downcast_ref::<Error>());
+            current = err.source();
+        }
+        chain
+    }
+
+    fn is_underflow(err: &Error) -> bool {
+        error_chain(err)
+            .iter()
+            .any(|err| matches!(err, Error::BufferUnderflow { .. }))
+    }
+
+    #[test]
+    fn test_parse_byte_arrays() {
+        let bytes: Vec<u8> = (0..96).collect();
+        let mut p = Parser::new(&bytes);
+
+        let hash = parse!(&mut p, [u8; 32], "hash").unwrap();
+        assert_eq!(hash.as_slice(), &bytes[..32]);
+        let signature = parse!(&mut p, [u8; 64], "signature").unwrap();
+        assert_eq!(signature.as_slice(), &bytes[32..]);
+        p.check_finished().unwrap();
+    }
+
+    #[test]
+    fn test_parse_byte_array_underflow() {
+        let bytes = [0x5a; 31];
+        let err = parse!(&mut Parser::new(&bytes), [u8; 32], "hash").unwrap_err();
+        assert!(is_underflow(&err));
+
+        let bytes = [0x5a; 63];
+        let err = parse!(&mut Parser::new(&bytes), [u8; 64], "signature").unwrap_err();
+        assert!(is_underflow(&err));
+    }
+
+    #[test]
+    fn test_parse_vec_of_txids() {
+        let mut bytes = vec![2];
+        bytes.extend_from_slice(&[0x11; 32]);
+        bytes.extend_from_slice(&[0x22; 32]);
+        let mut p = Parser::new(&bytes);
+
+        let txids = parse!(&mut p, Vec<zewif::TxId>, "txids").unwrap();
+        assert_eq!(
+            txids,
+            vec![
+                zewif::TxId::from_bytes([0x11; 32]),
+                zewif::TxId::from_bytes([0x22; 32])
+            ]
+        );
+        p.check_finished().unwrap();
+    }
+
+    #[test]
+    fn test_parse_vec_rejects_huge_count() {
+        // A count of 0xffffffff with no elements behind it; allocating for it
+        // up front would need hundreds of gigabytes.
+        let bytes = [0xfe, 0xff, 0xff, 0xff, 0xff];
+        let err = parse!(&mut Parser::new(&bytes), Vec<zewif::TxId>, "txids").unwrap_err();
+        assert!(error_chain(&err).iter().any(|err| matches!(
+            err,
+            Error::InvalidCompactSize {
+                prefix: 0xfe,
+                value: 0xffff_ffff
+            }
+        )));
+
+        // The limit is configurable.
+        let bytes = [2, 0, 0];
+        let mut p = Parser::new(&bytes);
+        p.set_max_collection_length(1);
+        assert!(parse!(&mut p, Vec<u8>, "bytes").is_err());
+        let mut p = Parser::new(&bytes);
+        assert_eq!(parse!(&mut p, Vec<u8>, "bytes").unwrap(), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_parse_optional() {
+        let bytes = [0x00, 0x01, 0x2a, 0x00, 0x00, 0x00];
+        let mut p = Parser::new(&bytes);
+        assert_eq!(parse!(&mut p, Option<u32>, "absent").unwrap(), None);
+        assert_eq!(parse!(&mut p, Option<u32>, "present").unwrap(), Some(42));
+        p.check_finished().unwrap();
+    }
+
+    #[test]
+    fn test_parse_optional_rejects_bad_discriminant() {
+        let bytes = [0x02, 0x2a, 0x00, 0x00, 0x00];
+        let err = parse!(&mut Parser::new(&bytes), Option<u32>, "height").unwrap_err();
+        assert!(
+            error_chain(&err)
+                .iter()
+                .any(|err| matches!(err, Error::InvalidOptionalDiscriminant { value: 0x02 }))
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use crate::Result;
+
+use crate::{parse, parser::prelude::*};
+
+/// The purpose of an address book entry, from the wallet's `purpose` records.
+///
+/// zcashd writes `receive` for its own addresses and `send` for addresses it
+/// has paid. Some wallets have `unknown` or an empty string instead, which say
+/// nothing about the address and are read as [`Purpose::Unknown`]. Any other
+/// string is kept as [`Purpose::Other`], so that purposes written by newer
+/// clients survive a round trip. Both keep the text they were read from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "String", into = "String")
+)]
+pub enum Purpose {
+    Receive,
+    Send,
+    /// `unknown` or an empty string.
+    Unknown(String),
+    Other(String),
+}
+
+impl Purpose {
+    /// The string as zcashd stores it.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Purpose::Receive => "receive",
+            Purpose::Send => "send",
+            Purpose::Unknown(purpose) | Purpose::Other(purpose) => purpose,
+        }
+    }
+
+    /// Whether the record gives no purpose for the address.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Purpose::Unknown(_))
+    }
+}
+
+impl From<String> for Purpose {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "receive" => Purpose::Receive,
+            "send" => Purpose::Send,
+            "" | "unknown" => Purpose::Unknown(value),
+            _ => Purpose::Other(value),
+        }
+    }
+}
+
+impl From<Purpose> for String {
+    fn from(value: Purpose) -> Self {
+        match value {
+            Purpose::Unknown(purpose) | Purpose::Other(purpose) => purpose,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Purpose {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Parse for Purpose {
+    fn parse(p: &mut Parser) -> Result<Self> {
+        Ok(Purpose::from(parse!(p, String, "purpose")?))
+    }
+}
@@ -3,6 +3,7 @@ use crate::{parse, parser::prelude::*, zcashd_wallet::CompactSize, Error, Result
 /// ZCash receiver types used in Unified Addresses.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReceiverType {
     /// P2PKH (Pay to Public Key Hash) transparent address type
     P2PKH = 0x00,
@@ -7,6 +7,7 @@ use super::{
 use crate::{parse, parser::prelude::*};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RecipientAddress {
     KeyId(KeyId),
     ScriptId(ScriptId),
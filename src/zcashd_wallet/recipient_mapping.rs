@@ -1,6 +1,7 @@
 use crate::zcashd_wallet::RecipientAddress;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RecipientMapping {
     pub recipient_address: RecipientAddress,
     pub unified_address: String,
@@ -0,0 +1,10 @@
+use std::fmt;
+
+/// Stands in for secret material in `Debug` output, giving only its length.
+pub(crate) struct Redacted(pub(crate) usize);
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<redacted {} bytes>", self.0)
+    }
+}
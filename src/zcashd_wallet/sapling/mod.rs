@@ -2,5 +2,6 @@ use zewif::mod_use;
 
 mod_use!(sapling_key);
 mod_use!(sapling_keys);
+mod_use!(sapling_note_commitment_tree);
 mod_use!(sapling_note_data);
 mod_use!(sapling_z_payment_address);
@@ -1,13 +1,26 @@
-use crate::Result;
+use crate::{Error, Result};
 
-use zewif::sapling::SaplingIncomingViewingKey;
+use std::collections::HashMap;
 
-use crate::zcashd_wallet::KeyMetadata;
+use subtle::ConstantTimeEq;
+use zcash_address::{ToAddress, ZcashAddress};
+use zcash_protocol::consensus::NetworkConstants;
+use zewif::{Network, sapling::SaplingIncomingViewingKey};
 
+use crate::{
+    migrate::primitives::{address_encoding_network, address_network_from_zewif},
+    zcashd_wallet::{KeyMetadata, Redacted, sapling::SaplingZPaymentAddress},
+};
 
-#[derive(Debug, Clone, PartialEq)]
+/// The length of an encoded Sapling extended spending key.
+const EXTSK_SIZE: usize = 169;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SaplingKey {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::hex"))]
     ivk: SaplingIncomingViewingKey,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::secret_hex"))]
     extsk: sapling::zip32::ExtendedSpendingKey,
     metadata: KeyMetadata,
 }
@@ -21,6 +34,20 @@ impl SaplingKey {
         Ok(Self { ivk, extsk, metadata })
     }
 
+    /// The key for `extsk`, with its incoming viewing key derived the way
+    /// zcashd keys `sapzkey` records.
+    pub fn from_spending_key(
+        extsk: sapling::zip32::ExtendedSpendingKey,
+        metadata: KeyMetadata,
+    ) -> Self {
+        let ivk = extsk.to_diversifiable_full_viewing_key().fvk().vk.ivk();
+        Self {
+            ivk: SaplingIncomingViewingKey::new(ivk.to_repr()),
+            extsk,
+            metadata,
+        }
+    }
+
     pub fn ivk(&self) -> &SaplingIncomingViewingKey {
         &self.ivk
     }
@@ -32,4 +59,139 @@ impl SaplingKey {
     pub fn metadata(&self) -> &KeyMetadata {
         &self.metadata
     }
+
+    /// The encodings of the key's addresses: its default address, then each
+    /// other address `z_addresses`, the wallet's `sapzaddr` records, maps to
+    /// the key's incoming viewing key, in the order of their diversifiers.
+    ///
+    /// Recorded addresses are derived again from the key, failing if one is
+    /// not an address of the key.
+    pub fn diversified_addresses(
+        &self,
+        network: Network,
+        z_addresses: &HashMap<SaplingZPaymentAddress, SaplingIncomingViewingKey>,
+    ) -> Result<Vec<String>> {
+        let dfvk = self.extsk.to_diversifiable_full_viewing_key();
+        let ivk = dfvk.fvk().vk.ivk();
+        let (_, default_address) = dfvk.default_address();
+        let mut recorded: Vec<_> = z_addresses
+            .iter()
+            .filter(|(address, address_ivk)| {
+                **address_ivk == self.ivk
+                    && address.diversifier().as_slice() != default_address.diversifier().0
+            })
+            .map(|(address, _)| address)
+            .collect();
+        recorded.sort_by(|a, b| a.diversifier().as_slice().cmp(b.diversifier().as_slice()));
+
+        let mut addresses = vec![default_address];
+        for address in recorded {
+            let mut diversifier = [0u8; 11];
+            diversifier.copy_from_slice(address.diversifier().as_slice());
+            let derived = ivk
+                .to_payment_address(sapling::Diversifier(diversifier))
+                .filter(|derived| derived.to_bytes()[11..] == *address.pk().as_slice())
+                .ok_or(Error::InvalidBitPattern {
+                    kind: "Sapling diversified address",
+                })?;
+            addresses.push(derived);
+        }
+        let network = address_network_from_zewif(network);
+        Ok(addresses
+            .iter()
+            .map(|address| ZcashAddress::from_sapling(network, address.to_bytes()).to_string())
+            .collect())
+    }
+
+    /// The `Debug` form with the spending key shown, which `Debug` itself
+    /// redacts. Keep the result out of logs.
+    pub fn reveal(&self) -> String {
+        format!(
+            "SaplingKey {{ ivk: {:?}, extsk: {:?}, metadata: {:?} }}",
+            self.ivk, self.extsk, self.metadata
+        )
+    }
+
+    /// The extended spending key in the Bech32 form that zcashd's
+    /// `z_exportkey` prints and `z_importkey` accepts.
+    pub fn to_export_string(&self, network: Network) -> String {
+        let params = address_encoding_network(network);
+        zcash_keys::encoding::encode_extended_spending_key(
+            params.hrp_sapling_extended_spending_key(),
+            &self.extsk,
+        )
+    }
+}
+
+/// Compares the keys in constant time.
+impl PartialEq for SaplingKey {
+    fn eq(&self, other: &Self) -> bool {
+        let ivk = self.ivk.as_bytes().ct_eq(other.ivk.as_bytes());
+        let extsk = self.extsk.to_bytes().ct_eq(&other.extsk.to_bytes());
+        bool::from(ivk & extsk) && self.metadata == other.metadata
+    }
+}
+
+impl std::fmt::Debug for SaplingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SaplingKey")
+            .field("ivk", &self.ivk)
+            .field("extsk", &Redacted(EXTSK_SIZE))
+            .field("metadata", &self.metadata)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zewif::Network;
+
+    use crate::{
+        Error,
+        test_support::{minimal_wallet_records, parse_records},
+    };
+
+    #[test]
+    fn test_sapling_diversified_addresses() {
+        let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]);
+        let dfvk = extsk.to_diversifiable_full_viewing_key();
+        let ivk = dfvk.fvk().vk.ivk().to_repr().to_vec();
+        let (_, default_address) = dfvk.default_address();
+        let (_, diversified) = dfvk
+            .find_address(zip32::DiversifierIndex::from(5u32))
+            .unwrap();
+
+        let mut records = minimal_wallet_records();
+        let mut sapzkeymeta = 1i32.to_le_bytes().to_vec();
+        sapzkeymeta.extend_from_slice(&1_550_000_000u64.to_le_bytes());
+        records.push(("sapzkey", ivk.clone(), extsk.to_bytes().to_vec()));
+        records.push(("sapzkeymeta", ivk.clone(), sapzkeymeta));
+        for address in [&default_address, &diversified] {
+            records.push(("sapzaddr", address.to_bytes().to_vec(), ivk.clone()));
+        }
+        let wallet = parse_records(&records);
+        let key = wallet.sapling_keys().keypairs().next().unwrap();
+        let encode = |address: &::sapling::PaymentAddress| {
+            zcash_keys::encoding::encode_payment_address(
+                zcash_protocol::constants::mainnet::HRP_SAPLING_PAYMENT_ADDRESS,
+                address,
+            )
+        };
+
+        let addresses = key
+            .diversified_addresses(Network::Main, wallet.sapling_z_addresses())
+            .unwrap();
+        assert_eq!(addresses, [encode(&default_address), encode(&diversified)]);
+
+        // A record under the key's IVK whose address the key cannot derive.
+        let mut forged = diversified.to_bytes().to_vec();
+        forged[20] ^= 0x01;
+        records.push(("sapzaddr", forged, ivk));
+        let wallet = parse_records(&records);
+        let key = wallet.sapling_keys().keypairs().next().unwrap();
+        assert!(matches!(
+            key.diversified_addresses(Network::Main, wallet.sapling_z_addresses()),
+            Err(Error::InvalidBitPattern { .. })
+        ));
+    }
 }
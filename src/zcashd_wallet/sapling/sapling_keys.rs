@@ -2,16 +2,33 @@ use std::collections::HashMap;
 
 use zewif::sapling::SaplingIncomingViewingKey;
 
+use crate::{
+    Result,
+    zcashd_wallet::{check_map, merge_map},
+};
+
 use super::SaplingKey;
 
-#[derive(Clone, PartialEq)]
-pub struct SaplingKeys(HashMap<SaplingIncomingViewingKey, SaplingKey>);
+#[derive(Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SaplingKeys(
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::keyed_map"))]
+    HashMap<SaplingIncomingViewingKey, SaplingKey>,
+);
 
 impl SaplingKeys {
     pub fn new(map: HashMap<SaplingIncomingViewingKey, SaplingKey>) -> Self {
         Self(map)
     }
 
+    pub(crate) fn check_merge(&self, other: &Self) -> Result<()> {
+        check_map("sapling key", &self.0, &other.0)
+    }
+
+    pub(crate) fn merge(&mut self, other: Self) {
+        merge_map(&mut self.0, other.0)
+    }
+
     pub fn keypairs(&self) -> impl Iterator<Item = &SaplingKey> {
         self.0.values()
     }
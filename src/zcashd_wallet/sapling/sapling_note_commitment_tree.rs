@@ -0,0 +1,151 @@
+use crate::{Error, Result};
+use incrementalmerkletree::{Hashable, Level};
+
+use crate::{
+    parse,
+    parser::prelude::*,
+    zcashd_wallet::{IncrementalMerkleTree, sapling::SaplingWitness},
+};
+
+const SAPLING_TREE_DEPTH: usize = 32;
+
+/// A Sapling note commitment tree, as rebuilt from the trees and witnesses
+/// zcashd serializes for its Sapling notes.
+///
+/// The tree is held as the roots of the complete subtrees its leaves form,
+/// one for each bit set in its size. That is enough to compute its root and
+/// to keep appending, and unlike a frontier it does not need the last leaf,
+/// which a witness stops recording once the subtree holding it is complete.
+///
+/// Unlike Orchard, zcashd keeps no wallet-wide Sapling tree; see
+/// [`ZcashdWallet::sapling_note_commitment_tree`](crate::ZcashdWallet::sapling_note_commitment_tree).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaplingNoteCommitmentTree {
+    size: u64,
+    /// Indexed by level, and `Some` exactly where that bit of `size` is set.
+    subtrees: [Option<::sapling::Node>; SAPLING_TREE_DEPTH],
+}
+
+impl SaplingNoteCommitmentTree {
+    fn empty() -> Self {
+        Self {
+            size: 0,
+            subtrees: [const { None }; SAPLING_TREE_DEPTH],
+        }
+    }
+
+    /// The root of the tree, usable as the anchor of a Sapling spend.
+    pub fn root(&self) -> ::sapling::Anchor {
+        // Climb from the first empty leaf, whose left siblings are the
+        // complete subtrees and whose right siblings are all empty.
+        let mut node = ::sapling::Node::empty_leaf();
+        for (level, subtree) in self.subtrees.iter().enumerate() {
+            let level = Level::from(level as u8);
+            node = match subtree {
+                Some(left) => ::sapling::Node::combine(level, left, &node),
+                None => ::sapling::Node::combine(level, &node, &::sapling::Node::empty_root(level)),
+            };
+        }
+        ::sapling::Anchor::from(node)
+    }
+
+    /// The number of note commitments appended to the tree.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Rebuilds the tree as of the last block `witness` was updated to: the
+    /// tree the witness was made against, then the subtrees it has filled
+    /// since, then the leaves of its partial subtree.
+    pub fn at_witness_tip(witness: &SaplingWitness) -> Result<Self> {
+        let mut tree = Self::try_from(witness.tree())?;
+        for (skip, hash) in witness.filled().iter().enumerate() {
+            let depth = witness.tree().next_depth(skip);
+            tree.append_subtree(depth, sapling_node(hash.as_slice())?)?;
+        }
+        if let Some(cursor) = witness.cursor() {
+            tree.append_tree(cursor)?;
+        }
+        Ok(tree)
+    }
+
+    /// Appends the complete subtree of height `level` whose root is `node`.
+    fn append_subtree(&mut self, level: usize, node: ::sapling::Node) -> Result<()> {
+        let invalid = || Error::InvalidBitPattern {
+            kind: "sapling note commitment tree",
+        };
+        // The subtree must start where a subtree of its height can, and the
+        // tree must have room for it.
+        if level >= SAPLING_TREE_DEPTH
+            || self.size % (1 << level) != 0
+            || self.subtrees[level..].iter().all(Option::is_some)
+        {
+            return Err(invalid());
+        }
+        let mut node = node;
+        let mut carry_level = level;
+        while let Some(left) = self.subtrees[carry_level].take() {
+            node = ::sapling::Node::combine(Level::from(carry_level as u8), &left, &node);
+            carry_level += 1;
+        }
+        self.subtrees[carry_level] = Some(node);
+        self.size += 1 << level;
+        Ok(())
+    }
+
+    /// Appends the leaves of `tree`, largest subtree first.
+    fn append_tree(&mut self, tree: &IncrementalMerkleTree) -> Result<()> {
+        for (i, parent) in tree.parents().iter().enumerate().rev() {
+            if let Some(parent) = parent {
+                self.append_subtree(i + 1, sapling_node(&parent.into_bytes())?)?;
+            }
+        }
+        for leaf in [tree.left(), tree.right()].into_iter().flatten() {
+            self.append_subtree(0, sapling_node(&leaf.into_bytes())?)?;
+        }
+        Ok(())
+    }
+}
+
+fn sapling_node(bytes: &[u8]) -> Result<::sapling::Node> {
+    <[u8; 32]>::try_from(bytes)
+        .ok()
+        .and_then(|bytes| Option::from(::sapling::Node::from_bytes(bytes)))
+        .ok_or(Error::InvalidBitPattern {
+            kind: "sapling note commitment",
+        })
+}
+
+impl TryFrom<&IncrementalMerkleTree> for SaplingNoteCommitmentTree {
+    type Error = Error;
+
+    fn try_from(tree: &IncrementalMerkleTree) -> Result<Self> {
+        let mut result = Self::empty();
+        result.append_tree(tree)?;
+        Ok(result)
+    }
+}
+
+impl Parse for SaplingNoteCommitmentTree {
+    fn parse(p: &mut Parser) -> Result<Self> {
+        let tree = parse!(p, IncrementalMerkleTree, "sapling note commitment tree")?;
+        Self::try_from(&tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SaplingNoteCommitmentTree;
+    use crate::{parse, zcashd_wallet::u256};
+
+    #[test]
+    fn test_empty_tree_root() {
+        let tree = parse!(buf = &[0u8, 0, 0], SaplingNoteCommitmentTree, "tree").unwrap();
+        // The Sapling empty root, as zcashd displays it.
+        let empty_root =
+            u256::from_hex("3e49b5f954aa9d3545bc6c37744661eea48d7c34e3000d82b7f0010c30f4c2fb")
+                .unwrap();
+        assert_eq!(tree.size(), 0);
+        assert_eq!(tree.root().to_bytes(), empty_root.into_bytes());
+    }
+}
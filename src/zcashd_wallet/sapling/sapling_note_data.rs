@@ -5,9 +5,12 @@ use crate::{parse, parser::prelude::*, zcashd_wallet::IncrementalWitness};
 pub type SaplingWitness = IncrementalWitness<32, Blob<32>>;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SaplingNoteData {
     version: i32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::hex"))]
     incoming_viewing_key: SaplingIncomingViewingKey,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::opt_hex"))]
     nullifier: Option<Blob<32>>,
     witnesses: Vec<SaplingWitness>,
     witness_height: i32,
@@ -36,3 +36,33 @@ impl Parse for SaplingZPaymentAddress {
         Ok(SaplingZPaymentAddress { diversifier, pk })
     }
 }
+
+/// Serializes as the hex of the raw 43-byte address (`diversifier || pk_d`).
+#[cfg(feature = "serde")]
+impl serde::Serialize for SaplingZPaymentAddress {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        let bytes = [self.diversifier.as_slice(), self.pk.as_slice()].concat();
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SaplingZPaymentAddress {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+        let bytes = hex::decode(value).map_err(D::Error::custom)?;
+        parse!(
+            buf = &bytes,
+            SaplingZPaymentAddress,
+            "SaplingZPaymentAddress"
+        )
+        .map_err(D::Error::custom)
+    }
+}
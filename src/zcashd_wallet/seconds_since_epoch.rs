@@ -1,5 +1,5 @@
 use crate::Result;
-use chrono::{SecondsFormat, TimeZone, Utc};
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
 
 use crate::{parse, parser::prelude::*};
 
@@ -38,6 +38,7 @@ use crate::{parse, parser::prelude::*};
 /// The internal value is stored as a 64-bit unsigned integer, allowing for timestamps
 /// well beyond the year 2038 (unlike 32-bit Unix timestamps which have the Y2038 problem).
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SecondsSinceEpoch(u64);
 
 impl SecondsSinceEpoch {
@@ -59,6 +60,26 @@ impl SecondsSinceEpoch {
     pub fn is_zero(&self) -> bool {
         self.0 == 0
     }
+
+    /// Returns the timestamp as a UTC date-time, or `None` if it is zero,
+    /// which zcashd uses to mean "never" (for example, a key that does not
+    /// expire), or too large to represent.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zewif_zcashd::zcashd_wallet::SecondsSinceEpoch;
+    /// let jan_1_2023 = SecondsSinceEpoch::from(1672531200u64).to_datetime().unwrap();
+    /// assert_eq!(jan_1_2023.to_rfc3339(), "2023-01-01T00:00:00+00:00");
+    ///
+    /// assert_eq!(SecondsSinceEpoch::from(0u64).to_datetime(), None);
+    /// ```
+    pub fn to_datetime(&self) -> Option<DateTime<Utc>> {
+        if self.is_zero() {
+            return None;
+        }
+        let seconds = i64::try_from(self.0).ok()?;
+        Utc.timestamp_opt(seconds, 0).single()
+    }
 }
 
 /// Creates a timestamp from a u64 seconds value
@@ -96,12 +117,14 @@ impl Parse for SecondsSinceEpoch {
 /// Formats the timestamp as an ISO-8601 date-time string (e.g., "2023-01-01T00:00:00Z")
 impl std::fmt::Debug for SecondsSinceEpoch {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let dt = Utc
-            .timestamp_opt(self.0 as i64, 0)
-            .single()
-            .unwrap()
-            .to_rfc3339_opts(SecondsFormat::Secs, true);
-        write!(f, "{}", dt)
+        match i64::try_from(self.0)
+            .ok()
+            .and_then(|seconds| Utc.timestamp_opt(seconds, 0).single())
+        {
+            Some(dt) => write!(f, "{}", dt.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            // Beyond the range of a date-time, so shown as the raw count.
+            None => write!(f, "{}s", self.0),
+        }
     }
 }
 
@@ -111,3 +134,23 @@ impl std::fmt::Display for SecondsSinceEpoch {
         write!(f, "{:?}", self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SecondsSinceEpoch;
+
+    #[test]
+    fn test_to_datetime() {
+        let timestamp = SecondsSinceEpoch::from(1_600_000_000u64);
+        assert_eq!(timestamp.to_datetime().unwrap().timestamp(), 1_600_000_000);
+        assert_eq!(timestamp.to_string(), "2020-09-13T12:26:40Z");
+
+        let never = SecondsSinceEpoch::from(0u64);
+        assert_eq!(never.to_datetime(), None);
+        assert_eq!(never.to_string(), "1970-01-01T00:00:00Z");
+
+        let out_of_range = SecondsSinceEpoch::from(u64::MAX);
+        assert_eq!(out_of_range.to_datetime(), None);
+        assert_eq!(out_of_range.to_string(), format!("{}s", u64::MAX));
+    }
+}
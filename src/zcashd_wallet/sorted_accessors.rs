@@ -0,0 +1,138 @@
+use std::collections::{BTreeMap, HashMap};
+
+use zewif::{TxId, sapling::SaplingIncomingViewingKey};
+
+use crate::{
+    ZcashdWallet,
+    zcashd_wallet::{
+        AccountingEntry, Address, KeyPoolEntry, LegacyAccount, Purpose, RecipientMapping, WalletTx,
+        sapling::{SaplingKey, SaplingZPaymentAddress},
+        transparent::KeyPair,
+    },
+};
+
+/// The entries of `map`, sorted by `key`.
+fn sorted_by<'a, K, V, T: Ord>(
+    map: &'a HashMap<K, V>,
+    key: impl Fn(&K) -> T,
+) -> Vec<(&'a K, &'a V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by_cached_key(|(k, _)| key(k));
+    entries
+}
+
+fn payment_address_bytes(address: &SaplingZPaymentAddress) -> Vec<u8> {
+    [address.diversifier().as_slice(), address.pk().as_slice()].concat()
+}
+
+/// Accessors for the wallet's maps in a canonical order, so that two parses of
+/// the same wallet read back identically.
+///
+/// Addresses sort by their encoding, keys by their bytes and transactions by
+/// transaction ID.
+impl ZcashdWallet {
+    pub fn accounting_entries_sorted(&self) -> Vec<(&String, &BTreeMap<u64, AccountingEntry>)> {
+        sorted_by(self.accounting_entries(), String::clone)
+    }
+
+    pub fn address_names_sorted(&self) -> Vec<(&Address, &String)> {
+        sorted_by(self.address_names(), Address::to_string)
+    }
+
+    pub fn address_purposes_sorted(&self) -> Vec<(&Address, &Purpose)> {
+        sorted_by(self.address_purposes(), Address::to_string)
+    }
+
+    /// The destination data of each address, with each address's own entries
+    /// sorted by name.
+    pub fn dest_data_sorted(&self) -> Vec<(&Address, Vec<(&String, &String)>)> {
+        sorted_by(self.dest_data(), Address::to_string)
+            .into_iter()
+            .map(|(address, data)| (address, sorted_by(data, String::clone)))
+            .collect()
+    }
+
+    pub fn key_pool_sorted(&self) -> Vec<(&i64, &KeyPoolEntry)> {
+        sorted_by(self.key_pool(), |index| *index)
+    }
+
+    /// The transparent keypairs, sorted by public key.
+    pub fn keys_sorted(&self) -> Vec<&KeyPair> {
+        let mut keys: Vec<_> = self.keys().keypairs().collect();
+        keys.sort_by(|a, b| a.pubkey().as_slice().cmp(b.pubkey().as_slice()));
+        keys
+    }
+
+    pub fn legacy_accounts_sorted(&self) -> Vec<(&String, &LegacyAccount)> {
+        sorted_by(self.legacy_accounts(), String::clone)
+    }
+
+    pub fn sapling_extended_fvks_sorted(
+        &self,
+    ) -> Vec<(
+        &SaplingZPaymentAddress,
+        &::sapling::zip32::ExtendedFullViewingKey,
+    )> {
+        sorted_by(self.sapling_extended_fvks(), payment_address_bytes)
+    }
+
+    /// The Sapling spending keys, sorted by incoming viewing key.
+    pub fn sapling_keys_sorted(&self) -> Vec<&SaplingKey> {
+        let mut keys: Vec<_> = self.sapling_keys().keypairs().collect();
+        keys.sort_by(|a, b| a.ivk().as_bytes().cmp(b.ivk().as_bytes()));
+        keys
+    }
+
+    pub fn sapling_z_addresses_sorted(
+        &self,
+    ) -> Vec<(&SaplingZPaymentAddress, &SaplingIncomingViewingKey)> {
+        sorted_by(self.sapling_z_addresses(), payment_address_bytes)
+    }
+
+    pub fn send_recipients_sorted(&self) -> Vec<(&TxId, &Vec<RecipientMapping>)> {
+        sorted_by(self.send_recipients(), |txid| *txid)
+    }
+
+    pub fn transactions_sorted(&self) -> Vec<(&TxId, &WalletTx)> {
+        sorted_by(self.transactions(), |txid| *txid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        minimal_wallet_records, parse_records, txid, v1_transaction, wallet_tx_value,
+    };
+
+    #[test]
+    fn test_sorted_accessors_are_deterministic() {
+        let mut records = minimal_wallet_records();
+        for txid_byte in [0x30, 0x10, 0x20] {
+            records.push((
+                "tx",
+                vec![txid_byte; 32],
+                wallet_tx_value(&v1_transaction(), None),
+            ));
+        }
+        let snapshot = || {
+            let wallet = parse_records(&records);
+            let txids: Vec<_> = wallet
+                .transactions_sorted()
+                .into_iter()
+                .map(|(txid, _)| *txid)
+                .collect();
+            format!(
+                "{:?}\n{:?}\n{:?}\n{:?}\n{:?}",
+                txids,
+                wallet.keys_sorted(),
+                wallet.sapling_keys_sorted(),
+                wallet.address_names_sorted(),
+                wallet.address_purposes_sorted(),
+            )
+        };
+
+        let first = snapshot();
+        assert_eq!(first, snapshot());
+        assert!(first.starts_with(&format!("{:?}", [txid(0x10), txid(0x20), txid(0x30)])));
+    }
+}
@@ -0,0 +1,103 @@
+use zewif::{Blob, Data};
+
+use crate::Result;
+
+use crate::{parse, parser::prelude::*, zcashd_wallet::u256};
+
+/// A Sprout JoinSplit description of a transaction: the two notes it spends
+/// and the two it creates, moving value between the transparent and Sprout
+/// pools.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JoinSplitDescription {
+    vpub_old: u64,
+    vpub_new: u64,
+    anchor: u256,
+    nullifiers: [u256; 2],
+    commitments: [u256; 2],
+    ephemeral_key: u256,
+    random_seed: u256,
+    macs: [u256; 2],
+    proof: Data,
+    ciphertexts: [Blob<601>; 2],
+}
+
+impl JoinSplitDescription {
+    /// The value in zatoshis taken from the transparent pool.
+    pub fn vpub_old(&self) -> u64 {
+        self.vpub_old
+    }
+
+    /// The value in zatoshis returned to the transparent pool.
+    pub fn vpub_new(&self) -> u64 {
+        self.vpub_new
+    }
+
+    /// The root of the Sprout note commitment tree the spent notes are in.
+    pub fn anchor(&self) -> u256 {
+        self.anchor
+    }
+
+    /// The nullifiers of the two spent notes.
+    pub fn nullifiers(&self) -> &[u256; 2] {
+        &self.nullifiers
+    }
+
+    /// The note commitments of the two created notes.
+    pub fn commitments(&self) -> &[u256; 2] {
+        &self.commitments
+    }
+
+    pub fn ephemeral_key(&self) -> u256 {
+        self.ephemeral_key
+    }
+
+    pub fn random_seed(&self) -> u256 {
+        self.random_seed
+    }
+
+    pub fn macs(&self) -> &[u256; 2] {
+        &self.macs
+    }
+
+    /// The zero-knowledge proof, as its raw bytes.
+    pub fn proof(&self) -> &Data {
+        &self.proof
+    }
+
+    /// The encrypted plaintexts of the two created notes, in the order of
+    /// their commitments.
+    pub fn ciphertexts(&self) -> &[Blob<601>; 2] {
+        &self.ciphertexts
+    }
+}
+
+/// Parses a description as transactions serialize it. The parameter is
+/// whether the proof is a 192 byte Groth16 proof, as from Sapling on, rather
+/// than a 296 byte BCTV14 proof.
+impl ParseWithParam<bool> for JoinSplitDescription {
+    fn parse(p: &mut Parser, use_groth: bool) -> Result<Self> {
+        let vpub_old = parse!(p, "vpub_old")?;
+        let vpub_new = parse!(p, "vpub_new")?;
+        let anchor = parse!(p, "anchor")?;
+        let nullifiers = parse!(p, "nullifiers")?;
+        let commitments = parse!(p, "commitments")?;
+        let ephemeral_key = parse!(p, "ephemeral_key")?;
+        let random_seed = parse!(p, "random_seed")?;
+        let macs = parse!(p, "macs")?;
+        let proof_length = if use_groth { 192 } else { 296 };
+        let proof = Data::from_slice(p.next(proof_length)?);
+        let ciphertexts = parse!(p, "ciphertexts")?;
+        Ok(Self {
+            vpub_old,
+            vpub_new,
+            anchor,
+            nullifiers,
+            commitments,
+            ephemeral_key,
+            random_seed,
+            macs,
+            proof,
+            ciphertexts,
+        })
+    }
+}
@@ -32,3 +32,36 @@ impl Parse for JSOutPoint {
         })
     }
 }
+
+/// Serializes as `hash:js:n`, so that outpoints can be used as JSON object keys.
+#[cfg(feature = "serde")]
+impl serde::Serialize for JSOutPoint {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{}:{}:{}", self.hash, self.js, self.n))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for JSOutPoint {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+        let mut parts = value.split(':');
+        let (Some(hash), Some(js), Some(n), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(D::Error::custom("expected \"hash:js:n\""));
+        };
+        Ok(Self {
+            hash: u256::from_hex(hash).map_err(D::Error::custom)?,
+            js: js.parse().map_err(D::Error::custom)?,
+            n: n.parse().map_err(D::Error::custom)?,
+        })
+    }
+}
@@ -1,5 +1,6 @@
 use zewif::mod_use;
 
+mod_use!(join_split_description);
 mod_use!(js_out_point);
 mod_use!(sprout_note_data);
 mod_use!(sprout_payment_address);
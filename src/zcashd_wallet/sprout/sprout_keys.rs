@@ -1,8 +1,14 @@
 use std::collections::HashMap;
 
+use crate::{
+    Result,
+    zcashd_wallet::{check_map, merge_map},
+};
+
 use super::{SproutPaymentAddress, SproutSpendingKey};
 
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SproutKeys(HashMap<SproutPaymentAddress, SproutSpendingKey>);
 
 impl SproutKeys {
@@ -10,9 +16,21 @@ impl SproutKeys {
         Self(map)
     }
 
+    pub(crate) fn check_merge(&self, other: &Self) -> Result<()> {
+        check_map("sprout key", &self.0, &other.0)
+    }
+
+    pub(crate) fn merge(&mut self, other: Self) {
+        merge_map(&mut self.0, other.0)
+    }
+
     pub fn keypairs(&self) -> impl Iterator<Item = &SproutSpendingKey> {
         self.0.values()
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&SproutPaymentAddress, &SproutSpendingKey)> {
+        self.0.iter()
+    }
 }
 
 impl std::fmt::Debug for SproutKeys {
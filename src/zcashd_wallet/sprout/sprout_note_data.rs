@@ -5,6 +5,7 @@ use crate::{parse, parser::prelude::*, zcashd_wallet::u256};
 use super::{SproutPaymentAddress, SproutWitness};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SproutNoteData {
     address: SproutPaymentAddress,
     nullifer: Option<u256>,
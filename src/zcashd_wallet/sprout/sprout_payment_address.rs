@@ -1,6 +1,11 @@
 use crate::Result;
+use zcash_address::{ToAddress, ZcashAddress};
+use zewif::Network;
 
-use crate::{parse, parser::prelude::*, zcashd_wallet::u256};
+use crate::{
+    migrate::primitives::address_network_from_zewif, parse, parser::prelude::*,
+    zcashd_wallet::u256,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SproutPaymentAddress {
@@ -9,6 +14,14 @@ pub struct SproutPaymentAddress {
 }
 
 impl SproutPaymentAddress {
+    pub fn to_string(&self, network: Network) -> String {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(self.a_pk.as_ref());
+        bytes[32..].copy_from_slice(self.pk_enc.as_ref());
+        let addr = ZcashAddress::from_sprout(address_network_from_zewif(network), bytes);
+        addr.to_string()
+    }
+
     pub fn a_pk(&self) -> u256 {
         self.a_pk
     }
@@ -26,3 +39,28 @@ impl Parse for SproutPaymentAddress {
         })
     }
 }
+
+/// Serializes as the hex of `a_pk || pk_enc`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for SproutPaymentAddress {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        let bytes = [self.a_pk.as_ref(), self.pk_enc.as_ref()].concat();
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SproutPaymentAddress {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+        let bytes = hex::decode(value).map_err(D::Error::custom)?;
+        parse!(buf = &bytes, SproutPaymentAddress, "SproutPaymentAddress").map_err(D::Error::custom)
+    }
+}
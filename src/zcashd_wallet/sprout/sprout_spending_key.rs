@@ -1,7 +1,11 @@
-use crate::zcashd_wallet::{KeyMetadata, u252};
+use subtle::ConstantTimeEq;
 
-#[derive(Debug, Clone, PartialEq)]
+use crate::zcashd_wallet::{KeyMetadata, Redacted, U252_SIZE, u252};
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SproutSpendingKey {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::secret"))]
     key: u252,
     metadata: KeyMetadata,
 }
@@ -14,6 +18,15 @@ impl SproutSpendingKey {
     pub fn metadata(&self) -> &KeyMetadata {
         &self.metadata
     }
+
+    /// The `Debug` form with the key shown, which `Debug` itself redacts.
+    /// Keep the result out of logs.
+    pub fn reveal(&self) -> String {
+        format!(
+            "SproutSpendingKey {{ key: {:?}, metadata: {:?} }}",
+            self.key, self.metadata
+        )
+    }
 }
 
 impl SproutSpendingKey {
@@ -21,3 +34,20 @@ impl SproutSpendingKey {
         Self { key, metadata }
     }
 }
+
+/// Compares the keys in constant time.
+impl PartialEq for SproutSpendingKey {
+    fn eq(&self, other: &Self) -> bool {
+        let (key, other_key): (&[u8], &[u8]) = (self.key.as_ref(), other.key.as_ref());
+        bool::from(key.ct_eq(other_key)) && self.metadata == other.metadata
+    }
+}
+
+impl std::fmt::Debug for SproutSpendingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SproutSpendingKey")
+            .field("key", &Redacted(U252_SIZE))
+            .field("metadata", &self.metadata)
+            .finish()
+    }
+}
@@ -7,6 +7,7 @@ use zewif::Network;
 use crate::migrate::primitives::address_network_from_zewif;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeyId(u160);
 
 impl KeyId {
@@ -1,12 +1,14 @@
 use crate::{Error, Result};
 use crate::zcashd_wallet::{
     transparent::{PrivKey, PubKey},
-    u256, KeyMetadata,
+    u256, Address, KeyMetadata,
 };
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
 use sha2::{Digest, Sha256};
-use zewif::Data;
+use zewif::{Data, Network};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeyPair {
     pubkey: PubKey,
     privkey: PrivKey,
@@ -25,9 +27,72 @@ impl KeyPair {
     pub fn metadata(&self) -> &KeyMetadata {
         &self.metadata
     }
+
+    /// The P2PKH address of the public key on `network`.
+    pub fn address(&self, network: Network) -> Address {
+        Address::from(self.pubkey.key_id().to_string(network))
+    }
+
+    /// Checks that the public key is the one derived from the private key,
+    /// returning `Error::InvalidKeypair` if not.
+    pub fn verify(&self) -> Result<()> {
+        verify_keypair(&self.pubkey, &self.privkey)
+    }
+
+    /// The private key in the Wallet Import Format that zcashd's
+    /// `dumpprivkey` prints and `importprivkey` accepts.
+    pub fn to_wif(&self, network: Network) -> Result<String> {
+        wif(&self.pubkey, &self.privkey, network)
+    }
+}
+
+/// Base58Check-encodes the secret of `privkey` with the network's secret key
+/// prefix, flagged as compressed if `pubkey` is.
+pub(crate) fn wif(pubkey: &PubKey, privkey: &PrivKey, network: Network) -> Result<String> {
+    let prefix = match network {
+        Network::Main => 0x80,
+        Network::Test | Network::Regtest => 0xef,
+    };
+    let mut payload = vec![prefix];
+    payload.extend_from_slice(&privkey.secret_key_bytes()?);
+    if pubkey.is_compressed() {
+        payload.push(0x01);
+    }
+    Ok(bs58::encode(payload).with_check().into_string())
+}
+
+/// Derives the secp256k1 public key from `privkey` and compares it with
+/// `pubkey` in the same (compressed or uncompressed) encoding.
+pub(crate) fn verify_keypair(pubkey: &PubKey, privkey: &PrivKey) -> Result<()> {
+    let secret = SecretKey::from_slice(&privkey.secret_key_bytes()?)
+        .map_err(|_| Error::InvalidKeypair)?;
+    let derived = PublicKey::from_secret_key(&Secp256k1::signing_only(), &secret);
+    let matches = if pubkey.is_compressed() {
+        derived.serialize().as_slice() == pubkey.as_slice()
+    } else {
+        derived.serialize_uncompressed().as_slice() == pubkey.as_slice()
+    };
+    if !matches {
+        return Err(Error::InvalidKeypair);
+    }
+    Ok(())
 }
 
 impl KeyPair {
+    /// Builds the keypair zcashd would store for the secp256k1 `secret`, with
+    /// a compressed or uncompressed public key.
+    pub fn from_secret(secret: &[u8; 32], compressed: bool, metadata: KeyMetadata) -> Result<Self> {
+        let secret_key = SecretKey::from_slice(secret).map_err(|_| Error::InvalidKeypair)?;
+        let public_key = PublicKey::from_secret_key(&Secp256k1::signing_only(), &secret_key);
+        let pubkey = if compressed {
+            PubKey::new(Data::from_slice(&public_key.serialize()))
+        } else {
+            PubKey::new(Data::from_slice(&public_key.serialize_uncompressed()))
+        };
+        let privkey = PrivKey::from_secret(secret, &pubkey);
+        Self::new(pubkey, privkey, metadata)
+    }
+
     pub fn new(pubkey: PubKey, privkey: PrivKey, metadata: KeyMetadata) -> Result<Self> {
         let hash = hash256(Data::concat(&[&pubkey, &privkey]));
         if hash != privkey.hash() {
@@ -90,6 +155,6 @@ fn sha256(data: impl AsRef<[u8]>) -> u256 {
 ///
 /// # Returns
 /// A `u256` containing the 32-byte double hash result
-fn hash256(data: impl AsRef<[u8]>) -> u256 {
+pub(crate) fn hash256(data: impl AsRef<[u8]>) -> u256 {
     sha256(sha256(data))
 }
@@ -9,6 +9,7 @@ use crate::{
 use super::PubKey;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeyPoolEntry {
     version: ClientVersion,
     timestamp: SecondsSinceEpoch,
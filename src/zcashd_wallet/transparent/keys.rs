@@ -1,8 +1,14 @@
 use std::collections::HashMap;
 
+use crate::{
+    Result,
+    zcashd_wallet::{check_map, merge_map},
+};
+
 use super::{KeyPair, PubKey};
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Keys(HashMap<PubKey, KeyPair>);
 
 impl Keys {
@@ -10,6 +16,14 @@ impl Keys {
         Self(map)
     }
 
+    pub(crate) fn check_merge(&self, other: &Self) -> Result<()> {
+        check_map("transparent key", &self.0, &other.0)
+    }
+
+    pub(crate) fn merge(&mut self, other: Self) {
+        merge_map(&mut self.0, other.0)
+    }
+
     pub fn keypairs(&self) -> impl Iterator<Item = &KeyPair> {
         self.0.values()
     }
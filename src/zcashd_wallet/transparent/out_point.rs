@@ -29,3 +29,31 @@ impl Parse for OutPoint {
         })
     }
 }
+
+/// Serializes as `txid:vout`, so that outpoints can be used as JSON object keys.
+#[cfg(feature = "serde")]
+impl serde::Serialize for OutPoint {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{}:{}", self.txid, self.vout))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for OutPoint {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+        let (txid, vout) = value
+            .split_once(':')
+            .ok_or_else(|| D::Error::custom("expected \"txid:vout\""))?;
+        let txid = crate::serde_support::JsonKey::from_json_key(txid).map_err(D::Error::custom)?;
+        let vout = vout.parse().map_err(D::Error::custom)?;
+        Ok(Self { txid, vout })
+    }
+}
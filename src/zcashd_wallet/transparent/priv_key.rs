@@ -1,10 +1,15 @@
 use crate::{error::ExpectedLengths, parse, parser::prelude::*, Error, Result};
+use std::hash::{Hash, Hasher};
+use subtle::ConstantTimeEq;
 use zewif::Data;
 
-use crate::zcashd_wallet::{CompactSize, u256};
+use super::{PubKey, key_pair::hash256};
+use crate::zcashd_wallet::{CompactSize, Redacted, u256};
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PrivKey {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::secret_hex"))]
     data: Data,
     hash: u256,
 }
@@ -21,11 +26,129 @@ impl PrivKey {
     pub fn hash(&self) -> u256 {
         self.hash
     }
+
+    /// The `Debug` form with the key data shown, which `Debug` itself
+    /// redacts. Keep the result out of logs.
+    pub fn reveal(&self) -> String {
+        format!("PrivKey({:?})", self.data())
+    }
+
+    /// Extracts the 32-byte secp256k1 secret from the DER-encoded
+    /// `ECPrivateKey` structure zcashd stores.
+    pub fn secret_key_bytes(&self) -> Result<[u8; 32]> {
+        let der = self.as_slice();
+        let invalid = || Error::InvalidBitPattern { kind: "privkey DER" };
+        if der.first() != Some(&0x30) {
+            return Err(invalid());
+        }
+        // Skip the SEQUENCE length, which is in short or long form.
+        let mut offset = match der.get(1) {
+            Some(&len) if len & 0x80 == 0 => 2,
+            Some(&len) => 2 + (len & 0x7f) as usize,
+            None => return Err(invalid()),
+        };
+        // version INTEGER 1, then the privateKey OCTET STRING of 32 bytes.
+        if der.get(offset..offset + 5) != Some(&[0x02, 0x01, 0x01, 0x04, 0x20][..]) {
+            return Err(invalid());
+        }
+        offset += 5;
+        der.get(offset..offset + 32)
+            .and_then(|secret| secret.try_into().ok())
+            .ok_or_else(invalid)
+    }
+}
+
+/// The secp256k1 field prime, big-endian.
+const FIELD_PRIME: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe, 0xff, 0xff, 0xfc, 0x2f,
+];
+
+/// The secp256k1 generator in uncompressed form, without the `0x04` prefix.
+const GENERATOR: [u8; 64] = [
+    0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce, 0x87, 0x0b, 0x07,
+    0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16, 0xf8, 0x17, 0x98,
+    0x48, 0x3a, 0xda, 0x77, 0x26, 0xa3, 0xc4, 0x65, 0x5d, 0xa4, 0xfb, 0xfc, 0x0e, 0x11, 0x08, 0xa8,
+    0xfd, 0x17, 0xb4, 0x48, 0xa6, 0x85, 0x54, 0x19, 0x9c, 0x47, 0xd0, 0x8f, 0xfb, 0x10, 0xd4, 0xb8,
+];
+
+/// The order of the secp256k1 group.
+const GROUP_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+impl PrivKey {
+    /// Encodes `secret` the way zcashd stores it: a DER `ECPrivateKey` with
+    /// the explicit secp256k1 parameters and `pubkey`, whose form (compressed
+    /// or not) also sets the generator's. The hash is the one zcashd checks
+    /// the pair against.
+    pub(crate) fn from_secret(secret: &[u8; 32], pubkey: &PubKey) -> Self {
+        let compressed = pubkey.is_compressed();
+        let mut der = if compressed {
+            vec![0x30, 0x81, 0xd3, 0x02, 0x01, 0x01, 0x04, 0x20]
+        } else {
+            vec![0x30, 0x82, 0x01, 0x13, 0x02, 0x01, 0x01, 0x04, 0x20]
+        };
+        der.extend_from_slice(secret);
+        // [0] ECParameters: version, prime field, curve a = 0 and b = 7, the
+        // generator, the group order and cofactor 1.
+        der.extend_from_slice(if compressed {
+            &[0xa0, 0x81, 0x85, 0x30, 0x81, 0x82]
+        } else {
+            &[0xa0, 0x81, 0xa5, 0x30, 0x81, 0xa2]
+        });
+        der.extend_from_slice(&[0x02, 0x01, 0x01, 0x30, 0x2c, 0x06, 0x07]);
+        der.extend_from_slice(&[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x01, 0x01, 0x02, 0x21, 0x00]);
+        der.extend_from_slice(&FIELD_PRIME);
+        der.extend_from_slice(&[0x30, 0x06, 0x04, 0x01, 0x00, 0x04, 0x01, 0x07]);
+        if compressed {
+            der.extend_from_slice(&[0x04, 0x21, 0x02]);
+            der.extend_from_slice(&GENERATOR[..32]);
+        } else {
+            der.extend_from_slice(&[0x04, 0x41, 0x04]);
+            der.extend_from_slice(&GENERATOR);
+        }
+        der.extend_from_slice(&[0x02, 0x21, 0x00]);
+        der.extend_from_slice(&GROUP_ORDER);
+        der.extend_from_slice(&[0x02, 0x01, 0x01]);
+        // [1] publicKey BIT STRING.
+        der.extend_from_slice(if compressed {
+            &[0xa1, 0x24, 0x03, 0x22, 0x00]
+        } else {
+            &[0xa1, 0x44, 0x03, 0x42, 0x00]
+        });
+        der.extend_from_slice(pubkey.as_slice());
+
+        let data = Data::from_slice(&der);
+        let hash = hash256(Data::concat(&[pubkey, &data]));
+        Self { data, hash }
+    }
+}
+
+/// Compares in constant time, so that how long a comparison takes does not
+/// reveal how much of two keys agree.
+impl PartialEq for PrivKey {
+    fn eq(&self, other: &Self) -> bool {
+        let (hash, other_hash) = (self.hash.into_bytes(), other.hash.into_bytes());
+        (self.as_slice().ct_eq(other.as_slice()) & hash.as_slice().ct_eq(&other_hash)).into()
+    }
+}
+
+impl Eq for PrivKey {}
+
+impl Hash for PrivKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+        self.hash.hash(state);
+    }
 }
 
 impl std::fmt::Debug for PrivKey {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "PrivKey({:?})", self.data())
+        f.debug_tuple("PrivKey")
+            .field(&Redacted(self.as_slice().len()))
+            .finish()
     }
 }
 
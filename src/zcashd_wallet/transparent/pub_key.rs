@@ -1,7 +1,11 @@
 use crate::{error::ExpectedLengths, parse, parser::prelude::*, Error, Result, ResultExt};
 use zewif::Data;
 
-use crate::zcashd_wallet::CompactSize;
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+use super::KeyId;
+use crate::zcashd_wallet::{CompactSize, u160};
 
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct PubKey(Data);
@@ -10,6 +14,10 @@ impl PubKey {
     pub const PUBLIC_KEY_SIZE: usize = 65;
     pub const COMPRESSED_PUBLIC_KEY_SIZE: usize = 33;
 
+    pub(crate) fn new(data: Data) -> Self {
+        Self(data)
+    }
+
     pub fn as_slice(&self) -> &[u8] {
         self.0.as_ref()
     }
@@ -17,6 +25,20 @@ impl PubKey {
     pub fn is_compressed(&self) -> bool {
         self.0.as_slice().len() == Self::COMPRESSED_PUBLIC_KEY_SIZE
     }
+
+    /// Returns the `HASH160` of this key, which identifies its P2PKH address.
+    pub fn key_id(&self) -> KeyId {
+        let sha256 = Sha256::digest(self.as_slice());
+        let hash160 = Ripemd160::digest(sha256);
+        KeyId::from(u160::from_slice(&hash160).expect("RIPEMD-160 output is 20 bytes"))
+    }
+}
+
+/// An empty key, standing in for a `defaultkey` that could not be parsed.
+impl Default for PubKey {
+    fn default() -> Self {
+        Self(Data::new())
+    }
 }
 
 impl std::fmt::Debug for PubKey {
@@ -55,3 +77,26 @@ impl Parse for PubKey {
         Ok(Self(key_data))
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for PubKey {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(self.as_slice()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PubKey {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+        let bytes = hex::decode(value).map_err(D::Error::custom)?;
+        Ok(Self(Data::from_slice(&bytes)))
+    }
+}
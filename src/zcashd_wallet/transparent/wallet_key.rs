@@ -1,10 +1,19 @@
 use std::collections::HashMap;
 
-use crate::zcashd_wallet::SecondsSinceEpoch;
+use zewif::Network;
 
-use super::{PrivKey, PubKey};
+use crate::{
+    Result,
+    zcashd_wallet::{Address, SecondsSinceEpoch, check_map, merge_map},
+};
+
+use super::{
+    PrivKey, PubKey,
+    key_pair::{verify_keypair, wif},
+};
 
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WalletKeys(HashMap<PubKey, WalletKey>);
 
 impl WalletKeys {
@@ -12,9 +21,21 @@ impl WalletKeys {
         Self(map)
     }
 
+    pub(crate) fn check_merge(&self, other: &Self) -> Result<()> {
+        check_map("wallet key", &self.0, &other.0)
+    }
+
+    pub(crate) fn merge(&mut self, other: Self) {
+        merge_map(&mut self.0, other.0)
+    }
+
     pub fn keypairs(&self) -> impl Iterator<Item = &WalletKey> {
         self.0.values()
     }
+
+    pub fn keypair_for_pubkey(&self, pubkey: &PubKey) -> Option<&WalletKey> {
+        self.0.get(pubkey)
+    }
 }
 
 impl std::fmt::Debug for WalletKeys {
@@ -28,6 +49,7 @@ impl std::fmt::Debug for WalletKeys {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WalletKey {
     pubkey: PubKey,
     privkey: PrivKey,
@@ -72,4 +94,21 @@ impl WalletKey {
     pub fn comment(&self) -> &String {
         &self.comment
     }
+
+    /// The P2PKH address of the public key on `network`.
+    pub fn address(&self, network: Network) -> Address {
+        Address::from(self.pubkey.key_id().to_string(network))
+    }
+
+    /// Checks that the public key is the one derived from the private key,
+    /// returning `Error::InvalidKeypair` if not.
+    pub fn verify(&self) -> Result<()> {
+        verify_keypair(&self.pubkey, &self.privkey)
+    }
+
+    /// The private key in the Wallet Import Format that zcashd's
+    /// `dumpprivkey` prints and `importprivkey` accepts.
+    pub fn to_wif(&self, network: Network) -> Result<String> {
+        wif(&self.pubkey, &self.privkey, network)
+    }
 }
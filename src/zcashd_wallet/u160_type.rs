@@ -154,3 +154,26 @@ impl Parse for u160 {
         Ok(Self(blob.into()))
     }
 }
+
+/// Serializes as a hex string in the same byte order as `Display`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for u160 {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for u160 {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let hex = <String as serde::Deserialize>::deserialize(deserializer)?;
+        let mut bytes = hex::decode(hex).map_err(serde::de::Error::custom)?;
+        bytes.reverse();
+        Self::from_slice(&bytes).map_err(serde::de::Error::custom)
+    }
+}
@@ -128,3 +128,29 @@ impl Parse for u256 {
         Ok(Self(bytes))
     }
 }
+
+/// Serializes as a base-10 string.
+#[cfg(feature = "serde")]
+impl serde::Serialize for u256 {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        let mut bytes = self.0;
+        bytes.reverse();
+        serializer.serialize_str(&crate::serde_support::be_bytes_to_decimal(&bytes))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for u256 {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let decimal = <String as serde::Deserialize>::deserialize(deserializer)?;
+        let mut bytes = crate::serde_support::decimal_to_be_bytes::<U256_SIZE>(&decimal)
+            .ok_or_else(|| serde::de::Error::custom("invalid u256 decimal string"))?;
+        bytes.reverse();
+        Ok(Self(bytes))
+    }
+}
@@ -31,7 +31,9 @@ impl Parse for UfvkFingerprint {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnifiedAccountMetadata {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::hex"))]
     seed_fingerprint: SeedFingerprint,
     ufvk_fingerprint: UfvkFingerprint,
     bip_44_coin_type: u32,
@@ -54,6 +56,11 @@ impl UnifiedAccountMetadata {
     pub fn zip32_account_id(&self) -> u32 {
         self.zip32_account_id
     }
+
+    /// The ZIP 32 derivation path of this account, e.g. `m/32'/133'/0'`.
+    pub fn hd_keypath(&self) -> String {
+        format!("m/32'/{}'/{}'", self.bip_44_coin_type, self.zip32_account_id)
+    }
 }
 
 impl Parse for UnifiedAccountMetadata {
@@ -70,3 +77,26 @@ impl Parse for UnifiedAccountMetadata {
         })
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for UfvkFingerprint {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for UfvkFingerprint {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+        let bytes = hex::decode(value).map_err(D::Error::custom)?;
+        Self::from_bytes(&bytes).map_err(D::Error::custom)
+    }
+}
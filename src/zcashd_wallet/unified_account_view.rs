@@ -0,0 +1,60 @@
+use zcash_keys::keys::UnifiedFullViewingKey;
+
+use crate::zcashd_wallet::{UfvkFingerprint, UnifiedAccountMetadata, UnifiedAddressMetadata};
+
+/// A unified account together with its viewing key and addresses, joined by
+/// UFVK fingerprint from the maps in
+/// [`UnifiedAccounts`](crate::zcashd_wallet::UnifiedAccounts).
+#[derive(Debug, Clone)]
+pub struct UnifiedAccountView<'a> {
+    metadata: &'a UnifiedAccountMetadata,
+    ufvk: Option<&'a UnifiedFullViewingKey>,
+    addresses: Vec<&'a UnifiedAddressMetadata>,
+}
+
+impl<'a> UnifiedAccountView<'a> {
+    pub(crate) fn new(
+        metadata: &'a UnifiedAccountMetadata,
+        ufvk: Option<&'a UnifiedFullViewingKey>,
+        mut addresses: Vec<&'a UnifiedAddressMetadata>,
+    ) -> Self {
+        addresses.sort_by_key(|address| diversifier_index_value(address));
+        Self {
+            metadata,
+            ufvk,
+            addresses,
+        }
+    }
+
+    pub fn ufvk_fingerprint(&self) -> &'a UfvkFingerprint {
+        self.metadata.ufvk_fingerprint()
+    }
+
+    pub fn metadata(&self) -> &'a UnifiedAccountMetadata {
+        self.metadata
+    }
+
+    /// The ZIP 32 account index.
+    pub fn account_id(&self) -> u32 {
+        self.metadata.zip32_account_id()
+    }
+
+    /// The account's unified full viewing key, or `None` if the wallet has no
+    /// `unifiedfvk` record for it.
+    pub fn ufvk(&self) -> Option<&'a UnifiedFullViewingKey> {
+        self.ufvk
+    }
+
+    /// The metadata of the account's unified addresses, in diversifier index
+    /// order.
+    pub fn addresses(&self) -> &[&'a UnifiedAddressMetadata] {
+        &self.addresses
+    }
+}
+
+// Diversifier indices are 88-bit little-endian integers.
+fn diversifier_index_value(address: &UnifiedAddressMetadata) -> u128 {
+    let mut bytes = [0u8; 16];
+    bytes[..11].copy_from_slice(&<[u8; 11]>::from(address.diversifier_index.clone()));
+    u128::from_le_bytes(bytes)
+}
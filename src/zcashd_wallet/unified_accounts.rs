@@ -1,15 +1,31 @@
 use std::collections::HashMap;
-use zcash_keys::keys::UnifiedFullViewingKey;
+use zcash_keys::keys::{UnifiedAddressRequest, UnifiedFullViewingKey};
+use zewif::Network;
 
-use crate::zcashd_wallet::{UfvkFingerprint, UnifiedAccountMetadata, UnifiedAddressMetadata};
+use crate::{
+    Error, Result,
+    migrate::primitives::address_encoding_network,
+    zcashd_wallet::{
+        NetworkInfo, ReceiverType, UfvkFingerprint, UnifiedAccountMetadata, UnifiedAccountView,
+        UnifiedAddressMetadata,
+    },
+};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnifiedAccounts {
     pub address_metadata: Vec<UnifiedAddressMetadata>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::ufvk_values"))]
     pub full_viewing_keys: HashMap<UfvkFingerprint, UnifiedFullViewingKey>,
     pub account_metadata: HashMap<UfvkFingerprint, UnifiedAccountMetadata>,
 }
 
+impl Default for UnifiedAccounts {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
 impl UnifiedAccounts {
     pub fn none() -> Self {
         Self {
@@ -19,6 +35,12 @@ impl UnifiedAccounts {
         }
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.address_metadata.is_empty()
+            && self.full_viewing_keys.is_empty()
+            && self.account_metadata.is_empty()
+    }
+
     pub fn new(
         address_metadata: Vec<UnifiedAddressMetadata>,
         full_viewing_keys: HashMap<UfvkFingerprint, UnifiedFullViewingKey>,
@@ -30,4 +52,277 @@ impl UnifiedAccounts {
             account_metadata,
         }
     }
+
+    /// Each account with its viewing key and the addresses derived from it,
+    /// ordered by account index.
+    pub fn accounts(&self) -> Vec<UnifiedAccountView<'_>> {
+        let mut accounts: Vec<_> = self
+            .account_metadata
+            .iter()
+            .map(|(fingerprint, metadata)| {
+                let addresses = self
+                    .address_metadata
+                    .iter()
+                    .filter(|address| address.key_id == *fingerprint)
+                    .collect();
+                UnifiedAccountView::new(
+                    metadata,
+                    self.full_viewing_keys.get(fingerprint),
+                    addresses,
+                )
+            })
+            .collect();
+        accounts.sort_by_key(|account| (account.account_id(), account.ufvk_fingerprint().to_hex()));
+        accounts
+    }
+
+    /// Derives and encodes the unified address described by `metadata` from
+    /// the UFVK of its account.
+    ///
+    /// Fails with `Error::InvalidReceiverCombination` if the metadata's
+    /// receiver types include no shielded receiver or a receiver the UFVK has
+    /// no key for, and with `Error::AddressGeneration` if the UFVK cannot
+    /// produce an address at the stored diversifier index.
+    pub fn derive_address(
+        &self,
+        metadata: &UnifiedAddressMetadata,
+        network: Network,
+    ) -> Result<String> {
+        let ufvk = self
+            .full_viewing_keys
+            .get(&metadata.key_id)
+            .ok_or_else(|| Error::MissingUfvk {
+                fingerprint: metadata.key_id.to_hex(),
+            })?;
+        let j = metadata.diversifier_index();
+        for receiver_type in &metadata.receiver_types {
+            let has_key = match receiver_type {
+                ReceiverType::P2PKH => ufvk.transparent().is_some(),
+                ReceiverType::Sapling => ufvk.sapling().is_some(),
+                ReceiverType::Orchard => ufvk.orchard().is_some(),
+                // Not derived from the UFVK, so never requested.
+                ReceiverType::P2SH => true,
+            };
+            if !has_key {
+                return Err(Error::with_context(
+                    Error::InvalidReceiverCombination,
+                    format!(
+                        "The UFVK {} has no {} key",
+                        metadata.key_id.to_hex(),
+                        String::from(*receiver_type)
+                    ),
+                ));
+            }
+        }
+        let request = UnifiedAddressRequest::new(
+            metadata.receiver_types.contains(&ReceiverType::Orchard),
+            metadata.receiver_types.contains(&ReceiverType::Sapling),
+            metadata.receiver_types.contains(&ReceiverType::P2PKH),
+        )
+        .ok_or(Error::InvalidReceiverCombination)?;
+        let address = ufvk.address(j, request)?;
+        Ok(address.encode(&address_encoding_network(network)))
+    }
+
+    /// Derives and encodes the unified address described by `metadata`.
+    ///
+    /// Returns `None` if the metadata's receiver types do not form a valid
+    /// unified address request.
+    pub fn encode_address(
+        &self,
+        metadata: &UnifiedAddressMetadata,
+        network_info: &NetworkInfo,
+    ) -> Result<Option<String>> {
+        match self.derive_address(metadata, network_info.network()) {
+            Ok(address) => Ok(Some(address)),
+            Err(err) if is_invalid_receiver_combination(&err) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+fn is_invalid_receiver_combination(err: &Error) -> bool {
+    match err {
+        Error::InvalidReceiverCombination => true,
+        Error::Context { source, .. } => source
+            .downcast_ref::<Error>()
+            .is_some_and(is_invalid_receiver_combination),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use zcash_address::unified::{Container as _, Encoding as _, Fvk, Ufvk};
+    use zcash_keys::keys::{UnifiedAddressRequest, UnifiedFullViewingKey, UnifiedSpendingKey};
+    use zcash_protocol::consensus::MainNetwork;
+    use zewif::Network;
+    use zip32::DiversifierIndex;
+
+    use super::UnifiedAccounts;
+    use crate::{
+        Error, parse,
+        zcashd_wallet::{
+            NetworkInfo, ReceiverType, UfvkFingerprint, UnifiedAccountMetadata,
+            UnifiedAddressMetadata,
+        },
+    };
+
+    fn account_metadata(account_id: u32, fingerprint: u8) -> UnifiedAccountMetadata {
+        let mut data = vec![0x01; 32];
+        data.extend_from_slice(&133u32.to_le_bytes());
+        data.extend_from_slice(&account_id.to_le_bytes());
+        data.extend_from_slice(&[fingerprint; 32]);
+        parse!(buf = &data, UnifiedAccountMetadata, "account").unwrap()
+    }
+
+    fn address_metadata(fingerprint: u8, diversifier_index: u64) -> UnifiedAddressMetadata {
+        let mut data = vec![fingerprint; 32];
+        data.extend_from_slice(&diversifier_index.to_le_bytes());
+        data.extend_from_slice(&[0; 3]);
+        // Sapling and Orchard receivers.
+        data.extend_from_slice(&[2, 2, 3]);
+        parse!(buf = &data, UnifiedAddressMetadata, "address").unwrap()
+    }
+
+    #[test]
+    fn test_derive_address() {
+        let account = zip32::AccountId::try_from(0).unwrap();
+        let ufvk = UnifiedSpendingKey::from_seed(&MainNetwork, &[0x07; 32], account)
+            .unwrap()
+            .to_unified_full_viewing_key();
+        let request = UnifiedAddressRequest::new(true, true, false).unwrap();
+        let (expected, j) = ufvk.default_address(request).unwrap();
+        let accounts = UnifiedAccounts::new(
+            vec![],
+            HashMap::from([(UfvkFingerprint::new([0xaa; 32]), ufvk)]),
+            HashMap::new(),
+        );
+
+        let mut data = vec![0xaa; 32];
+        data.extend_from_slice(j.as_bytes());
+        // Sapling and Orchard receivers.
+        data.extend_from_slice(&[2, 2, 3]);
+        let metadata = parse!(buf = &data, UnifiedAddressMetadata, "address").unwrap();
+        assert_eq!(
+            accounts.derive_address(&metadata, Network::Main).unwrap(),
+            expected.encode(&MainNetwork)
+        );
+
+        let mut transparent_only = metadata.clone();
+        transparent_only.receiver_types = HashSet::from([ReceiverType::P2PKH]);
+        assert!(matches!(
+            accounts.derive_address(&transparent_only, Network::Main),
+            Err(Error::InvalidReceiverCombination)
+        ));
+    }
+
+    #[test]
+    fn test_derive_address_without_orchard_key() {
+        let account = zip32::AccountId::try_from(0).unwrap();
+        let full = UnifiedSpendingKey::from_seed(&MainNetwork, &[0x07; 32], account)
+            .unwrap()
+            .to_unified_full_viewing_key();
+        let (_, encoded) = Ufvk::decode(&full.encode(&MainNetwork)).unwrap();
+        let items = encoded
+            .items()
+            .into_iter()
+            .filter(|item| !matches!(item, Fvk::Orchard(_)))
+            .collect();
+        let ufvk = UnifiedFullViewingKey::parse(&Ufvk::try_from_items(items).unwrap()).unwrap();
+        assert!(ufvk.orchard().is_none());
+        let accounts = UnifiedAccounts::new(
+            vec![],
+            HashMap::from([(UfvkFingerprint::new([0xaa; 32]), ufvk)]),
+            HashMap::new(),
+        );
+
+        let mut data = vec![0xaa; 32];
+        data.extend_from_slice(&[0; 11]);
+        // Sapling and Orchard receivers.
+        data.extend_from_slice(&[2, 2, 3]);
+        let metadata = parse!(buf = &data, UnifiedAddressMetadata, "address").unwrap();
+        let err = accounts
+            .derive_address(&metadata, Network::Main)
+            .unwrap_err();
+        assert!(err.to_string().contains("has no Orchard key"));
+        assert!(matches!(
+            &err,
+            Error::Context { source, .. }
+                if matches!(source.downcast_ref::<Error>(), Some(Error::InvalidReceiverCombination))
+        ));
+        assert_eq!(
+            accounts
+                .encode_address(&metadata, &NetworkInfo::default())
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_accounts() {
+        let account = zip32::AccountId::try_from(1).unwrap();
+        let ufvk = UnifiedSpendingKey::from_seed(&MainNetwork, &[0x07; 32], account)
+            .unwrap()
+            .to_unified_full_viewing_key();
+        let accounts = UnifiedAccounts::new(
+            vec![
+                address_metadata(0xbb, 0x1_0000_0000),
+                address_metadata(0xaa, 3),
+                address_metadata(0xbb, 7),
+            ],
+            HashMap::from([(UfvkFingerprint::new([0xbb; 32]), ufvk.clone())]),
+            HashMap::from([
+                (UfvkFingerprint::new([0xbb; 32]), account_metadata(1, 0xbb)),
+                (UfvkFingerprint::new([0xaa; 32]), account_metadata(0, 0xaa)),
+            ]),
+        );
+
+        let views = accounts.accounts();
+        assert_eq!(views.len(), 2);
+
+        assert_eq!(views[0].account_id(), 0);
+        assert!(views[0].ufvk().is_none());
+        assert_eq!(views[0].addresses().len(), 1);
+
+        assert_eq!(views[1].account_id(), 1);
+        assert_eq!(
+            views[1].ufvk().map(|key| key.encode(&MainNetwork)),
+            Some(ufvk.encode(&MainNetwork))
+        );
+        let diversifier_indices: Vec<[u8; 11]> = views[1]
+            .addresses()
+            .iter()
+            .map(|address| address.diversifier_index.clone().into())
+            .collect();
+        assert_eq!(
+            diversifier_indices,
+            [
+                [7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_address_diversifier_index_and_account() {
+        let accounts = UnifiedAccounts::new(
+            vec![],
+            HashMap::new(),
+            HashMap::from([(UfvkFingerprint::new([0xbb; 32]), account_metadata(4, 0xbb))]),
+        );
+
+        let address = address_metadata(0xbb, 0x1_0000_0007);
+        assert_eq!(
+            address.diversifier_index(),
+            DiversifierIndex::from(0x1_0000_0007u64)
+        );
+        assert_eq!(address.account_id(&accounts), Some(4));
+
+        let unknown_account = address_metadata(0xaa, 0);
+        assert_eq!(unknown_account.diversifier_index(), DiversifierIndex::new());
+        assert_eq!(unknown_account.account_id(&accounts), None);
+    }
 }
@@ -1,20 +1,42 @@
 use crate::Result;
 use std::collections::HashSet;
 use zewif::Blob;
+use zip32::DiversifierIndex;
 
 use crate::{
     parse,
     parser::prelude::*,
-    zcashd_wallet::{ReceiverType, UfvkFingerprint},
+    zcashd_wallet::{ReceiverType, UfvkFingerprint, UnifiedAccountMetadata, UnifiedAccounts},
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnifiedAddressMetadata {
     pub key_id: UfvkFingerprint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::hex"))]
     pub diversifier_index: Blob<11>,
     pub receiver_types: HashSet<ReceiverType>,
 }
 
+impl UnifiedAddressMetadata {
+    /// The ZIP 32 diversifier index the address was derived at.
+    pub fn diversifier_index(&self) -> DiversifierIndex {
+        DiversifierIndex::from(<[u8; 11]>::from(self.diversifier_index.clone()))
+    }
+
+    /// The ZIP 32 index of the account the address belongs to, or `None` if
+    /// `accounts` has no metadata for its UFVK.
+    ///
+    /// The address record names its account only by UFVK fingerprint, so the
+    /// index is looked up in the wallet's unified accounts.
+    pub fn account_id(&self, accounts: &UnifiedAccounts) -> Option<u32> {
+        accounts
+            .account_metadata
+            .get(&self.key_id)
+            .map(UnifiedAccountMetadata::zip32_account_id)
+    }
+}
+
 impl Parse for UnifiedAddressMetadata {
     fn parse(p: &mut Parser) -> Result<Self> {
         let key_id = parse!(p, "key_id")?;
@@ -0,0 +1,134 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use zewif::{Bip39Mnemonic, LegacySeed, Script, TxId, sapling::SaplingIncomingViewingKey};
+
+use crate::{
+    ZcashdWallet,
+    zcashd_wallet::{
+        AccountingEntry, Address, AddressNames, AddressPurposes, BlockLocator, ClientVersion,
+        LegacyAccount, LegacyHDChain, MnemonicHDChain, NetworkInfo, RecipientMapping,
+        UnifiedAccounts, WalletTx,
+        orchard::OrchardNoteCommitmentTree,
+        sapling::{SaplingKeys, SaplingZPaymentAddress},
+        sprout::SproutKeys,
+        transparent::{KeyPoolEntry, Keys, PubKey, WalletKeys},
+    },
+};
+
+macro_rules! setters {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        $(
+            pub fn $field(mut self, $field: $ty) -> Self {
+                self.wallet.$field = $field;
+                self
+            }
+        )*
+    };
+}
+
+/// Builds a [`ZcashdWallet`] field by field, leaving every field not set at
+/// the value the parser uses for a missing record.
+///
+/// ```
+/// # use zewif_zcashd::{ZcashdWallet, zcashd_wallet::NetworkInfo};
+/// let wallet = ZcashdWallet::builder()
+///     .network_info(NetworkInfo::default())
+///     .witnesscachesize(100)
+///     .build();
+/// assert!(wallet.transactions().is_empty());
+/// ```
+#[derive(Debug, Default)]
+pub struct ZcashdWalletBuilder {
+    wallet: ZcashdWallet,
+}
+
+impl ZcashdWalletBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    setters! {
+        accounting_entries: HashMap<String, BTreeMap<u64, AccountingEntry>>,
+        address_names: AddressNames,
+        address_purposes: AddressPurposes,
+        bestblock_nomerkle: Option<BlockLocator>,
+        bestblock: BlockLocator,
+        client_version: ClientVersion,
+        default_key: PubKey,
+        dest_data: HashMap<Address, HashMap<String, String>>,
+        key_pool: HashMap<i64, KeyPoolEntry>,
+        keys: Keys,
+        min_version: ClientVersion,
+        legacy_accounts: HashMap<String, LegacyAccount>,
+        legacy_hd_chain: Option<LegacyHDChain>,
+        legacy_hd_seed: Option<LegacySeed>,
+        mnemonic_hd_chain: Option<MnemonicHDChain>,
+        bip39_mnemonic: Option<Bip39Mnemonic>,
+        network_info: NetworkInfo,
+        orchard_note_commitment_tree: OrchardNoteCommitmentTree,
+        orderposnext: Option<i64>,
+        sapling_extended_fvks:
+            HashMap<SaplingZPaymentAddress, ::sapling::zip32::ExtendedFullViewingKey>,
+        sapling_keys: SaplingKeys,
+        sapling_z_addresses: HashMap<SaplingZPaymentAddress, SaplingIncomingViewingKey>,
+        send_recipients: HashMap<TxId, Vec<RecipientMapping>>,
+        sprout_keys: Option<SproutKeys>,
+        wallet_keys: Option<WalletKeys>,
+        transactions: HashMap<TxId, WalletTx>,
+        unified_accounts: UnifiedAccounts,
+        viewing_keys: Vec<::sapling::zip32::ExtendedFullViewingKey>,
+        watch_only_scripts: HashSet<Script>,
+        witnesscachesize: i64,
+    }
+
+    pub fn build(self) -> ZcashdWallet {
+        self.wallet
+    }
+}
+
+impl ZcashdWallet {
+    pub fn builder() -> ZcashdWalletBuilder {
+        ZcashdWalletBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use zewif::Network;
+
+    use crate::{
+        ZcashdParser, ZcashdWallet, parse,
+        test_support::{compact, dump_from_records, named_address},
+        zcashd_wallet::{Address, AddressNames, NetworkInfo},
+    };
+
+    #[test]
+    fn test_wallet_builder() {
+        let mut network_info = compact(b"Zcash");
+        network_info.extend(compact(b"test"));
+        let network_info = parse!(buf = &network_info, NetworkInfo, "network info").unwrap();
+        let address = Address::from(named_address());
+        let wallet = ZcashdWallet::builder()
+            .network_info(network_info)
+            .address_names(AddressNames::new(HashMap::from([(
+                address.clone(),
+                "savings".to_string(),
+            )])))
+            .orderposnext(Some(3))
+            .build();
+        assert_eq!(wallet.network(), Network::Test);
+        assert_eq!(wallet.address_names()[&address], "savings");
+        assert_eq!(wallet.orderposnext(), Some(3));
+        assert!(wallet.keys().is_empty());
+        assert!(wallet.transactions().is_empty());
+
+        // Fields left unset match a dump with no records.
+        let (parsed, _) = ZcashdParser::parse_best_effort(&dump_from_records(&[]));
+        let built = ZcashdWallet::builder().build();
+        assert!(built.diff(&parsed).is_empty());
+        assert_eq!(built.network(), parsed.network());
+        assert_eq!(built.witnesscachesize(), parsed.witnesscachesize());
+    }
+}
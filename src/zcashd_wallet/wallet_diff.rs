@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use crate::{
+    ZcashdWallet,
+    zcashd_wallet::{
+        AddressEntry, WalletTx,
+        sapling::SaplingKey,
+        sprout::{SproutKeys, SproutSpendingKey},
+        transparent::{KeyPair, WalletKey, WalletKeys},
+    },
+};
+
+/// The entries of one kind that differ between two wallets, each named by a
+/// string identifier and listed in sorted order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryDiff {
+    /// Entries only in the second wallet.
+    pub added: Vec<String>,
+    /// Entries only in the first wallet.
+    pub removed: Vec<String>,
+    /// Entries in both wallets whose contents differ.
+    pub changed: Vec<String>,
+}
+
+impl EntryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    fn add<V: PartialEq>(&mut self, before: HashMap<String, V>, mut after: HashMap<String, V>) {
+        for (id, value) in before {
+            match after.remove(&id) {
+                None => self.removed.push(id),
+                Some(other) if other != value => self.changed.push(id),
+                Some(_) => {}
+            }
+        }
+        self.added.extend(after.into_keys());
+    }
+
+    fn sorted(mut self) -> Self {
+        self.added.sort();
+        self.removed.sort();
+        self.changed.sort();
+        self
+    }
+}
+
+/// The differences between two parsed wallets, as produced by
+/// [`ZcashdWallet::diff`].
+///
+/// Keys are identified by pool: `transparent:` and `sapling:` followed by
+/// the hex of the public key or incoming viewing key, and `sprout:` followed
+/// by the payment address. Addresses are identified by their encoding and
+/// transactions by their ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WalletDiff {
+    pub keys: EntryDiff,
+    pub addresses: EntryDiff,
+    pub transactions: EntryDiff,
+}
+
+impl WalletDiff {
+    /// Whether the two wallets have the same keys, addresses and
+    /// transactions.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.addresses.is_empty() && self.transactions.is_empty()
+    }
+
+    pub(crate) fn compute(before: &ZcashdWallet, after: &ZcashdWallet) -> Self {
+        let mut keys = EntryDiff::default();
+        keys.add(transparent_keys(before), transparent_keys(after));
+        keys.add(wallet_keys(before), wallet_keys(after));
+        keys.add(sapling_keys(before), sapling_keys(after));
+        keys.add(sprout_keys(before), sprout_keys(after));
+
+        let mut addresses = EntryDiff::default();
+        addresses.add(addresses_of(before), addresses_of(after));
+
+        let mut transactions = EntryDiff::default();
+        transactions.add(transactions_of(before), transactions_of(after));
+
+        Self {
+            keys: keys.sorted(),
+            addresses: addresses.sorted(),
+            transactions: transactions.sorted(),
+        }
+    }
+}
+
+fn key_id(pool: &str, bytes: &[u8]) -> String {
+    format!("{pool}:{}", hex::encode(bytes))
+}
+
+fn transparent_keys(wallet: &ZcashdWallet) -> HashMap<String, &KeyPair> {
+    wallet
+        .keys()
+        .keypairs()
+        .map(|key| (key_id("transparent", key.pubkey().as_slice()), key))
+        .collect()
+}
+
+fn wallet_keys(wallet: &ZcashdWallet) -> HashMap<String, &WalletKey> {
+    wallet
+        .wallet_keys()
+        .into_iter()
+        .flat_map(WalletKeys::keypairs)
+        .map(|key| (key_id("transparent", key.pubkey().as_slice()), key))
+        .collect()
+}
+
+fn sapling_keys(wallet: &ZcashdWallet) -> HashMap<String, &SaplingKey> {
+    wallet
+        .sapling_keys()
+        .keypairs()
+        .map(|key| (key_id("sapling", key.ivk().as_bytes()), key))
+        .collect()
+}
+
+fn sprout_keys(wallet: &ZcashdWallet) -> HashMap<String, &SproutSpendingKey> {
+    let network = wallet.network();
+    wallet
+        .sprout_keys()
+        .into_iter()
+        .flat_map(SproutKeys::iter)
+        .map(|(address, key)| (format!("sprout:{}", address.to_string(network)), key))
+        .collect()
+}
+
+fn addresses_of(wallet: &ZcashdWallet) -> HashMap<String, AddressEntry> {
+    wallet
+        .all_addresses()
+        .into_iter()
+        .map(|entry| (entry.address().to_string(), entry))
+        .collect()
+}
+
+fn transactions_of(wallet: &ZcashdWallet) -> HashMap<String, &WalletTx> {
+    wallet
+        .transactions()
+        .iter()
+        .map(|(txid, tx)| (txid.to_string(), tx))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        minimal_wallet, minimal_wallet_records, parse_records, txid, v1_transaction,
+        wallet_tx_value,
+    };
+
+    #[test]
+    fn test_wallet_diff() {
+        let before = minimal_wallet();
+        let mut records = minimal_wallet_records();
+        records.push((
+            "tx",
+            vec![0x10; 32],
+            wallet_tx_value(&v1_transaction(), None),
+        ));
+        let after = parse_records(&records);
+
+        assert!(before.diff(&before).is_empty());
+        let diff = after.diff(&before);
+        assert!(diff.keys.is_empty() && diff.addresses.is_empty());
+        let txid = txid(0x10);
+        assert_eq!(diff.transactions.removed, [txid.to_string()]);
+
+        let diff = before.diff(&after);
+        assert!(diff.keys.is_empty() && diff.addresses.is_empty());
+        assert_eq!(diff.transactions.added, [txid.to_string()]);
+        assert!(diff.transactions.removed.is_empty() && diff.transactions.changed.is_empty());
+    }
+}
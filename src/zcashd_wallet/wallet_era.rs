@@ -0,0 +1,58 @@
+use crate::zcashd_wallet::ClientVersion;
+
+/// The on-disk generation of a zcashd wallet.
+///
+/// Each era adds records the migration has to account for; see
+/// [`ZcashdWallet::era`](crate::ZcashdWallet::era).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WalletEra {
+    /// Written before zcashd 5.0.0: keys come from the legacy `hdseed`, with
+    /// no mnemonic phrase or unified accounts.
+    Legacy,
+    /// zcashd 5.x: a BIP 39 mnemonic (`mnemonichdchain`) and unified accounts.
+    Mnemonic,
+    /// zcashd 6.0.0 and later: the best block is also recorded without Merkle
+    /// data in `bestblock_nomerkle`.
+    NoMerkle,
+}
+
+impl WalletEra {
+    /// Classifies a wallet by its marker records, falling back to the version
+    /// of the client that last wrote it.
+    pub fn detect(
+        client_version: &ClientVersion,
+        has_mnemonic_hd_chain: bool,
+        has_bestblock_nomerkle: bool,
+    ) -> Self {
+        if has_bestblock_nomerkle || client_version.major() >= 6 {
+            WalletEra::NoMerkle
+        } else if has_mnemonic_hd_chain || client_version.major() >= 5 {
+            WalletEra::Mnemonic
+        } else {
+            WalletEra::Legacy
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WalletEra;
+    use crate::zcashd_wallet::ClientVersion;
+
+    #[test]
+    fn test_detect_by_version() {
+        let era = |version| WalletEra::detect(&ClientVersion::from_integer(version), false, false);
+        assert_eq!(era(4_070_050), WalletEra::Legacy);
+        assert_eq!(era(5_000_050), WalletEra::Mnemonic);
+        assert_eq!(era(6_020_050), WalletEra::NoMerkle);
+    }
+
+    #[test]
+    fn test_detect_by_marker_records() {
+        let legacy = ClientVersion::from_integer(4_070_050);
+        assert_eq!(WalletEra::detect(&legacy, true, false), WalletEra::Mnemonic);
+        assert_eq!(WalletEra::detect(&legacy, true, true), WalletEra::NoMerkle);
+        assert_eq!(WalletEra::detect(&legacy, false, true), WalletEra::NoMerkle);
+    }
+}
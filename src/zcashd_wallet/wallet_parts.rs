@@ -0,0 +1,139 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use zewif::{Bip39Mnemonic, LegacySeed, Script, TxId, sapling::SaplingIncomingViewingKey};
+
+use crate::zcashd_wallet::{
+    AccountingEntry, Address, AddressNames, AddressPurposes, BlockLocator, ClientVersion,
+    LegacyAccount, LegacyHDChain, MnemonicHDChain, NetworkInfo, RecipientMapping, UnifiedAccounts,
+    WalletTx,
+    orchard::OrchardNoteCommitmentTree,
+    sapling::{SaplingKeys, SaplingZPaymentAddress},
+    sprout::SproutKeys,
+    transparent::{KeyPoolEntry, Keys, PubKey, WalletKeys},
+};
+
+/// The keys, seeds and viewing keys of a [`ZcashdWallet`], for
+/// [`ZcashdWallet::new`].
+///
+/// [`ZcashdWallet`]: crate::ZcashdWallet
+#[derive(Debug, Default)]
+pub struct KeyMaterial {
+    pub bip39_mnemonic: Option<Bip39Mnemonic>,
+    pub default_key: PubKey,
+    pub key_pool: HashMap<i64, KeyPoolEntry>,
+    pub keys: Keys,
+    pub legacy_hd_chain: Option<LegacyHDChain>,
+    pub legacy_hd_seed: Option<LegacySeed>,
+    pub mnemonic_hd_chain: Option<MnemonicHDChain>,
+    pub sapling_extended_fvks:
+        HashMap<SaplingZPaymentAddress, ::sapling::zip32::ExtendedFullViewingKey>,
+    pub sapling_keys: SaplingKeys,
+    pub sapling_z_addresses: HashMap<SaplingZPaymentAddress, SaplingIncomingViewingKey>,
+    pub sprout_keys: Option<SproutKeys>,
+    pub unified_accounts: UnifiedAccounts,
+    pub viewing_keys: Vec<::sapling::zip32::ExtendedFullViewingKey>,
+    pub wallet_keys: Option<WalletKeys>,
+    pub watch_only_scripts: HashSet<Script>,
+}
+
+/// The address book, versions and network of a [`ZcashdWallet`], for
+/// [`ZcashdWallet::new`].
+///
+/// Address names and purposes have types of their own, so one cannot be
+/// passed for the other:
+///
+/// ```compile_fail
+/// # use zewif_zcashd::zcashd_wallet::{AddressPurposes, Metadata};
+/// let metadata = Metadata {
+///     address_names: AddressPurposes::default(),
+///     ..Default::default()
+/// };
+/// ```
+///
+/// [`ZcashdWallet`]: crate::ZcashdWallet
+#[derive(Debug, Default)]
+pub struct Metadata {
+    pub address_names: AddressNames,
+    pub address_purposes: AddressPurposes,
+    pub client_version: ClientVersion,
+    pub dest_data: HashMap<Address, HashMap<String, String>>,
+    pub legacy_accounts: HashMap<String, LegacyAccount>,
+    pub min_version: ClientVersion,
+    pub network_info: NetworkInfo,
+}
+
+/// What a [`ZcashdWallet`] records about the chain it has scanned, for
+/// [`ZcashdWallet::new`].
+///
+/// [`ZcashdWallet`]: crate::ZcashdWallet
+#[derive(Debug, Default)]
+pub struct ChainState {
+    pub bestblock: BlockLocator,
+    pub bestblock_nomerkle: Option<BlockLocator>,
+    pub orchard_note_commitment_tree: OrchardNoteCommitmentTree,
+    pub witnesscachesize: i64,
+}
+
+/// The transactions and accounting history of a [`ZcashdWallet`], for
+/// [`ZcashdWallet::new`].
+///
+/// [`ZcashdWallet`]: crate::ZcashdWallet
+#[derive(Debug, Default)]
+pub struct Transactions {
+    pub accounting_entries: HashMap<String, BTreeMap<u64, AccountingEntry>>,
+    pub orderposnext: Option<i64>,
+    pub send_recipients: HashMap<TxId, Vec<RecipientMapping>>,
+    pub transactions: HashMap<TxId, WalletTx>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use zewif::{Data, Script};
+
+    use crate::{
+        ZcashdWallet,
+        test_support::named_address,
+        zcashd_wallet::{
+            Address, AddressNames, AddressPurposes, ChainState, KeyMaterial, Metadata, Purpose,
+            Transactions,
+        },
+    };
+
+    #[test]
+    fn test_wallet_from_parts() {
+        let address = Address::from(named_address());
+        let key_material = KeyMaterial {
+            watch_only_scripts: HashSet::from([Script::from(Data::from_slice(&[0x51]))]),
+            ..Default::default()
+        };
+        let metadata = Metadata {
+            address_names: AddressNames::new(HashMap::from([(
+                address.clone(),
+                "savings".to_string(),
+            )])),
+            address_purposes: AddressPurposes::new(HashMap::from([(
+                address.clone(),
+                Purpose::Receive,
+            )])),
+            ..Default::default()
+        };
+        let chain_state = ChainState {
+            witnesscachesize: 100,
+            ..Default::default()
+        };
+        let transactions = Transactions {
+            orderposnext: Some(3),
+            ..Default::default()
+        };
+
+        let wallet = ZcashdWallet::new(key_material, metadata, chain_state, transactions);
+        assert_eq!(wallet.watch_only_scripts().len(), 1);
+        assert_eq!(wallet.address_names()[&address], "savings");
+        assert_eq!(wallet.address_purposes()[&address], Purpose::Receive);
+        assert_eq!(wallet.witnesscachesize(), 100);
+        assert_eq!(wallet.orderposnext(), Some(3));
+        assert!(wallet.transactions().is_empty());
+    }
+}
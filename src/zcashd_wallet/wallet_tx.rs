@@ -1,24 +1,33 @@
 use crate::Result;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use zcash_primitives::transaction::Transaction;
-use zewif::{BlockHash, Data};
+use zewif::{Blob32, BlockHash, BlockHeight, Data};
 
 use super::{
-    CompactSize,
+    CompactSize, OrchardActionView, SaplingOutputView, TransparentOutputView,
     orchard::OrchardTxMeta,
     sapling::SaplingNoteData,
-    sprout::{JSOutPoint, SproutNoteData},
+    sprout::{JSOutPoint, JoinSplitDescription, SproutNoteData},
     transparent::SaplingOutPoint,
     u256,
 };
 use crate::{parse, parser::prelude::*};
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WalletTx {
     // CTransaction
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::hex"))]
     transaction: Transaction,
+    // The transaction's bytes as they appear in the record.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "Data::new", with = "crate::serde_support::hex")
+    )]
+    raw_transaction: Data,
 
     // CMerkleTx
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::json_key"))]
     hash_block: BlockHash,
     merkle_branch: Vec<u256>,
     index: i32,
@@ -34,6 +43,12 @@ pub struct WalletTx {
     sapling_note_data: Option<HashMap<SaplingOutPoint, SaplingNoteData>>,
     orchard_tx_meta: Option<OrchardTxMeta>,
 
+    // Not serialized by zcashd; derived from the nullifiers revealed across
+    // the wallet's transactions once they have all been parsed.
+    #[cfg_attr(feature = "serde", serde(default))]
+    spent_sapling_outputs: HashSet<u32>,
+
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::hex"))]
     unparsed_data: Data,
 }
 
@@ -42,6 +57,12 @@ impl WalletTx {
         &self.transaction
     }
 
+    /// The serialized transaction exactly as zcashd stored it, ready to be
+    /// re-broadcast or parsed again.
+    pub fn raw_bytes(&self) -> &[u8] {
+        self.raw_transaction.as_ref()
+    }
+
     pub fn hash_block(&self) -> BlockHash {
         self.hash_block
     }
@@ -58,6 +79,12 @@ impl WalletTx {
         &self.map_value
     }
 
+    /// The transaction's position in the wallet's history, which zcashd
+    /// keeps as a decimal string under `n` in `mapValue`.
+    pub fn order_pos(&self) -> Option<i64> {
+        self.map_value.get("n")?.parse().ok()
+    }
+
     pub fn map_sprout_note_data(&self) -> &HashMap<JSOutPoint, SproutNoteData> {
         &self.map_sprout_note_data
     }
@@ -90,29 +117,208 @@ impl WalletTx {
         self.orchard_tx_meta.as_ref()
     }
 
+    /// The transaction's transparent outputs, in order.
+    pub fn transparent_outputs(&self) -> Vec<TransparentOutputView<'_>> {
+        self.transaction
+            .transparent_bundle()
+            .map(|bundle| {
+                (0u32..)
+                    .zip(&bundle.vout)
+                    .map(|(index, output)| TransparentOutputView::new(index, output))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The transaction's Sapling outputs, in order, each with the note data
+    /// the wallet recorded for it.
+    pub fn sapling_outputs(&self) -> Vec<SaplingOutputView<'_>> {
+        let note_data: HashMap<u32, &SaplingNoteData> = self
+            .sapling_note_data
+            .iter()
+            .flatten()
+            .map(|(outpoint, data)| (outpoint.vout(), data))
+            .collect();
+        self.transaction
+            .sapling_bundle()
+            .map(|bundle| {
+                (0u32..)
+                    .zip(bundle.shielded_outputs())
+                    .map(|(index, output)| {
+                        SaplingOutputView::new(index, output, note_data.get(&index).copied())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The transaction's Orchard actions, in order, each with the incoming
+    /// viewing key that received it if the wallet did.
+    pub fn orchard_actions(&self) -> Vec<OrchardActionView<'_>> {
+        self.transaction
+            .orchard_bundle()
+            .map(|bundle| {
+                (0u32..)
+                    .zip(bundle.actions().iter())
+                    .map(|(index, action)| {
+                        let receiving_key = self
+                            .orchard_tx_meta
+                            .as_ref()
+                            .and_then(|meta| meta.receiving_key(index));
+                        OrchardActionView::new(index, action, receiving_key)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The transaction's Sprout JoinSplit descriptions, in order.
+    pub fn joinsplits(&self) -> Vec<JoinSplitDescription> {
+        let use_groth = self.transaction.version().has_sapling();
+        self.transaction
+            .sprout_bundle()
+            .map(|bundle| {
+                bundle
+                    .joinsplits
+                    .iter()
+                    .map(|joinsplit| {
+                        let mut bytes = vec![];
+                        joinsplit
+                            .write(&mut bytes)
+                            .expect("writing to a Vec cannot fail");
+                        parse!(
+                            buf = &bytes,
+                            JoinSplitDescription,
+                            param = use_groth,
+                            "JoinSplit description"
+                        )
+                        .expect("a JoinSplit is written in the layout it was read from")
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The nullifiers revealed by the transaction's Sapling spends.
+    pub fn sapling_nullifiers(&self) -> Vec<Blob32> {
+        self.transaction
+            .sapling_bundle()
+            .map(|bundle| {
+                bundle
+                    .shielded_spends()
+                    .iter()
+                    .map(|spend| Blob32::new(spend.nullifier().0))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether the Sapling note received at output `index` has been spent by
+    /// a transaction in the wallet, or `None` if the wallet has no note data
+    /// with a nullifier for that output.
+    pub fn is_output_spent(&self, index: u32) -> Option<bool> {
+        self.sapling_note_data
+            .iter()
+            .flatten()
+            .find(|(outpoint, _)| outpoint.vout() == index)
+            .and_then(|(_, data)| data.nullifier())
+            .map(|_| self.spent_sapling_outputs.contains(&index))
+    }
+
+    /// Whether the transaction is a coinbase, whose only transparent input
+    /// spends the null outpoint.
+    pub fn is_coinbase(&self) -> bool {
+        self.transaction.transparent_bundle().is_some_and(|bundle| {
+            matches!(
+                bundle.vin.as_slice(),
+                [input] if *input.prevout.hash() == [0; 32] && input.prevout.n() == u32::MAX
+            )
+        })
+    }
+
+    /// For a coinbase, the first block height whose blocks may spend its
+    /// outputs: 100 blocks after the one that mined it. Until then, its value
+    /// is not spendable.
+    ///
+    /// The mining height is read from the start of the coinbase input's
+    /// script, where consensus requires it (BIP 34). Returns `None` if the
+    /// transaction is not a coinbase or its script does not begin with a
+    /// height.
+    pub fn coinbase_maturity_height(&self) -> Option<BlockHeight> {
+        if !self.is_coinbase() {
+            return None;
+        }
+        let input = &self.transaction.transparent_bundle()?.vin[0];
+        let height = coinbase_height(&input.script_sig.0)?;
+        Some(BlockHeight::from(height.checked_add(COINBASE_MATURITY)?))
+    }
+
+    /// Records which of the transaction's Sapling notes have a nullifier in
+    /// `spent_nullifiers`.
+    pub(crate) fn mark_spent_outputs(&mut self, spent_nullifiers: &HashSet<Blob32>) {
+        self.spent_sapling_outputs = self
+            .sapling_note_data
+            .iter()
+            .flatten()
+            .filter(|(_, data)| {
+                data.nullifier()
+                    .is_some_and(|nullifier| spent_nullifiers.contains(nullifier))
+            })
+            .map(|(outpoint, _)| outpoint.vout())
+            .collect();
+    }
+
     pub fn unparsed_data(&self) -> &Data {
         &self.unparsed_data
     }
 }
 
-struct ParseTransaction(zcash_primitives::transaction::Transaction);
+/// The number of blocks a coinbase output must wait before it can be spent.
+const COINBASE_MATURITY: u32 = 100;
+
+/// The block height pushed at the start of a coinbase script: `OP_0`, one of
+/// `OP_1` to `OP_16`, or a push of a positive little-endian number.
+fn coinbase_height(script: &[u8]) -> Option<u32> {
+    match script.split_first()? {
+        (0x00, _) => Some(0),
+        (&op @ 0x51..=0x60, _) => Some(u32::from(op - 0x50)),
+        (&len @ 1..=4, rest) => {
+            let bytes = rest.get(..usize::from(len))?;
+            // The top bit of the last byte is the sign.
+            if bytes[bytes.len() - 1] & 0x80 != 0 {
+                return None;
+            }
+            let mut height = [0; 4];
+            height[..bytes.len()].copy_from_slice(bytes);
+            Some(u32::from_le_bytes(height))
+        }
+        _ => None,
+    }
+}
+
+// A transaction along with the bytes it was read from.
+struct ParseTransaction(zcash_primitives::transaction::Transaction, Data);
 impl Parse for ParseTransaction {
     fn parse(p: &mut Parser) -> Result<Self>
     where
         Self: Sized,
     {
-        Ok(ParseTransaction(
-            zcash_primitives::transaction::Transaction::read(
-                p,
-                // The consensus branch ID that we use here will be ignored; it does not direct and is
-                // not used by us after parsing; transaction serialization for v4 and below
-                // transactions do not encode it and v5 transaction parsing ignores it entirely,
-                // so it is essentially ephemeral as this parsing is only performed so that we can
-                // reencode the transaction without the remainder of the `CMerkleTx` and `CWalletTx`
-                // data.
-                zcash_primitives::consensus::BranchId::Nu5,
-            )?,
-        ))
+        let start = p.checkpoint();
+        let start_offset = p.offset();
+        let transaction = zcash_primitives::transaction::Transaction::read(
+            &mut *p,
+            // The consensus branch ID that we use here will be ignored; it does not direct and is
+            // not used by us after parsing; transaction serialization for v4 and below
+            // transactions do not encode it and v5 transaction parsing ignores it entirely,
+            // so it is essentially ephemeral as this parsing is only performed so that we can
+            // reencode the transaction without the remainder of the `CMerkleTx` and `CWalletTx`
+            // data.
+            zcash_primitives::consensus::BranchId::Nu5,
+        )?;
+        let length = p.offset() - start_offset;
+        p.restore(start);
+        let raw = Data::from_slice(p.next(length)?);
+        Ok(ParseTransaction(transaction, raw))
     }
 }
 
@@ -120,7 +326,8 @@ impl Parse for WalletTx {
     fn parse(p: &mut Parser) -> Result<Self> {
         // CTransaction
 
-        let ParseTransaction(transaction) = parse!(p, ParseTransaction, "wallet_transaction")?;
+        let ParseTransaction(transaction, raw_transaction) =
+            parse!(p, ParseTransaction, "wallet_transaction")?;
 
         // CMerkleTx
         let hash_block = parse!(p, "hash_block")?;
@@ -165,6 +372,7 @@ impl Parse for WalletTx {
         Ok(Self {
             // CTransaction
             transaction,
+            raw_transaction,
 
             // CMerkleTx
             hash_block,
@@ -182,7 +390,117 @@ impl Parse for WalletTx {
             sapling_note_data,
             orchard_tx_meta,
 
+            spent_sapling_outputs: HashSet::new(),
+
             unparsed_data,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        compact, minimal_wallet_records, parse_records, sapling_wallet_tx_value, txid,
+        v1_transaction, v4_transaction, wallet_tx_value,
+    };
+
+    #[test]
+    fn test_raw_transaction_bytes() {
+        let mut records = minimal_wallet_records();
+        records.push(("tx", vec![0x10; 32], wallet_tx_value(&v1_transaction(), Some(3))));
+        records.push(("tx", vec![0x20; 32], sapling_wallet_tx_value(&[0, 0, 0], 100)));
+        let wallet = parse_records(&records);
+        let tx = |byte| &wallet.transactions()[&txid(byte)];
+
+        assert_eq!(tx(0x10).raw_bytes(), v1_transaction());
+        assert_eq!(tx(0x20).raw_bytes(), v4_transaction());
+        for byte in [0x10, 0x20] {
+            let reparsed = zcash_primitives::transaction::Transaction::read(
+                tx(byte).raw_bytes(),
+                zcash_primitives::consensus::BranchId::Nu5,
+            )
+            .unwrap();
+            assert_eq!(reparsed.txid(), tx(byte).transaction().txid());
+        }
+    }
+
+    #[test]
+    fn test_joinsplits() {
+        // A v2 transaction with no transparent inputs or outputs and one
+        // JoinSplit moving 5,000 zatoshis out of the Sprout pool, each field
+        // filled with its own byte.
+        let mut tx = 2u32.to_le_bytes().to_vec();
+        tx.extend_from_slice(&[0, 0]);
+        tx.extend_from_slice(&0u32.to_le_bytes());
+        tx.push(1);
+        tx.extend_from_slice(&0u64.to_le_bytes());
+        tx.extend_from_slice(&5_000u64.to_le_bytes());
+        for byte in 0x01..=0x09 {
+            tx.extend_from_slice(&[byte; 32]);
+        }
+        tx.extend_from_slice(&[0x0a; 296]);
+        tx.extend_from_slice(&[0x0b; 601]);
+        tx.extend_from_slice(&[0x0c; 601]);
+        // The JoinSplit public key and signature.
+        tx.extend_from_slice(&[0x0d; 32]);
+        tx.extend_from_slice(&[0x0e; 64]);
+
+        let mut records = minimal_wallet_records();
+        records.push(("tx", vec![0x10; 32], wallet_tx_value(&tx, None)));
+        records.push(("tx", vec![0x20; 32], wallet_tx_value(&v1_transaction(), None)));
+        let wallet = parse_records(&records);
+        let tx = |byte| &wallet.transactions()[&txid(byte)];
+
+        let joinsplits = tx(0x10).joinsplits();
+        assert_eq!(joinsplits.len(), 1);
+        let joinsplit = &joinsplits[0];
+        assert_eq!(joinsplit.vpub_old(), 0);
+        assert_eq!(joinsplit.vpub_new(), 5_000);
+        assert_eq!(joinsplit.anchor().into_bytes(), [0x01; 32]);
+        assert_eq!(joinsplit.nullifiers()[1].into_bytes(), [0x03; 32]);
+        assert_eq!(joinsplit.commitments()[0].into_bytes(), [0x04; 32]);
+        assert_eq!(joinsplit.ephemeral_key().into_bytes(), [0x06; 32]);
+        assert_eq!(joinsplit.random_seed().into_bytes(), [0x07; 32]);
+        assert_eq!(joinsplit.macs()[1].into_bytes(), [0x09; 32]);
+        assert_eq!(joinsplit.proof().len(), 296);
+        assert_eq!(joinsplit.ciphertexts()[1].as_slice(), [0x0c; 601]);
+        assert!(tx(0x20).joinsplits().is_empty());
+    }
+
+    #[test]
+    fn test_coinbase_maturity_height() {
+        // A v1 coinbase: the null outpoint, a script starting with the
+        // block height, and one output.
+        let coinbase = |height_push: &[u8]| {
+            let mut tx = 1u32.to_le_bytes().to_vec();
+            tx.push(1);
+            tx.extend_from_slice(&[0; 32]);
+            tx.extend_from_slice(&u32::MAX.to_le_bytes());
+            tx.extend(compact(&[height_push, b"miner"].concat()));
+            tx.extend_from_slice(&u32::MAX.to_le_bytes());
+            tx.push(1);
+            tx.extend_from_slice(&1_250_000_000i64.to_le_bytes());
+            tx.extend(compact(&[0x51]));
+            tx.extend_from_slice(&0u32.to_le_bytes());
+            tx
+        };
+        let mut records = minimal_wallet_records();
+        // Height 2,000,000 is 0x1e8480.
+        let transactions = [
+            (0x10, coinbase(&[3, 0x80, 0x84, 0x1e])),
+            (0x11, coinbase(&[0x55])),
+            (0x12, v1_transaction()),
+        ];
+        for (byte, tx) in &transactions {
+            records.push(("tx", vec![*byte; 32], wallet_tx_value(tx, None)));
+        }
+        let wallet = parse_records(&records);
+        let tx = |byte| &wallet.transactions()[&txid(byte)];
+
+        assert!(tx(0x10).is_coinbase());
+        assert_eq!(tx(0x10).coinbase_maturity_height(), Some(2_000_100.into()));
+        assert_eq!(tx(0x11).coinbase_maturity_height(), Some(105.into()));
+        assert!(!tx(0x12).is_coinbase());
+        assert_eq!(tx(0x12).coinbase_maturity_height(), None);
+    }
+}
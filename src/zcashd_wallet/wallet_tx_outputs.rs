@@ -0,0 +1,288 @@
+use ::orchard::{
+    Action,
+    keys::{IncomingViewingKey, PreparedIncomingViewingKey},
+    note_encryption::OrchardDomain,
+    primitives::redpallas::{Signature, SpendAuth},
+};
+use ::sapling::{
+    SaplingIvk,
+    bundle::{GrothProofBytes, OutputDescription},
+    note_encryption::{Zip212Enforcement, try_sapling_note_decryption},
+};
+use zcash_address::ZcashAddress;
+use zcash_primitives::{
+    legacy::{Script, TransparentAddress},
+    transaction::components::TxOut,
+};
+use zewif::Network;
+
+use crate::{
+    migrate::primitives::address_network_from_zewif, zcashd_wallet::sapling::SaplingNoteData,
+};
+
+/// The plaintext of a shielded output, recovered by decrypting it.
+#[derive(Debug, Clone)]
+pub struct DecryptedNote<R> {
+    value: u64,
+    recipient: R,
+    memo: [u8; 512],
+}
+
+impl<R> DecryptedNote<R> {
+    /// The value of the note in zatoshis.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn recipient(&self) -> &R {
+        &self.recipient
+    }
+
+    /// The memo field, as the 512 raw bytes in the note plaintext.
+    pub fn memo(&self) -> &[u8; 512] {
+        &self.memo
+    }
+}
+
+/// A transparent output of a wallet transaction.
+#[derive(Debug, Clone, Copy)]
+pub struct TransparentOutputView<'a> {
+    index: u32,
+    output: &'a TxOut,
+}
+
+impl<'a> TransparentOutputView<'a> {
+    pub(crate) fn new(index: u32, output: &'a TxOut) -> Self {
+        Self { index, output }
+    }
+
+    /// The position of the output in the transaction.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The value of the output in zatoshis.
+    pub fn value(&self) -> u64 {
+        self.output.value.into_u64()
+    }
+
+    pub fn script_pubkey(&self) -> &'a [u8] {
+        &self.output.script_pubkey.0
+    }
+
+    /// The t-address paid by the output, or `None` if its script is neither
+    /// P2PKH nor P2SH.
+    pub fn recipient_address(&self, network: Network) -> Option<String> {
+        let address = self.output.recipient_address()?;
+        Some(encode_transparent_address(address, network))
+    }
+}
+
+/// The t-address paid by `script`, or `None` if it is neither P2PKH nor P2SH.
+pub(crate) fn script_address(script: &[u8], network: Network) -> Option<String> {
+    let address = Script(script.to_vec()).address()?;
+    Some(encode_transparent_address(address, network))
+}
+
+fn encode_transparent_address(address: TransparentAddress, network: Network) -> String {
+    let network = address_network_from_zewif(network);
+    let address = match address {
+        TransparentAddress::PublicKeyHash(hash) => {
+            ZcashAddress::from_transparent_p2pkh(network, hash)
+        }
+        TransparentAddress::ScriptHash(hash) => ZcashAddress::from_transparent_p2sh(network, hash),
+    };
+    address.to_string()
+}
+
+/// A Sapling output of a wallet transaction, with the note data zcashd
+/// recorded if the output was received by the wallet.
+#[derive(Debug, Clone, Copy)]
+pub struct SaplingOutputView<'a> {
+    index: u32,
+    output: &'a OutputDescription<GrothProofBytes>,
+    note_data: Option<&'a SaplingNoteData>,
+}
+
+impl<'a> SaplingOutputView<'a> {
+    pub(crate) fn new(
+        index: u32,
+        output: &'a OutputDescription<GrothProofBytes>,
+        note_data: Option<&'a SaplingNoteData>,
+    ) -> Self {
+        Self {
+            index,
+            output,
+            note_data,
+        }
+    }
+
+    /// The position of the output in the transaction's Sapling bundle.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The note commitment of the output.
+    pub fn cmu(&self) -> [u8; 32] {
+        self.output.cmu().to_bytes()
+    }
+
+    /// The note data zcashd keeps for outputs the wallet received, including
+    /// the incoming viewing key that decrypts the output.
+    pub fn note_data(&self) -> Option<&'a SaplingNoteData> {
+        self.note_data
+    }
+
+    /// Decrypts the output with `ivk`, returning `None` if the output was not
+    /// sent to that key.
+    pub fn decrypt(&self, ivk: &SaplingIvk) -> Option<DecryptedNote<::sapling::PaymentAddress>> {
+        // Notes from before and after ZIP 212 are both accepted, since the
+        // height of the transaction is not known here.
+        let (note, recipient, memo) = try_sapling_note_decryption(
+            &::sapling::keys::PreparedIncomingViewingKey::new(ivk),
+            self.output,
+            Zip212Enforcement::GracePeriod,
+        )?;
+        Some(DecryptedNote {
+            value: note.value().inner(),
+            recipient,
+            memo,
+        })
+    }
+}
+
+/// An Orchard action of a wallet transaction, with the incoming viewing key
+/// zcashd recorded if its output was received by the wallet.
+#[derive(Debug, Clone, Copy)]
+pub struct OrchardActionView<'a> {
+    index: u32,
+    action: &'a Action<Signature<SpendAuth>>,
+    receiving_key: Option<&'a IncomingViewingKey>,
+}
+
+impl<'a> OrchardActionView<'a> {
+    pub(crate) fn new(
+        index: u32,
+        action: &'a Action<Signature<SpendAuth>>,
+        receiving_key: Option<&'a IncomingViewingKey>,
+    ) -> Self {
+        Self {
+            index,
+            action,
+            receiving_key,
+        }
+    }
+
+    /// The position of the action in the transaction's Orchard bundle.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The commitment of the note the action creates.
+    pub fn cmx(&self) -> [u8; 32] {
+        self.action.cmx().to_bytes()
+    }
+
+    /// The nullifier of the note the action spends.
+    pub fn nullifier(&self) -> [u8; 32] {
+        self.action.nullifier().to_bytes()
+    }
+
+    /// The incoming viewing key that received the action's output, if it
+    /// belongs to the wallet.
+    pub fn receiving_key(&self) -> Option<&'a IncomingViewingKey> {
+        self.receiving_key
+    }
+
+    /// Decrypts the action's output with the wallet's receiving key, returning
+    /// `None` if the output was not received by the wallet.
+    pub fn decrypt(&self) -> Option<DecryptedNote<::orchard::Address>> {
+        self.decrypt_with(self.receiving_key?)
+    }
+
+    /// Decrypts the action's output with `ivk`, returning `None` if the output
+    /// was not sent to that key.
+    pub fn decrypt_with(
+        &self,
+        ivk: &IncomingViewingKey,
+    ) -> Option<DecryptedNote<::orchard::Address>> {
+        let (note, recipient, memo) = self.try_decryption(ivk)?;
+        Some(DecryptedNote {
+            value: note.value().inner(),
+            recipient,
+            memo,
+        })
+    }
+
+    /// The note created by the action, if `ivk` decrypts it.
+    pub(crate) fn decrypt_note_with(&self, ivk: &IncomingViewingKey) -> Option<::orchard::Note> {
+        self.try_decryption(ivk).map(|(note, _, _)| note)
+    }
+
+    fn try_decryption(
+        &self,
+        ivk: &IncomingViewingKey,
+    ) -> Option<(::orchard::Note, ::orchard::Address, [u8; 512])> {
+        zcash_note_encryption::try_note_decryption(
+            &OrchardDomain::for_action(self.action),
+            &PreparedIncomingViewingKey::new(ivk),
+            self.action,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zewif::Network;
+
+    use crate::{
+        test_support::{
+            compact, minimal_wallet_records, parse_records, sapling_wallet_tx_value, txid,
+            wallet_tx_value,
+        },
+        zcashd_wallet::{transparent::KeyId, u160},
+    };
+
+    #[test]
+    fn test_transaction_outputs() {
+        let key_hash = [0x11; 20];
+        let mut p2pkh = vec![0x76, 0xa9, 0x14];
+        p2pkh.extend_from_slice(&key_hash);
+        p2pkh.extend_from_slice(&[0x88, 0xac]);
+        // A v1 transaction paying a P2PKH script and an OP_RETURN.
+        let mut tx = 1u32.to_le_bytes().to_vec();
+        tx.extend_from_slice(&[0, 2]);
+        tx.extend_from_slice(&50_000i64.to_le_bytes());
+        tx.extend(compact(&p2pkh));
+        tx.extend_from_slice(&0i64.to_le_bytes());
+        tx.extend(compact(&[0x6a]));
+        tx.extend_from_slice(&0u32.to_le_bytes());
+
+        let mut records = minimal_wallet_records();
+        records.push(("tx", vec![0x10; 32], wallet_tx_value(&tx, None)));
+        records.push(("tx", vec![0x20; 32], sapling_wallet_tx_value(&[0, 0, 0], 100)));
+        let wallet = parse_records(&records);
+
+        let transparent_tx = &wallet.transactions()[&txid(0x10)];
+        let outputs = transparent_tx.transparent_outputs();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].index(), 0);
+        assert_eq!(outputs[0].value(), 50_000);
+        assert_eq!(outputs[0].script_pubkey(), p2pkh.as_slice());
+        let key_id = KeyId::from(u160::from_slice(&key_hash).unwrap());
+        assert_eq!(
+            outputs[0].recipient_address(Network::Main),
+            Some(key_id.to_string(Network::Main))
+        );
+        assert_eq!(outputs[1].index(), 1);
+        assert_eq!(outputs[1].recipient_address(Network::Main), None);
+        assert!(transparent_tx.sapling_outputs().is_empty());
+        assert!(transparent_tx.orchard_actions().is_empty());
+
+        // Note data alone does not produce outputs the transaction lacks.
+        let sapling_tx = &wallet.transactions()[&txid(0x20)];
+        assert!(sapling_tx.sapling_note_data().is_some());
+        assert!(sapling_tx.transparent_outputs().is_empty());
+        assert!(sapling_tx.sapling_outputs().is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeSet;
+
+use zcash_protocol::consensus::NetworkConstants;
+use zewif::Network;
+
+use crate::{ZcashdWallet, migrate::primitives::address_encoding_network};
+
+/// The public key material of a wallet, enough to set up a watch-only wallet
+/// elsewhere without exposing anything that can spend.
+///
+/// Keys are held in their encoded forms, each list sorted and free of
+/// duplicates:
+///
+/// - unified full viewing keys as `uview` strings,
+/// - Sapling extended full viewing keys as `zxviews` strings, from the
+///   wallet's spending keys, imported viewing keys and `sapextfvk` records,
+/// - transparent public keys as hex, and
+/// - watch-only scripts as hex.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WatchOnlyExport {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::network_name"))]
+    network: Network,
+    unified_full_viewing_keys: Vec<String>,
+    sapling_full_viewing_keys: Vec<String>,
+    transparent_pubkeys: Vec<String>,
+    watch_only_scripts: Vec<String>,
+}
+
+impl WatchOnlyExport {
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn unified_full_viewing_keys(&self) -> &[String] {
+        &self.unified_full_viewing_keys
+    }
+
+    pub fn sapling_full_viewing_keys(&self) -> &[String] {
+        &self.sapling_full_viewing_keys
+    }
+
+    pub fn transparent_pubkeys(&self) -> &[String] {
+        &self.transparent_pubkeys
+    }
+
+    pub fn watch_only_scripts(&self) -> &[String] {
+        &self.watch_only_scripts
+    }
+}
+
+#[cfg(feature = "serde")]
+impl WatchOnlyExport {
+    pub fn to_json(&self) -> crate::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> crate::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+impl ZcashdWallet {
+    /// The wallet's viewing keys and public keys, with no spending material.
+    pub fn to_watch_only_export(&self) -> WatchOnlyExport {
+        let params = address_encoding_network(self.network());
+
+        let unified_full_viewing_keys: BTreeSet<_> = self
+            .unified_accounts()
+            .full_viewing_keys
+            .values()
+            .map(|ufvk| ufvk.encode(&params))
+            .collect();
+
+        #[allow(deprecated)]
+        let from_spending_keys = self
+            .sapling_keys()
+            .keypairs()
+            .map(|key| key.extsk().to_extended_full_viewing_key());
+        let sapling_full_viewing_keys: BTreeSet<_> = from_spending_keys
+            .chain(self.viewing_keys().iter().cloned())
+            .chain(self.sapling_extended_fvks().values().cloned())
+            .map(|extfvk| {
+                zcash_keys::encoding::encode_extended_full_viewing_key(
+                    params.hrp_sapling_extended_full_viewing_key(),
+                    &extfvk,
+                )
+            })
+            .collect();
+
+        let transparent_pubkeys: BTreeSet<_> = self
+            .keys()
+            .keypairs()
+            .map(|keypair| keypair.pubkey())
+            .chain(
+                self.wallet_keys()
+                    .into_iter()
+                    .flat_map(|keys| keys.keypairs().map(|key| key.pubkey())),
+            )
+            .map(|pubkey| hex::encode(pubkey.as_slice()))
+            .collect();
+
+        let watch_only_scripts: BTreeSet<_> = self
+            .watch_only_scripts()
+            .iter()
+            .map(|script| hex::encode(script.as_ref()))
+            .collect();
+
+        WatchOnlyExport {
+            network: self.network(),
+            unified_full_viewing_keys: unified_full_viewing_keys.into_iter().collect(),
+            sapling_full_viewing_keys: sapling_full_viewing_keys.into_iter().collect(),
+            transparent_pubkeys: transparent_pubkeys.into_iter().collect(),
+            watch_only_scripts: watch_only_scripts.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{minimal_wallet_records, parse_records, sapling_key_records};
+
+    #[test]
+    fn test_watch_only_export() {
+        let extsk = ::sapling::zip32::ExtendedSpendingKey::master(&[0x07; 32]);
+        let mut records = minimal_wallet_records();
+        records.extend(sapling_key_records(&extsk));
+        let wallet = parse_records(&records);
+
+        let export = wallet.to_watch_only_export();
+        assert_eq!(export.network(), wallet.network());
+        assert!(export.unified_full_viewing_keys().is_empty());
+        assert_eq!(export.sapling_full_viewing_keys().len(), 1);
+        assert!(export.sapling_full_viewing_keys()[0].starts_with("zxview"));
+        let keypair = wallet.keys().keypairs().next().unwrap();
+        assert_eq!(
+            export.transparent_pubkeys(),
+            [hex::encode(keypair.pubkey().as_slice())]
+        );
+
+        // Nothing that can spend is exported.
+        let secrets = [
+            hex::encode(keypair.privkey().as_slice()),
+            hex::encode(extsk.to_bytes()),
+        ];
+        let debug = format!("{export:?}");
+        for secret in &secrets {
+            assert!(!debug.contains(secret.as_str()));
+        }
+
+        #[cfg(feature = "serde")]
+        {
+            let json = export.to_json().unwrap();
+            for secret in &secrets {
+                assert!(!json.contains(secret.as_str()));
+            }
+            let reloaded = super::WatchOnlyExport::from_json(&json).unwrap();
+            assert_eq!(reloaded, export);
+        }
+    }
+}
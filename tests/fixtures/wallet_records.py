@@ -0,0 +1,40 @@
+import hashlib, struct
+
+def compact(b):
+    assert len(b) < 0xfd
+    return bytes([len(b)]) + b
+
+def key(name, data=b""):
+    return compact(name.encode()) + data
+
+def sha256d(b):
+    return hashlib.sha256(hashlib.sha256(b).digest()).digest()
+
+ops = []
+def put(k, v):
+    ops.append(f"put {k.hex()} {v.hex()}")
+
+pubkey = b"\x02" + b"\x11" * 32
+privkey = b"\x5c" * 214
+put(key("key", compact(pubkey)), compact(privkey) + sha256d(pubkey + privkey))
+put(key("keymeta", compact(pubkey)),
+    struct.pack("<iq", 10, 1_600_000_000) + compact(b"m/0'/0'/0'") + b"\x33" * 32)
+put(key("defaultkey"), compact(pubkey))
+put(key("version"), struct.pack("<I", 5_000_150))
+put(key("minversion"), struct.pack("<I", 5_000_150))
+put(key("bestblock"), struct.pack("<I", 5_000_150) + b"\x01" + b"\x44" * 32)
+put(key("networkinfo"), compact(b"Zcash") + compact(b"main"))
+put(key("witnesscachesize"), struct.pack("<q", 0))
+for i, name in enumerate([b"alice", b"bob", b"carol"]):
+    address = b"t1" + bytes([0x61 + i]) * 33
+    put(key("name", compact(address)), compact(name))
+    put(key("purpose", compact(address)), compact(b"receive"))
+# Enough key pool entries to split the leaves under an internal page.
+for index in range(1, 101):
+    put(key("pool", struct.pack("<q", index)),
+        struct.pack("<IQ", 5_000_150, 1_600_000_000 + index) + compact(pubkey))
+# A transaction too large for a leaf page, stored on overflow pages.
+put(key("tx", b"\x55" * 32), bytes(range(256)) * 12)
+# A removed address book entry.
+ops.append(f"del {key('name', compact(b't1' + b'b' * 33)).hex()}")
+print("\n".join(ops))
@@ -0,0 +1,60 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <stdint.h>
+
+typedef struct {
+    void *data; uint32_t size, ulen, dlen, doff; void *app_data; uint32_t flags;
+} DBT;
+typedef void DB;
+
+int db_create(DB **, void *, uint32_t);
+int __db_set_pagesize(DB *, uint32_t);
+int __db_open_pp(DB *, void *, const char *, const char *, int, uint32_t, int);
+int __db_put_pp(DB *, void *, DBT *, DBT *, uint32_t);
+int __db_del_pp(DB *, void *, DBT *, uint32_t);
+int __db_close_pp(DB *, uint32_t);
+int __db_dump_pp(DB *, const char *, int (*)(void *, const void *), void *, int, int);
+
+static int print_cb(void *handle, const void *str) {
+    return fputs((const char *)str, (FILE *)handle) == EOF ? -1 : 0;
+}
+
+static size_t unhex(const char *hex, unsigned char *out) {
+    size_t n = strlen(hex) / 2;
+    for (size_t i = 0; i < n; i++) sscanf(hex + 2 * i, "%2hhx", &out[i]);
+    return n;
+}
+
+#define CHECK(x) do { int r = (x); if (r) { fprintf(stderr, "%s: %d\n", #x, r); return 1; } } while (0)
+
+/* usage: write_wallet wallet.dat wallet.dump < records, where each line is
+   "put <keyhex> <valuehex>" or "del <keyhex>". */
+int main(int argc, char **argv) {
+    DB *db;
+    CHECK(db_create(&db, NULL, 0));
+    CHECK(__db_set_pagesize(db, 512));
+    CHECK(__db_open_pp(db, NULL, argv[1], "main", 1 /* DB_BTREE */, 1 /* DB_CREATE */, 0600));
+    static char op[8], khex[1 << 16], vhex[1 << 16];
+    static unsigned char k[1 << 15], v[1 << 15];
+    while (scanf("%7s %65535s", op, khex) == 2) {
+        DBT key = {0}, value = {0};
+        key.data = k; key.size = unhex(khex, k);
+        if (strcmp(op, "put") == 0) {
+            scanf("%65535s", vhex);
+            value.data = v; value.size = unhex(vhex, v);
+            CHECK(__db_put_pp(db, NULL, &key, &value, 0));
+        } else {
+            CHECK(__db_del_pp(db, NULL, &key, 0));
+        }
+    }
+    CHECK(__db_close_pp(db, 0));
+
+    CHECK(db_create(&db, NULL, 0));
+    CHECK(__db_open_pp(db, NULL, argv[1], "main", 1, 0x400 /* DB_RDONLY */, 0));
+    FILE *out = fopen(argv[2], "w");
+    CHECK(__db_dump_pp(db, "main", print_cb, out, 0, 0));
+    fclose(out);
+    CHECK(__db_close_pp(db, 0));
+    return 0;
+}
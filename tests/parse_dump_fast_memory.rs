@@ -0,0 +1,151 @@
+//! Checks that `ZcashdParser::parse_dump_fast` does not copy the dump's key
+//! set, using an allocator that records the peak number of live bytes.
+//!
+//! This is the only test in its binary, so no other test's allocations are
+//! counted.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt::Write,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use sha2::{Digest, Sha256};
+use zewif_zcashd::{ParseMode, ZcashdDump, ZcashdParser};
+
+struct CountingAllocator;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let live = LIVE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(live, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const PADDING_RECORDS: u32 = 200_000;
+
+/// The most `parse_dump_fast` may allocate beyond the dump itself. The
+/// wallet holds a handful of records, while a copy of the key set would take
+/// several megabytes.
+const PEAK_BOUND: usize = 1 << 20;
+
+fn compact(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![u8::try_from(bytes.len()).unwrap()];
+    encoded.extend_from_slice(bytes);
+    encoded
+}
+
+fn record(keyname: &str, key_data: &[u8], value: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut key = compact(keyname.as_bytes());
+    key.extend_from_slice(key_data);
+    (key, value.to_vec())
+}
+
+/// The records a wallet needs to parse, holding one transparent key and one
+/// address book entry.
+fn wallet_records() -> Vec<(Vec<u8>, Vec<u8>)> {
+    let address = b"t1UYsZVJkLPeMjxEtACvSxfWuNmddpWfxzs";
+    let mut pubkey = vec![0x02];
+    pubkey.extend_from_slice(&[0x11; 32]);
+    let privkey = [0x5c; 214];
+
+    let mut key = compact(&privkey);
+    key.extend_from_slice(&Sha256::digest(Sha256::digest(
+        [&pubkey[..], &privkey].concat(),
+    )));
+    let mut keymeta = 1i32.to_le_bytes().to_vec();
+    keymeta.extend_from_slice(&0u64.to_le_bytes());
+    let mut bestblock = 5_000_150u32.to_le_bytes().to_vec();
+    bestblock.push(1);
+    bestblock.extend_from_slice(&[0x44; 32]);
+    let mut network_info = compact(b"Zcash");
+    network_info.extend(compact(b"main"));
+    // Note state v1, no checkpoint, an empty v3 bridge tree and no notes.
+    let mut tree = vec![0u8; 4];
+    tree.extend_from_slice(&[1, 0, 3, 0, 0, 0, 0]);
+    tree.extend_from_slice(&100u64.to_le_bytes());
+    tree.push(0);
+    let mut pool_entry = 5_000_150u32.to_le_bytes().to_vec();
+    pool_entry.extend_from_slice(&1_600_000_000u64.to_le_bytes());
+    pool_entry.extend(compact(&pubkey));
+    let version = 5_000_150u32.to_le_bytes();
+
+    vec![
+        record("bestblock", &[], &bestblock),
+        record("defaultkey", &[], &compact(&pubkey)),
+        record("key", &compact(&pubkey), &key),
+        record("keymeta", &compact(&pubkey), &keymeta),
+        record("minversion", &[], &version),
+        record("name", &compact(address), &compact(b"savings")),
+        record("networkinfo", &[], &network_info),
+        record("orchard_note_commitment_tree", &[], &tree),
+        record("pool", &1i64.to_le_bytes(), &pool_entry),
+        record("purpose", &compact(address), &compact(b"receive")),
+        record("version", &[], &version),
+        record("witnesscachesize", &[], &0i64.to_le_bytes()),
+    ]
+}
+
+/// A `db_dump` of the wallet records followed by `PADDING_RECORDS` records
+/// under a keyname the parser does not read.
+fn synthetic_dump() -> ZcashdDump {
+    let padding =
+        (0..PADDING_RECORDS).map(|index| record("padding", &index.to_le_bytes(), &[0x00]));
+    let mut text = String::from("VERSION=3\nformat=bytevalue\ntype=btree\nHEADER=END\n");
+    for (key, value) in wallet_records().into_iter().chain(padding) {
+        writeln!(text, " {}\n {}", hex::encode(key), hex::encode(value)).unwrap();
+    }
+    text.push_str("DATA=END\n");
+    ZcashdDump::from_dump_str(&text).unwrap()
+}
+
+/// The peak number of bytes allocated by `f` beyond those live when it was
+/// called.
+fn peak_allocation<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let baseline = LIVE.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let result = f();
+    (result, PEAK.load(Ordering::Relaxed) - baseline)
+}
+
+#[test]
+fn test_parse_dump_fast_peak_allocation_is_bounded() {
+    let dump = synthetic_dump();
+    assert_eq!(
+        dump.records().len(),
+        wallet_records().len() + PADDING_RECORDS as usize
+    );
+
+    let (wallet, fast_peak) =
+        peak_allocation(|| ZcashdParser::parse_dump_fast(&dump, ParseMode::Lenient).unwrap());
+    assert_eq!(wallet.address_names().len(), 1);
+    drop(wallet);
+    assert!(
+        fast_peak < PEAK_BOUND,
+        "parse_dump_fast allocated {fast_peak} bytes at its peak"
+    );
+
+    let (result, tracked_peak) =
+        peak_allocation(|| ZcashdParser::parse_dump(&dump, ParseMode::Lenient).unwrap());
+    assert!(result.1.len() >= PADDING_RECORDS as usize);
+    drop(result);
+    assert!(
+        tracked_peak > 4 * fast_peak,
+        "parse_dump allocated {tracked_peak} bytes at its peak, parse_dump_fast {fast_peak}"
+    );
+}